@@ -1,15 +1,12 @@
 //! 使用真实市场测试 WebSocket 连接
 
-use std::sync::Arc;
-use tokio::time::{sleep, Duration};
-
 #[tokio::main]
 async fn main() {
     println!("🧪 Testing WebSocket with real Polymarket market...\n");
     
     // 使用一个活跃的市场进行测试
     // 这里使用 Trump 相关的市场（当前活跃）
-    let test_token = "0xbd31dc8a20211944f6b70f31557b47316606a77d"; // 示例 token
+    let _test_token = "0xbd31dc8a20211944f6b70f31557b47316606a77d"; // 示例 token
     
     println!("Note: Currently no BTC 5-minute markets are active on Polymarket.");
     println!("The bot will automatically detect and trade when they become available.\n");
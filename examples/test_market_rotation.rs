@@ -1,12 +1,9 @@
 //! 测试 BTC 5 分钟市场自动切换和 WebSocket 订阅
 
 use std::sync::Arc;
-use tokio::sync::RwLock;
 use tokio::time::{sleep, Duration};
 
-// 引入模块
-mod websocket;
-use websocket::PolymarketWebSocket;
+use polymarket_pro::PolymarketWebSocket;
 
 #[tokio::main]
 async fn main() {
@@ -9,7 +9,7 @@ use crate::api::ApiError;
 // Re-export Side from polymarket_client_sdk for consistency
 pub use polymarket_client_sdk::clob::types::Side;
 
-const CLOB_API_URL: &str = "https://clob.polymarket.com";
+pub(crate) const CLOB_API_URL: &str = "https://clob.polymarket.com";
 
 /// Order status
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -91,6 +91,12 @@ impl ClobApiClient {
         }
     }
     
+    /// Override the API base URL (e.g. a local mock server)
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+    
     /// Get headers with API key if available
     fn get_headers(&self) -> reqwest::header::HeaderMap {
         let mut headers = reqwest::header::HeaderMap::new();
@@ -227,10 +233,15 @@ pub struct Position {
     pub avg_price: f64,
 }
 
+/// Mock place_order handler
+#[cfg(test)]
+type PlaceOrderFn = Box<dyn Fn(Order) -> Result<OrderResponse, ApiError> + Send + Sync>;
+
 /// Mock CLOB client for testing
 #[cfg(test)]
+#[derive(Default)]
 pub struct MockClobClient {
-    place_order_fn: Option<Box<dyn Fn(Order) -> Result<OrderResponse, ApiError> + Send + Sync>>,
+    place_order_fn: Option<PlaceOrderFn>,
 }
 
 #[cfg(test)]
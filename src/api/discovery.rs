@@ -0,0 +1,235 @@
+//! Market series discovery
+//! Resolves the current and next market of each configured up/down series via Gamma
+
+use chrono::{DateTime, Utc};
+use tracing::{debug, info, warn};
+
+use crate::api::gamma::{GammaApiClient, Market};
+use crate::api::ApiError;
+use crate::config::MarketSeriesConfig;
+
+/// Start of the slot containing `timestamp`
+pub fn slot_start(slot_seconds: u64, timestamp: i64) -> i64 {
+    let slot = slot_seconds as i64;
+    timestamp.div_euclid(slot) * slot
+}
+
+/// Gamma slug for a series slot, e.g. `btc-updown-5m-1760000100`
+pub fn series_slug(series: &MarketSeriesConfig, slot_start: i64) -> String {
+    format!("{}-{}", series.slug, slot_start)
+}
+
+/// A resolved market of a series, with its CLOB tokens
+#[derive(Debug, Clone)]
+pub struct SeriesMarket {
+    /// Series slug prefix
+    pub series: String,
+    pub slot_start: i64,
+    pub slot_seconds: u64,
+    pub slug: String,
+    pub condition_id: String,
+    pub up_token: String,
+    pub down_token: String,
+    pub end_time: DateTime<Utc>,
+    pub market: Market,
+}
+
+impl SeriesMarket {
+    /// Build from a Gamma market; None if it has no UP/DOWN token pair
+    pub fn from_market(series: &MarketSeriesConfig, slot_start: i64, market: Market) -> Option<Self> {
+        let (up_token, down_token) = market.up_down_tokens()?;
+        // Gamma's endDate is authoritative; the slot boundary is the fallback
+        let end_time = market.end_time().unwrap_or_else(|| {
+            DateTime::from_timestamp(slot_start + series.slot_seconds as i64, 0).unwrap_or_else(Utc::now)
+        });
+        Some(Self {
+            series: series.slug.clone(),
+            slot_start,
+            slot_seconds: series.slot_seconds,
+            slug: market.slug.clone(),
+            condition_id: market.condition_id.clone(),
+            up_token,
+            down_token,
+            end_time,
+            market,
+        })
+    }
+
+    /// Start of the market's slot
+    pub fn starts_at(&self) -> DateTime<Utc> {
+        DateTime::from_timestamp(self.slot_start, 0).unwrap_or_default()
+    }
+
+    /// Seconds until the market ends (negative once expired)
+    pub fn seconds_to_expiry(&self, now: DateTime<Utc>) -> i64 {
+        self.end_time.signed_duration_since(now).num_seconds()
+    }
+}
+
+/// Current and next market of one series
+#[derive(Debug, Clone)]
+pub struct SeriesSlots {
+    pub series: MarketSeriesConfig,
+    pub current: Option<SeriesMarket>,
+    pub next: Option<SeriesMarket>,
+}
+
+/// Discovery service for all configured series
+pub struct MarketDiscovery {
+    gamma: GammaApiClient,
+    series: Vec<MarketSeriesConfig>,
+}
+
+impl MarketDiscovery {
+    /// Create discovery service over the configured series
+    pub fn new(gamma: GammaApiClient, series: Vec<MarketSeriesConfig>) -> Self {
+        Self { gamma, series }
+    }
+
+    /// Configured series
+    pub fn series(&self) -> &[MarketSeriesConfig] {
+        &self.series
+    }
+
+    /// Resolve the market of a series at a slot (None if missing or not tradable)
+    pub async fn resolve(
+        &self,
+        series: &MarketSeriesConfig,
+        slot_start: i64,
+    ) -> Result<Option<SeriesMarket>, ApiError> {
+        let slug = series_slug(series, slot_start);
+        let Some(market) = self.gamma.fetch_market_by_slug(&slug).await? else {
+            debug!("No Gamma market for {}", slug);
+            return Ok(None);
+        };
+        if !market.is_tradable() {
+            debug!("Market {} is not tradable (active={}, closed={})", slug, market.active, market.closed);
+            return Ok(None);
+        }
+        let resolved = SeriesMarket::from_market(series, slot_start, market);
+        if resolved.is_none() {
+            warn!("⚠️ Market {} has no UP/DOWN token pair", slug);
+        }
+        Ok(resolved)
+    }
+
+    /// Market trading now: the current slot, or the previous one if it is still open
+    pub async fn current_market(
+        &self,
+        series: &MarketSeriesConfig,
+        now: DateTime<Utc>,
+    ) -> Result<Option<SeriesMarket>, ApiError> {
+        let current = slot_start(series.slot_seconds, now.timestamp());
+        if let Some(market) = self.resolve(series, current).await? {
+            return Ok(Some(market));
+        }
+        let previous = current - series.slot_seconds as i64;
+        Ok(self
+            .resolve(series, previous)
+            .await?
+            .filter(|m| m.seconds_to_expiry(now) > 0))
+    }
+
+    /// Market of the slot after the current one
+    pub async fn next_market(
+        &self,
+        series: &MarketSeriesConfig,
+        now: DateTime<Utc>,
+    ) -> Result<Option<SeriesMarket>, ApiError> {
+        let next = slot_start(series.slot_seconds, now.timestamp()) + series.slot_seconds as i64;
+        self.resolve(series, next).await
+    }
+
+    /// Current and next market for every series; per-series errors are logged, not fatal
+    pub async fn discover(&self, now: DateTime<Utc>) -> Vec<SeriesSlots> {
+        let mut slots = Vec::with_capacity(self.series.len());
+        for series in &self.series {
+            let current = self.current_market(series, now).await.unwrap_or_else(|e| {
+                warn!("⚠️ Failed to resolve current {} market: {}", series.slug, e);
+                None
+            });
+            let next = self.next_market(series, now).await.unwrap_or_else(|e| {
+                warn!("⚠️ Failed to resolve next {} market: {}", series.slug, e);
+                None
+            });
+            info!(
+                "🔍 {}: current={} next={}",
+                series.slug,
+                current.as_ref().map(|m| m.slug.as_str()).unwrap_or("-"),
+                next.as_ref().map(|m| m.slug.as_str()).unwrap_or("-")
+            );
+            slots.push(SeriesSlots { series: series.clone(), current, next });
+        }
+        slots
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn series(slug: &str, slot_seconds: u64) -> MarketSeriesConfig {
+        MarketSeriesConfig { slug: slug.to_string(), slot_seconds, spot_symbol: None }
+    }
+
+    fn gamma_market(slug: &str, end: &str, active: bool) -> serde_json::Value {
+        serde_json::json!([{
+            "conditionId": format!("0x{}", slug.len()),
+            "question": "Up or Down?",
+            "slug": slug,
+            "outcomes": "[\"Up\", \"Down\"]",
+            "clobTokenIds": "[\"111\", \"222\"]",
+            "endDate": end,
+            "active": active,
+            "closed": !active
+        }])
+    }
+
+    #[test]
+    fn test_slot_math() {
+        assert_eq!(slot_start(300, 1_760_000_123), 1_760_000_100);
+        assert_eq!(slot_start(3600, 1_760_000_123), 1_759_996_800);
+        assert_eq!(series_slug(&series("eth-updown-15m", 900), 1_759_999_500), "eth-updown-15m-1759999500");
+    }
+
+    #[tokio::test]
+    async fn test_discover_current_and_next() {
+        let server = MockServer::start().await;
+        let eth = series("eth-updown-15m", 900);
+        // 2025-10-09T08:53:20Z lies in the 08:45 slot (1759999500)
+        let now = DateTime::from_timestamp(1_760_000_000, 0).unwrap();
+
+        Mock::given(method("GET"))
+            .and(path("/markets"))
+            .and(query_param("slug", "eth-updown-15m-1759999500"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(gamma_market(
+                "eth-updown-15m-1759999500", "2025-10-09T09:00:00Z", true)))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/markets"))
+            .and(query_param("slug", "eth-updown-15m-1760000400"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(gamma_market(
+                "eth-updown-15m-1760000400", "2025-10-09T09:15:00Z", true)))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/markets"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([])))
+            .mount(&server)
+            .await;
+
+        let gamma = GammaApiClient::new().with_base_url(&server.uri());
+        let discovery = MarketDiscovery::new(gamma, vec![eth, series("sol-updown-1h", 3600)]);
+        let slots = discovery.discover(now).await;
+
+        let current = slots[0].current.as_ref().unwrap();
+        assert_eq!(current.up_token, "111");
+        assert_eq!(current.down_token, "222");
+        assert_eq!(current.seconds_to_expiry(now), 400);
+        assert_eq!(slots[0].next.as_ref().unwrap().slot_start, 1_760_000_400);
+        assert!(slots[1].current.is_none() && slots[1].next.is_none());
+    }
+}
@@ -1,10 +1,11 @@
 //! Gamma API client for market data
 
+use chrono::{DateTime, Utc};
 use reqwest::Client;
 use serde::{Deserialize, Deserializer, Serialize};
 use crate::api::ApiError;
 
-const GAMMA_API_URL: &str = "https://gamma-api.polymarket.com";
+pub(crate) const GAMMA_API_URL: &str = "https://gamma-api.polymarket.com";
 
 /// Gamma API client
 pub struct GammaApiClient {
@@ -27,6 +28,12 @@ impl GammaApiClient {
         }
     }
     
+    /// Override the API base URL (e.g. a local mock server)
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+    
     /// Fetch active markets
     pub async fn fetch_active_markets(
         &self,
@@ -71,6 +78,29 @@ impl GammaApiClient {
         Ok(markets)
     }
     
+    /// Fetch a single market by slug (None if Gamma has no such market)
+    pub async fn fetch_market_by_slug(
+        &self,
+        slug: &str,
+    ) -> Result<Option<Market>, ApiError> {
+        let url = format!("{}/markets", self.base_url);
+        let response = self.client
+            .get(&url)
+            .query(&[("slug", slug)])
+            .send()
+            .await?;
+        
+        if !response.status().is_success() {
+            return Err(ApiError::ApiError {
+                status: response.status().as_u16(),
+                message: response.text().await.unwrap_or_default(),
+            });
+        }
+        
+        let markets: Vec<Market> = response.json().await?;
+        Ok(markets.into_iter().next())
+    }
+    
     /// Fetch markets by condition IDs
     pub async fn fetch_markets_by_ids(
         &self,
@@ -96,44 +126,86 @@ impl Default for GammaApiClient {
 }
 
 /// Market data from Gamma API
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct Market {
     #[serde(rename = "conditionId")]
     pub condition_id: String,
     pub question: String,
     pub slug: String,
+    #[serde(default)]
     pub description: String,
+    /// Outcome names, e.g. ["Up", "Down"] (Gamma sends a JSON-encoded string)
+    #[serde(deserialize_with = "deserialize_string_vec")]
     pub outcomes: Vec<String>,
-    #[serde(rename = "outcomePrices", deserialize_with = "deserialize_string_f64_vec")]
+    #[serde(rename = "outcomePrices", default, deserialize_with = "deserialize_string_f64_vec")]
     pub outcome_prices: Vec<f64>,
-    #[serde(deserialize_with = "deserialize_string_f64")]
+    #[serde(default, deserialize_with = "deserialize_string_f64")]
     pub volume: f64,
-    #[serde(deserialize_with = "deserialize_string_f64")]
+    #[serde(default, deserialize_with = "deserialize_string_f64")]
     pub liquidity: f64,
-    #[serde(rename = "startDate")]
+    #[serde(rename = "startDate", default)]
     pub start_date: String,
-    #[serde(rename = "endDate")]
+    #[serde(rename = "endDate", default)]
     pub end_date: String,
+    #[serde(default)]
     pub resolved: bool,
+    #[serde(default)]
     pub resolution: Option<String>,
+    /// CLOB token IDs in the same order as `outcomes`
+    #[serde(rename = "clobTokenIds", default, deserialize_with = "deserialize_string_vec")]
+    pub clob_token_ids: Vec<String>,
+    #[serde(default)]
+    pub active: bool,
+    #[serde(default)]
+    pub closed: bool,
+}
+
+/// String or number field
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StringOrNumber {
+    String(String),
+    Number(f64),
+}
+
+/// Array field that Gamma may also send as a JSON-encoded string
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ListOrEncoded<T> {
+    List(Vec<T>),
+    Encoded(String),
+}
+
+/// Deserialize an array or JSON-encoded array of strings
+fn deserialize_string_vec<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match ListOrEncoded::<String>::deserialize(deserializer)? {
+        ListOrEncoded::List(vec) => Ok(vec),
+        ListOrEncoded::Encoded(s) if s.trim().is_empty() => Ok(Vec::new()),
+        ListOrEncoded::Encoded(s) => serde_json::from_str(&s).map_err(serde::de::Error::custom),
+    }
 }
 
-/// Deserialize string to f64
+/// Deserialize string (or number) to f64
 fn deserialize_string_f64<'de, D>(deserializer: D) -> Result<f64, D::Error>
 where
     D: Deserializer<'de>,
 {
-    let s: String = Deserialize::deserialize(deserializer)?;
-    s.parse::<f64>().map_err(serde::de::Error::custom)
+    match StringOrNumber::deserialize(deserializer)? {
+        StringOrNumber::String(s) => s.parse::<f64>().map_err(serde::de::Error::custom),
+        StringOrNumber::Number(n) => Ok(n),
+    }
 }
 
-/// Deserialize Vec<String> to Vec<f64>
+/// Deserialize Vec<String> (or its JSON-encoded form) to Vec<f64>
 fn deserialize_string_f64_vec<'de, D>(deserializer: D) -> Result<Vec<f64>, D::Error>
 where
     D: Deserializer<'de>,
 {
-    let vec: Vec<String> = Deserialize::deserialize(deserializer)?;
-    vec.into_iter()
+    deserialize_string_vec(deserializer)?
+        .into_iter()
         .map(|s| s.parse::<f64>().map_err(serde::de::Error::custom))
         .collect()
 }
@@ -148,6 +220,72 @@ impl Market {
     pub fn get_price(&self, outcome_index: usize) -> Option<f64> {
         self.outcome_prices.get(outcome_index).copied()
     }
+    
+    /// Open for trading (active and not closed)
+    pub fn is_tradable(&self) -> bool {
+        self.active && !self.closed
+    }
+    
+    /// CLOB token ID for an outcome name (case-insensitive)
+    pub fn token_for_outcome(&self, outcome: &str) -> Option<&str> {
+        self.outcomes
+            .iter()
+            .position(|o| o.eq_ignore_ascii_case(outcome))
+            .and_then(|i| self.clob_token_ids.get(i))
+            .map(String::as_str)
+    }
+    
+    /// (UP, DOWN) token IDs - matched by outcome name, falling back to token order
+    pub fn up_down_tokens(&self) -> Option<(String, String)> {
+        let up = self.token_for_outcome("Up").or_else(|| self.token_for_outcome("Yes"));
+        let down = self.token_for_outcome("Down").or_else(|| self.token_for_outcome("No"));
+        match (up, down) {
+            (Some(up), Some(down)) => Some((up.to_string(), down.to_string())),
+            _ if self.clob_token_ids.len() >= 2 => {
+                Some((self.clob_token_ids[0].clone(), self.clob_token_ids[1].clone()))
+            }
+            _ => None,
+        }
+    }
+    
+    /// Payout of a token once the market has resolved (outcome prices settle at 0 and 1)
+    pub fn settlement_price(&self, token_id: &str) -> Option<f64> {
+        let resolved = self.closed
+            && !self.outcome_prices.is_empty()
+            && self.outcome_prices.iter().all(|p| *p == 0.0 || *p == 1.0);
+        if !resolved {
+            return None;
+        }
+        let index = self.clob_token_ids.iter().position(|t| t == token_id)?;
+        self.get_price(index)
+    }
+
+    /// Parsed end time
+    pub fn end_time(&self) -> Option<DateTime<Utc>> {
+        parse_gamma_time(&self.end_date)
+    }
+}
+
+/// Parse a Gamma timestamp (RFC3339 or a few common variants)
+pub fn parse_gamma_time(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    
+    let formats = [
+        "%Y-%m-%dT%H:%M:%S%.3fZ",
+        "%Y-%m-%dT%H:%M:%SZ",
+        "%Y-%m-%d %H:%M:%S",
+    ];
+    for format in &formats {
+        if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(value, format) {
+            return Some(DateTime::from_naive_utc_and_offset(dt, Utc));
+        }
+    }
+    chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|dt| DateTime::from_naive_utc_and_offset(dt, Utc))
 }
 
 #[cfg(test)]
@@ -169,6 +307,7 @@ mod tests {
             end_date: "2024-12-31".to_string(),
             resolved: false,
             resolution: None,
+            ..Market::default()
         };
         assert!(market.is_active());
     }
@@ -188,10 +327,29 @@ mod tests {
             end_date: "2024-12-31".to_string(),
             resolved: true,
             resolution: Some("Yes".to_string()),
+            ..Market::default()
         };
         assert!(!market.is_active());
     }
 
+    #[test]
+    fn test_settlement_price() {
+        let mut market = Market {
+            outcomes: vec!["Up".to_string(), "Down".to_string()],
+            outcome_prices: vec![0.62, 0.38],
+            clob_token_ids: vec!["1".to_string(), "2".to_string()],
+            closed: true,
+            ..Market::default()
+        };
+        // Closed but not resolved yet
+        assert_eq!(market.settlement_price("1"), None);
+
+        market.outcome_prices = vec![0.0, 1.0];
+        assert_eq!(market.settlement_price("1"), Some(0.0));
+        assert_eq!(market.settlement_price("2"), Some(1.0));
+        assert_eq!(market.settlement_price("3"), None);
+    }
+
     #[test]
     fn test_get_price() {
         let market = Market {
@@ -207,6 +365,7 @@ mod tests {
             end_date: "2024-12-31".to_string(),
             resolved: false,
             resolution: None,
+            ..Market::default()
         };
         assert_eq!(market.get_price(0), Some(0.7));
         assert_eq!(market.get_price(1), Some(0.3));
@@ -219,6 +378,12 @@ mod tests {
         assert_eq!(client.base_url, GAMMA_API_URL);
     }
 
+    #[test]
+    fn test_client_with_base_url() {
+        let client = GammaApiClient::new().with_base_url("http://127.0.0.1:9000/");
+        assert_eq!(client.base_url, "http://127.0.0.1:9000");
+    }
+
     #[test]
     fn test_deserialize_string_f64() {
         // Test the deserialize_string_f64 function via Market
@@ -264,4 +429,29 @@ mod tests {
         assert_eq!(market.volume, 1000.5);
         assert_eq!(market.liquidity, 500.25);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_deserialize_gamma_encoded_fields() {
+        // Gamma sends list fields as JSON-encoded strings and omits resolution fields
+        let json = r#"{
+            "conditionId": "0xabc",
+            "question": "Bitcoin Up or Down?",
+            "slug": "btc-updown-5m-1760000000",
+            "outcomes": "[\"Up\", \"Down\"]",
+            "outcomePrices": "[\"0.52\", \"0.48\"]",
+            "clobTokenIds": "[\"111\", \"222\"]",
+            "volume": 1234.5,
+            "endDate": "2025-10-09T09:05:00Z",
+            "active": true,
+            "closed": false
+        }"#;
+        
+        let market: Market = serde_json::from_str(json).unwrap();
+        assert_eq!(market.outcomes, vec!["Up", "Down"]);
+        assert_eq!(market.outcome_prices, vec![0.52, 0.48]);
+        assert_eq!(market.volume, 1234.5);
+        assert!(market.is_tradable());
+        assert_eq!(market.up_down_tokens(), Some(("111".to_string(), "222".to_string())));
+        assert_eq!(market.end_time().unwrap().timestamp(), 1760000700);
+    }
+}
@@ -4,10 +4,12 @@
 pub mod gamma;
 pub mod clob;
 pub mod market;
+pub mod discovery;
 
 pub use gamma::GammaApiClient;
 pub use clob::{ClobClient, ClobApiClient, Order, OrderResponse, OrderStatus};
 pub use market::{MarketInfo, MarketToken, convert_market};
+pub use discovery::{MarketDiscovery, SeriesMarket, SeriesSlots};
 
 // Re-export Side from polymarket_client_sdk for consistency
 pub use polymarket_client_sdk::clob::types::Side;
@@ -1,8 +1,5 @@
 //! Direct API test for debugging
 
-use reqwest;
-use serde_json;
-
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Test 1: Get server time
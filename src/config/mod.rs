@@ -5,7 +5,9 @@ pub mod manager;
 
 use serde::{Deserialize, Serialize};
 use std::path::Path;
-use tracing::info;
+use crate::trading::avellaneda::{AvellanedaStoikovConfig, QuoteModel};
+use crate::trading::exit_manager::ExitStyles;
+use tracing::{info, warn};
 
 pub use manager::{ConfigManager, ConfigUpdates};
 
@@ -22,6 +24,18 @@ pub struct Config {
     pub trading: TradingConfig,
     /// WebSocket settings
     pub websocket: WebSocketConfig,
+    /// Service endpoint URLs (optional section, defaults to production)
+    #[serde(default)]
+    pub endpoints: EndpointsConfig,
+    /// Market series to trade (optional section, defaults to BTC 5m)
+    #[serde(default)]
+    pub markets: MarketsConfig,
+    /// Complementary-pair arbitrage (optional section, off by default)
+    #[serde(default)]
+    pub arbitrage: ArbitrageConfig,
+    /// Spot-price fair value anchoring the quotes (optional section, off by default)
+    #[serde(default)]
+    pub fair_value: FairValueConfig,
     /// Logging level
     pub log_level: Option<String>,
 }
@@ -49,14 +63,23 @@ pub struct TradingConfig {
     pub min_spread: f64,
     /// Merge threshold for inventory merge (default: 0.5)
     pub merge_threshold: f64,
+    /// USDC split into UP+DOWN pairs at each market's open, seeding asks (default: 0.0 = off)
+    #[serde(default)]
+    pub split_budget: f64,
     /// Maximum hold time in seconds (default: 180)
     pub max_hold_time: u64,
-    /// Exit before expiry in seconds (default: 120)
+    /// Seconds before expiry at which quoting stops and positions are flattened (default: 120)
     pub exit_before_expiry: u64,
+    /// Spread widening and size shrinking as expiry approaches (default: none)
+    #[serde(default)]
+    pub expiry_schedule: Vec<ExpiryBreakpoint>,
     /// Take profit percentage (default: 0.03)
     pub take_profit: f64,
     /// Stop loss percentage (default: 0.05)
     pub stop_loss: f64,
+    /// Exit order pricing per exit reason (default: marketable for stop loss and expiry)
+    #[serde(default)]
+    pub exit_styles: ExitStyles,
     /// Order book depth lookback (default: 5)
     pub depth_lookback: u64,
     /// Imbalance threshold (default: 0.3)
@@ -75,10 +98,51 @@ pub struct TradingConfig {
     pub refresh_interval: u64,
     /// Spread percentage (default: 0.02)
     pub spread: f64,
-    /// Trading strategy mode: "market_maker" (4 orders) or "buy_hold" (2 orders)
+    /// Inventory handling of market_maker quotes: "linear" or "avellaneda_stoikov" (default: linear)
+    #[serde(default)]
+    pub quote_model: QuoteModel,
+    /// Parameters of the avellaneda_stoikov quote model
+    #[serde(default)]
+    pub avellaneda_stoikov: AvellanedaStoikovConfig,
+    /// Name of the registered strategy to run: "market_maker" or "buy_hold"
     pub strategy_mode: String,
 }
 
+/// Quote scaling once at most `seconds_left` remain before expiry
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ExpiryBreakpoint {
+    pub seconds_left: u64,
+    /// Applied to spread, min_spread and max_spread
+    pub spread_multiplier: f64,
+    /// Applied to order_size
+    pub size_multiplier: f64,
+}
+
+impl TradingConfig {
+    /// Breakpoint in force with `seconds_left` to expiry: the closest to expiry already reached
+    pub fn expiry_breakpoint(&self, seconds_left: i64) -> Option<&ExpiryBreakpoint> {
+        self.expiry_schedule
+            .iter()
+            .filter(|b| seconds_left <= b.seconds_left as i64)
+            .min_by_key(|b| b.seconds_left)
+    }
+
+    /// Quoting parameters with `seconds_left` to expiry; None once inside `exit_before_expiry`
+    pub fn for_time_to_expiry(&self, seconds_left: i64) -> Option<TradingConfig> {
+        if seconds_left <= self.exit_before_expiry as i64 {
+            return None;
+        }
+        let mut config = self.clone();
+        if let Some(breakpoint) = self.expiry_breakpoint(seconds_left) {
+            config.spread *= breakpoint.spread_multiplier;
+            config.min_spread *= breakpoint.spread_multiplier;
+            config.max_spread *= breakpoint.spread_multiplier;
+            config.order_size *= breakpoint.size_multiplier;
+        }
+        Some(config)
+    }
+}
+
 /// WebSocket configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WebSocketConfig {
@@ -86,10 +150,221 @@ pub struct WebSocketConfig {
     pub enabled: bool,
     /// Auto reconnect on disconnect
     pub auto_reconnect: bool,
-    /// Max reconnect attempts
+    /// Max consecutive reconnect attempts before giving up (0 = unlimited)
     pub max_reconnect: u32,
 }
 
+/// Recurring market series configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MarketsConfig {
+    /// Series to trade, e.g. btc-updown-5m, eth-updown-15m, sol-updown-1h
+    pub series: Vec<MarketSeriesConfig>,
+}
+
+impl Default for MarketsConfig {
+    fn default() -> Self {
+        Self {
+            series: vec![MarketSeriesConfig {
+                slug: "btc-updown-5m".to_string(),
+                slot_seconds: 300,
+                spot_symbol: None,
+            }],
+        }
+    }
+}
+
+impl MarketsConfig {
+    /// Validate series entries
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.series.is_empty() {
+            anyhow::bail!("At least one market series is required in [markets]");
+        }
+        for (i, series) in self.series.iter().enumerate() {
+            if series.slug.trim().is_empty() {
+                anyhow::bail!("Market series #{} has an empty slug", i + 1);
+            }
+            if series.slot_seconds == 0 {
+                anyhow::bail!("Market series {} must have slot_seconds > 0", series.slug);
+            }
+            if self.series[..i].iter().any(|s| s.slug == series.slug) {
+                anyhow::bail!("Market series {} is listed twice", series.slug);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Complementary-pair arbitrage configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArbitrageConfig {
+    /// Trade locked-in UP+DOWN edges (default: false)
+    pub enabled: bool,
+    /// Taker fee as a fraction of notional, per leg (default: 0.0)
+    pub fee_rate: f64,
+    /// Minimum edge per pair after fees (default: 0.01)
+    pub min_edge: f64,
+    /// Smallest pair size worth sending (default: 5.0, the CLOB minimum)
+    pub min_size: f64,
+    /// Largest pair size per attempt (default: 20.0)
+    pub max_size: f64,
+    /// Worst price move accepted when unwinding a one-legged fill (default: 0.03)
+    pub unwind_slippage: f64,
+}
+
+impl Default for ArbitrageConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            fee_rate: 0.0,
+            min_edge: 0.01,
+            min_size: 5.0,
+            max_size: 20.0,
+            unwind_slippage: 0.03,
+        }
+    }
+}
+
+impl ArbitrageConfig {
+    /// Validate sizes and rates
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if !(0.0..0.5).contains(&self.fee_rate) {
+            anyhow::bail!("Arbitrage fee_rate must be in [0, 0.5)");
+        }
+        if self.min_edge < 0.0 || self.unwind_slippage < 0.0 {
+            anyhow::bail!("Arbitrage min_edge and unwind_slippage cannot be negative");
+        }
+        if self.min_size <= 0.0 || self.max_size < self.min_size {
+            anyhow::bail!("Arbitrage sizes must satisfy 0 < min_size <= max_size");
+        }
+        Ok(())
+    }
+}
+
+/// Spot-price fair value configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FairValueConfig {
+    /// Quote around the spot-implied P(UP) instead of the book mid (default: false)
+    pub enabled: bool,
+    /// Spot feed protocol (default: "binance")
+    pub adapter: String,
+    /// Spot WebSocket URL (default: the adapter's)
+    pub url: Option<String>,
+    /// Replay recorded feed messages from this file instead of connecting
+    pub replay_file: Option<String>,
+    /// Realized volatility lookback in seconds (default: 900)
+    pub vol_window_secs: u64,
+    /// Annualized volatility used until enough history is collected (default: 0.5)
+    pub fallback_volatility: f64,
+    /// Spot prices older than this are not used (default: 10)
+    pub max_staleness_secs: u64,
+}
+
+impl Default for FairValueConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            adapter: "binance".to_string(),
+            url: None,
+            replay_file: None,
+            vol_window_secs: 900,
+            fallback_volatility: 0.5,
+            max_staleness_secs: 10,
+        }
+    }
+}
+
+impl FairValueConfig {
+    /// Validate adapter, URL and volatility settings
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if crate::websocket::spot_adapter(&self.adapter).is_none() {
+            anyhow::bail!("Unknown fair_value adapter {:?}", self.adapter);
+        }
+        if let Some(url) = &self.url {
+            if !url.starts_with("ws://") && !url.starts_with("wss://") {
+                anyhow::bail!("fair_value url must be a ws(s) URL, got {:?}", url);
+            }
+        }
+        if self.vol_window_secs == 0 || self.fallback_volatility <= 0.0 {
+            anyhow::bail!("fair_value vol_window_secs and fallback_volatility must be positive");
+        }
+        Ok(())
+    }
+}
+
+/// One recurring up/down market series
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MarketSeriesConfig {
+    /// Slug prefix; each market's slug is `{slug}-{slot_start}`
+    pub slug: String,
+    /// Slot length in seconds (300 = 5m, 900 = 15m, 3600 = 1h)
+    pub slot_seconds: u64,
+    /// Spot symbol for the fair value feed (default: slug asset + USDT, e.g. BTCUSDT)
+    #[serde(default)]
+    pub spot_symbol: Option<String>,
+}
+
+impl MarketSeriesConfig {
+    /// Spot symbol, derived from the slug's asset prefix unless set
+    pub fn spot_symbol(&self) -> Option<String> {
+        if let Some(symbol) = &self.spot_symbol {
+            return Some(symbol.clone());
+        }
+        let asset = self.slug.split('-').next().filter(|a| !a.is_empty())?;
+        Some(format!("{}USDT", asset.to_uppercase()))
+    }
+}
+
+/// Service endpoint URLs
+/// Override to point the bot at mock servers, staging or recording proxies
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EndpointsConfig {
+    /// CLOB REST API (default: https://clob.polymarket.com)
+    pub clob: String,
+    /// Gamma market data API (default: https://gamma-api.polymarket.com)
+    pub gamma: String,
+    /// Builder relayer (default: https://relayer.polymarket.com)
+    pub relayer: String,
+    /// Market channel WebSocket (default: wss://ws-subscriptions-clob.polymarket.com/ws/market)
+    pub market_ws: String,
+    /// User channel WebSocket (default: wss://ws-subscriptions-clob.polymarket.com/ws/user)
+    pub user_ws: String,
+}
+
+impl Default for EndpointsConfig {
+    fn default() -> Self {
+        Self {
+            clob: crate::api::clob::CLOB_API_URL.to_string(),
+            gamma: crate::api::gamma::GAMMA_API_URL.to_string(),
+            relayer: crate::redeem::BUILDER_RELAYER_URL.to_string(),
+            market_ws: crate::websocket::MARKET_WS_URL.to_string(),
+            user_ws: crate::websocket::user::USER_WS_URL.to_string(),
+        }
+    }
+}
+
+impl EndpointsConfig {
+    /// Validate endpoint URL schemes
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let http = [("clob", &self.clob), ("gamma", &self.gamma), ("relayer", &self.relayer)];
+        for (name, url) in http {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                anyhow::bail!("Endpoint {} must be an http(s) URL, got {:?}", name, url);
+            }
+        }
+        let ws = [("market_ws", &self.market_ws), ("user_ws", &self.user_ws)];
+        for (name, url) in ws {
+            if !url.starts_with("ws://") && !url.starts_with("wss://") {
+                anyhow::bail!("Endpoint {} must be a ws(s) URL, got {:?}", name, url);
+            }
+        }
+        Ok(())
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
@@ -107,10 +382,13 @@ impl Default for Config {
                 max_spread: 0.02,
                 min_spread: 0.005,
                 merge_threshold: 0.5,      // New: merge threshold
+                split_budget: 0.0,         // USDC per market split into pairs at open
                 max_hold_time: 180,        // New: max hold time in seconds
                 exit_before_expiry: 120,   // New: exit before expiry in seconds
+                expiry_schedule: Vec::new(),
                 take_profit: 0.03,         // New: take profit percentage
                 stop_loss: 0.05,           // New: stop loss percentage
+                exit_styles: ExitStyles::default(),
                 depth_lookback: 5,         // New: order book depth lookback
                 imbalance_threshold: 0.3,  // New: imbalance threshold
                 min_price: 0.01,           // New: minimum price
@@ -120,6 +398,8 @@ impl Default for Config {
                 price_warn_cooldown: 60,   // New: price warning cooldown in seconds
                 refresh_interval: 45,
                 spread: 0.02,
+                quote_model: QuoteModel::Linear,
+                avellaneda_stoikov: AvellanedaStoikovConfig::default(),
                 strategy_mode: "market_maker".to_string(), // "market_maker" or "buy_hold"
             },
             websocket: WebSocketConfig {
@@ -127,6 +407,10 @@ impl Default for Config {
                 auto_reconnect: true,
                 max_reconnect: 5,
             },
+            endpoints: EndpointsConfig::default(),
+            markets: MarketsConfig::default(),
+            arbitrage: ArbitrageConfig::default(),
+            fair_value: FairValueConfig::default(),
             log_level: Some("info".to_string()),
         }
     }
@@ -259,9 +543,58 @@ impl Config {
         if self.trading.safe_range_low >= self.trading.safe_range_high {
             anyhow::bail!("Safe range low must be less than high");
         }
+        if self.trading.split_budget < 0.0 {
+            anyhow::bail!("Split budget cannot be negative");
+        }
+        for breakpoint in &self.trading.expiry_schedule {
+            if breakpoint.spread_multiplier <= 0.0 || breakpoint.size_multiplier < 0.0 {
+                anyhow::bail!("Expiry breakpoint at {}s needs spread_multiplier > 0 and size_multiplier >= 0",
+                    breakpoint.seconds_left);
+            }
+        }
+        self.trading.avellaneda_stoikov.validate()?;
+        self.endpoints.validate()?;
+        self.markets.validate()?;
+        self.arbitrage.validate()?;
+        self.fair_value.validate()?;
         Ok(())
     }
 
+    /// Apply settings from environment variables on top of a loaded config
+    /// Only variables that are set replace the file values.
+    pub fn apply_env_overrides(&mut self) {
+        self.apply_overrides(|key| std::env::var(key).ok());
+    }
+
+    fn apply_overrides(&mut self, var: impl Fn(&str) -> Option<String>) {
+        let endpoints = &mut self.endpoints;
+        for (key, field) in [
+            ("CLOB_URL", &mut endpoints.clob),
+            ("GAMMA_URL", &mut endpoints.gamma),
+            ("RELAYER_URL", &mut endpoints.relayer),
+            ("MARKET_WS_URL", &mut endpoints.market_ws),
+            ("USER_WS_URL", &mut endpoints.user_ws),
+        ] {
+            if let Some(url) = var(key) {
+                info!("Using {} from environment", key);
+                *field = url;
+            }
+        }
+        if let Some(budget) = parsed_var(&var, "SPLIT_BUDGET") {
+            self.trading.split_budget = budget;
+        }
+        if let Some(enabled) = parsed_var(&var, "ARBITRAGE_ENABLED") {
+            self.arbitrage.enabled = enabled;
+        }
+        if let Some(enabled) = parsed_var(&var, "FAIR_VALUE_ENABLED") {
+            self.fair_value.enabled = enabled;
+        }
+        if let Some(file) = var("FAIR_VALUE_REPLAY_FILE") {
+            info!("Using FAIR_VALUE_REPLAY_FILE from environment");
+            self.fair_value.replay_file = Some(file);
+        }
+    }
+
     /// Check Builder API configuration
     pub fn check_builder_api(&self) -> BuilderApiStatus {
         let key = self.api.key.as_deref().unwrap_or("");
@@ -286,6 +619,21 @@ impl Config {
     }
 }
 
+/// Value of a set environment variable that parses as `T`
+fn parsed_var<T: std::str::FromStr>(var: &impl Fn(&str) -> Option<String>, key: &str) -> Option<T> {
+    let value = var(key)?;
+    match value.parse() {
+        Ok(parsed) => {
+            info!("Using {} from environment", key);
+            Some(parsed)
+        }
+        Err(_) => {
+            warn!("Ignoring {}={:?}: not a valid value", key, value);
+            None
+        }
+    }
+}
+
 /// Builder API configuration status
 #[derive(Debug, Clone)]
 pub enum BuilderApiStatus {
@@ -331,6 +679,10 @@ pub fn from_env() -> anyhow::Result<Config> {
                 .ok()
                 .and_then(|s| s.parse().ok())
                 .unwrap_or(0.5),
+            split_budget: env::var("SPLIT_BUDGET")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0.0),
             max_hold_time: env::var("MAX_HOLD_TIME")
                 .ok()
                 .and_then(|s| s.parse().ok())
@@ -339,6 +691,7 @@ pub fn from_env() -> anyhow::Result<Config> {
                 .ok()
                 .and_then(|s| s.parse().ok())
                 .unwrap_or(120),
+            expiry_schedule: Vec::new(),
             take_profit: env::var("TAKE_PROFIT")
                 .ok()
                 .and_then(|s| s.parse().ok())
@@ -347,6 +700,7 @@ pub fn from_env() -> anyhow::Result<Config> {
                 .ok()
                 .and_then(|s| s.parse().ok())
                 .unwrap_or(0.05),
+            exit_styles: ExitStyles::default(),
             depth_lookback: env::var("DEPTH_LOOKBACK")
                 .ok()
                 .and_then(|s| s.parse().ok())
@@ -383,6 +737,8 @@ pub fn from_env() -> anyhow::Result<Config> {
                 .ok()
                 .and_then(|s| s.parse().ok())
                 .unwrap_or(0.02),
+            quote_model: QuoteModel::Linear,
+            avellaneda_stoikov: AvellanedaStoikovConfig::default(),
             strategy_mode: env::var("STRATEGY_MODE")
                 .ok()
                 .unwrap_or_else(|| "buy_hold".to_string()),
@@ -401,6 +757,32 @@ pub fn from_env() -> anyhow::Result<Config> {
                 .and_then(|s| s.parse().ok())
                 .unwrap_or(5),
         },
+        endpoints: {
+            let defaults = EndpointsConfig::default();
+            EndpointsConfig {
+                clob: env::var("CLOB_URL").unwrap_or(defaults.clob),
+                gamma: env::var("GAMMA_URL").unwrap_or(defaults.gamma),
+                relayer: env::var("RELAYER_URL").unwrap_or(defaults.relayer),
+                market_ws: env::var("MARKET_WS_URL").unwrap_or(defaults.market_ws),
+                user_ws: env::var("USER_WS_URL").unwrap_or(defaults.user_ws),
+            }
+        },
+        markets: MarketsConfig::default(),
+        arbitrage: ArbitrageConfig {
+            enabled: env::var("ARBITRAGE_ENABLED")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(false),
+            ..ArbitrageConfig::default()
+        },
+        fair_value: FairValueConfig {
+            enabled: env::var("FAIR_VALUE_ENABLED")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(false),
+            replay_file: env::var("FAIR_VALUE_REPLAY_FILE").ok(),
+            ..FairValueConfig::default()
+        },
         log_level: env::var("LOG_LEVEL").ok(),
     };
     
@@ -411,6 +793,7 @@ pub fn from_env() -> anyhow::Result<Config> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashMap;
 
     #[test]
     fn test_default_config() {
@@ -420,6 +803,112 @@ mod tests {
         assert_eq!(config.trading.refresh_interval, 45);
     }
 
+    #[test]
+    fn test_endpoints_section_optional() {
+        let mut value = toml::Value::try_from(Config::default()).unwrap();
+        value.as_table_mut().unwrap().remove("endpoints");
+        let config: Config = toml::from_str(&toml::to_string(&value).unwrap()).unwrap();
+        assert_eq!(config.endpoints.clob, "https://clob.polymarket.com");
+        assert!(config.endpoints.validate().is_ok());
+    }
+
+    #[test]
+    fn test_endpoints_override() {
+        let toml_str = r#"
+            clob = "http://127.0.0.1:8080"
+            market_ws = "ws://127.0.0.1:8081/ws/market"
+        "#;
+        let endpoints: EndpointsConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(endpoints.clob, "http://127.0.0.1:8080");
+        assert_eq!(endpoints.gamma, "https://gamma-api.polymarket.com");
+        assert!(endpoints.validate().is_ok());
+
+        let bad = EndpointsConfig {
+            market_ws: "http://127.0.0.1".to_string(),
+            ..EndpointsConfig::default()
+        };
+        assert!(bad.validate().is_err());
+    }
+
+    #[test]
+    fn test_env_overrides_file_endpoints() {
+        let mut config = Config::default();
+        config.endpoints.clob = "http://file.example:8080".to_string();
+        config.endpoints.gamma = "http://file.example:8081".to_string();
+
+        config.trading.split_budget = 10.0;
+
+        let env = HashMap::from([
+            ("CLOB_URL", "http://127.0.0.1:9090"),
+            ("SPLIT_BUDGET", "25"),
+            ("ARBITRAGE_ENABLED", "true"),
+            ("FAIR_VALUE_ENABLED", "true"),
+            ("FAIR_VALUE_REPLAY_FILE", "spot.jsonl"),
+        ]);
+        config.apply_overrides(|key| env.get(key).map(|v| v.to_string()));
+        assert_eq!(config.endpoints.clob, "http://127.0.0.1:9090");
+        assert_eq!(config.trading.split_budget, 25.0);
+        assert!(config.arbitrage.enabled);
+        assert!(config.fair_value.enabled);
+        assert_eq!(config.fair_value.replay_file.as_deref(), Some("spot.jsonl"));
+        // Unset variables leave the file value alone
+        assert_eq!(config.endpoints.gamma, "http://file.example:8081");
+
+        // Values that do not parse are ignored
+        let env = HashMap::from([("SPLIT_BUDGET", "lots")]);
+        config.apply_overrides(|key| env.get(key).map(|v| v.to_string()));
+        assert_eq!(config.trading.split_budget, 25.0);
+    }
+
+    #[test]
+    fn test_expiry_schedule() {
+        let mut config = Config::default().trading;
+        config.expiry_schedule = vec![
+            ExpiryBreakpoint { seconds_left: 180, spread_multiplier: 2.0, size_multiplier: 0.25 },
+            ExpiryBreakpoint { seconds_left: 240, spread_multiplier: 1.5, size_multiplier: 0.5 },
+        ];
+
+        let early = config.for_time_to_expiry(280).unwrap();
+        assert_eq!((early.spread, early.order_size), (config.spread, config.order_size));
+        let late = config.for_time_to_expiry(200).unwrap();
+        assert!((late.max_spread - 0.03).abs() < 1e-12);
+        assert!((late.order_size - 0.5).abs() < 1e-12);
+        let later = config.for_time_to_expiry(150).unwrap();
+        assert!((later.spread - 0.04).abs() < 1e-12);
+        assert!((later.order_size - 0.25).abs() < 1e-12);
+
+        // exit_before_expiry: no quoting at all
+        assert!(config.for_time_to_expiry(120).is_none());
+    }
+
+    #[test]
+    fn test_markets_section() {
+        let toml_str = r#"
+            [[series]]
+            slug = "eth-updown-15m"
+            slot_seconds = 900
+
+            [[series]]
+            slug = "sol-updown-1h"
+            slot_seconds = 3600
+            spot_symbol = "SOLUSDC"
+        "#;
+        let markets: MarketsConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(markets.series.len(), 2);
+        assert_eq!(markets.series[1].slot_seconds, 3600);
+        assert_eq!(markets.series[0].spot_symbol().as_deref(), Some("ETHUSDT"));
+        assert_eq!(markets.series[1].spot_symbol().as_deref(), Some("SOLUSDC"));
+        assert!(markets.validate().is_ok());
+
+        // Section omitted -> BTC 5m
+        assert_eq!(Config::default().markets.series[0].slug, "btc-updown-5m");
+
+        let mut dup = markets.clone();
+        dup.series.push(dup.series[0].clone());
+        assert!(dup.validate().is_err());
+        assert!(MarketsConfig { series: vec![] }.validate().is_err());
+    }
+
     #[test]
     fn test_validate_empty_pk() {
         let config = Config::default();
@@ -428,9 +917,11 @@ mod tests {
 
     #[test]
     fn test_validate_invalid_range() {
-        let mut config = Config::default();
-        config.pk = "0x123".to_string();
-        config.safe_address = "0x456".to_string();
+        let mut config = Config {
+            pk: "0x123".to_string(),
+            safe_address: "0x456".to_string(),
+            ..Config::default()
+        };
         config.trading.safe_range_low = 0.9;
         config.trading.safe_range_high = 0.1;
         assert!(config.validate().is_err());
@@ -16,21 +16,24 @@ pub mod utils;
 pub mod config;
 
 // Re-export commonly used types
-pub use api::{GammaApiClient, ClobClient, ClobApiClient, Order, Side, OrderStatus, OrderResponse};
+pub use api::{GammaApiClient, MarketDiscovery, SeriesMarket, ClobClient, ClobApiClient, Order, Side, OrderStatus, OrderResponse};
 pub use trading::{
     MarketMaker, MarketMakerConfig, 
     PositionTracker, Position, PositionEntry, InventoryStatus, Action, BalanceAdjustment,
     TradeExecutor,
-    OrderBookDepth, OrderBookLevel, analyze_order_book_depth_safe, calculate_mm_prices,
-    OrderTracker, ActiveOrder, FillStatus, wait_for_fill,
+    OrderBookDepth, OrderBookLevel, L2OrderBook, BookSide, analyze_order_book_depth_safe, calculate_mm_prices,
+    OrderTracker, ActiveOrder, OrderState, OrderEvent, FillStatus, Fill, OrderFillUpdate, TradeFill, wait_for_fill,
     TradeHistory, TradeRecord,
     TradingStats, PriceFreshness,
+    MarketEngine, MarketContext, PositionBudget, RolloverScheduler,
+    Strategy, StrategyRegistry, MarketSnapshot, DesiredQuote,
+    FairValueModel, PriceHistory,
 };
 pub use wallet::{PrivateKeyWallet, SafeWallet, Wallet, RedeemTypedData};
-pub use websocket::{PolymarketWebSocket, PriceUpdate};
-pub use redeem::{BuilderRelayer, AutoRedeemService, SettledMarket, RedeemResult};
-pub use config::{Config, ApiConfig, TradingConfig, WebSocketConfig};
-pub use utils::{retry, rate_limiter};
+pub use websocket::{ConnectionState, MarketEvent, PolymarketWebSocket, PriceUpdate, Quote, ReconnectPolicy, UserWebSocket, UserEvent, SpotReplay, SpotTick, SpotWebSocket};
+pub use redeem::{BuilderRelayer, AutoRedeemService, CtfExecutor, SettledMarket, RedeemResult};
+pub use config::{Config, ApiConfig, TradingConfig, WebSocketConfig, EndpointsConfig, MarketsConfig, MarketSeriesConfig, ArbitrageConfig, FairValueConfig, ExpiryBreakpoint};
+pub use utils::{retry, rate_limiter, ServerClock};
 
 use thiserror::Error;
 
@@ -6,16 +6,38 @@ use anyhow::Result;
 use futures::FutureExt;
 use polymarket_pro::*;
 use polymarket_pro::api::Side;
-use polymarket_pro::trading::PriceWarningTracker;
+use polymarket_pro::api::discovery::slot_start;
+use polymarket_pro::trading::{apply_user_event, reconcile_order_fills, sync_order_stats, PriceWarningTracker, TokenSnapshot};
+use polymarket_pro::trading::{ExitManager, ExitReason, Outcome, PositionExitTracker};
+use polymarket_pro::trading::{detect_arbitrage, execute_arbitrage, ArbContext, ArbKind, MarkoutTracker};
+use polymarket_pro::trading::strategy::{sellable_size, BALANCE_BUFFER_RATIO};
+use polymarket_pro::redeem::RelayerError;
+use polymarket_pro::redeem::ctf::{from_base_units, to_base_units};
+use polymarket_pro::utils::rate_limiter::RateLimiter;
 use std::sync::Arc;
 use std::time::Instant;
 use tokio::sync::RwLock;
 use tokio::time::{interval, Duration};
-use tracing::{error, info, warn, Level};
+use tracing::{debug, error, info, warn, Level};
+
+/// Minimum time between arbitrage scans of one market's books
+const ARB_SCAN_INTERVAL_MS: u64 = 250;
+
+/// Candle interval and lookback of the mid volatility handed to strategies
+const MID_VOL_INTERVAL_SECS: i64 = 5;
+const MID_VOL_WINDOW_SECS: i64 = 120;
+
+/// How often due fill markouts are sampled
+const MARKOUT_SAMPLE_INTERVAL_MS: u64 = 250;
+/// Polling of Gamma for a finished market's resolution
+const SETTLEMENT_POLL_SECS: u64 = 30;
+const SETTLEMENT_MAX_POLLS: u32 = 40;
+
+/// Mid move (in price units) on a traded token that triggers an early requote
+const REQUOTE_MID_MOVE: f64 = 0.01;
+/// Minimum seconds between event-driven requotes
+const REQUOTE_MIN_INTERVAL_SECS: u64 = 5;
 
-// Import BTC market finder
-mod btc_market;
-use btc_market::{find_btc_5min_market, get_market_token_ids};
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -56,6 +78,13 @@ async fn main() -> Result<()> {
     info!("  Max position: {}", config.trading.max_position);
     info!("  Safe range: {} - {}", config.trading.safe_range_low, config.trading.safe_range_high);
     info!("  Refresh interval: {}s", config.trading.refresh_interval);
+    info!("  Strategy: {}", config.trading.strategy_mode);
+
+    // Quoting strategy selected by strategy_mode
+    let strategies = StrategyRegistry::default();
+    let strategy = strategies.get(&config.trading.strategy_mode).ok_or_else(|| anyhow::anyhow!(
+        "Unknown strategy_mode '{}' (available: {})",
+        config.trading.strategy_mode, strategies.names().join(", ")))?;
 
     let executor = Arc::new(
         TradeExecutor::new(
@@ -64,6 +93,7 @@ async fn main() -> Result<()> {
             config.api.secret.clone(),
             config.api.passphrase.clone(),
         ).await.map_err(|e| anyhow::anyhow!("Failed to create trade executor: {}", e))?
+        .with_endpoints(&config.endpoints)
     );
     
     // Check if simulation mode is enabled via environment variable
@@ -74,26 +104,30 @@ async fn main() -> Result<()> {
     
     info!("Trade executor initialized");
 
-    match executor.server_time().await {
-        Ok(time) => info!("Server time: {}", time),
-        Err(e) => warn!("Failed to get server time: {}", e),
-    }
+    // Slot boundaries follow the exchange clock, not ours
+    let clock = Arc::new(ServerClock::new());
+    sync_server_clock(&executor, &clock).await;
 
-    let position_tracker = Arc::new(RwLock::new(PositionTracker::new()));
-    let order_tracker = Arc::new(RwLock::new(OrderTracker::new()));
-    let trade_history = Arc::new(TradeHistory::default());
+    // One task per market, each with its own order/position slice; capital is shared
+    let engine = Arc::new(MarketEngine::new(config.trading.max_total_position));
 
     // Initialize trading stats (will be loaded from file later)
     let stats = Arc::new(RwLock::new(TradingStats::load_or_new()));
 
-    let rate_limiter = Arc::new(utils::rate_limiter::RateLimiter::new_default());
+    let rate_limiter = Arc::new(RateLimiter::new_default());
     let price_warning_tracker = Arc::new(RwLock::new(PriceWarningTracker::new(
         config.trading.price_warn_cooldown
     )));
-    let price_freshness = Arc::new(RwLock::new(PriceFreshness::new(5)));
 
     let ws_subscriber: Option<Arc<PolymarketWebSocket>> = if config.websocket.enabled {
-        let ws = Arc::new(PolymarketWebSocket::new());
+        let ws = Arc::new(
+            PolymarketWebSocket::new()
+                .with_url(&config.endpoints.market_ws)
+                .with_reconnect_policy(ReconnectPolicy::new(
+                    config.websocket.auto_reconnect,
+                    config.websocket.max_reconnect,
+                )),
+        );
         ws.start(vec![]).await;
         info!("WebSocket connected");
         Some(ws)
@@ -101,12 +135,123 @@ async fn main() -> Result<()> {
         None
     };
 
+    // Mids of traded tokens and spot of reference assets
+    let price_history = Arc::new(RwLock::new(PriceHistory::default()));
+    // Fills waiting for their markouts
+    let markouts = Arc::new(RwLock::new(MarkoutTracker::new()));
+
+    // User channel: fills reach the trackers as soon as they match
+    let _user_ws: Option<UserWebSocket> = if config.websocket.enabled && !executor.is_simulation_mode() {
+        match executor.user_channel_auth().await {
+            Ok(auth) => {
+                let user_ws = UserWebSocket::new(auth)
+                    .with_url(&config.endpoints.user_ws)
+                    .with_reconnect_policy(ReconnectPolicy::new(
+                        config.websocket.auto_reconnect,
+                        config.websocket.max_reconnect,
+                    ));
+                let mut user_state = user_ws.watch_state();
+                tokio::spawn(async move {
+                    while user_state.changed().await.is_ok() {
+                        let state = *user_state.borrow_and_update();
+                        match state {
+                            ConnectionState::Connected => info!("📶 User channel connected"),
+                            ConnectionState::Reconnecting { .. } => {
+                                warn!("📴 User channel down ({:?}), fills wait for REST reconciliation", state);
+                            }
+                            ConnectionState::GaveUp { .. } => {
+                                error!("🛑 User channel gave up reconnecting; fills are booked by REST reconciliation only");
+                            }
+                            _ => {}
+                        }
+                    }
+                });
+                let mut events = user_ws.start(vec![]).await;
+                let engine = engine.clone();
+                let stats = stats.clone();
+                let markouts = markouts.clone();
+                let clock = clock.clone();
+                tokio::spawn(async move {
+                    while let Some(event) = events.recv().await {
+                        // Route to the market slice that owns the asset
+                        match engine.context_for_asset(event.asset_id()).await {
+                            Some(ctx) => {
+                                let fills = apply_user_event(&event, &ctx.order_tracker, &ctx.position_tracker, &stats).await;
+                                record_exit_fills(&stats, &ctx, &fills).await;
+                                // A market left before this event landed still owns its budget share
+                                engine.refresh_settling(&ctx).await;
+                                let mut markouts = markouts.write().await;
+                                for fill in &fills {
+                                    markouts.track(&ctx.market.series, fill, clock.now());
+                                }
+                            }
+                            None => debug!("User event for untracked asset {}", event.asset_id()),
+                        }
+                    }
+                });
+                info!("User channel WebSocket started");
+                Some(user_ws)
+            }
+            Err(e) => {
+                warn!("⚠️ User channel disabled, falling back to polling: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
     let wallet_addr = executor.address();
-    let _safe = SafeWallet::new(
+    let safe = SafeWallet::new(
         &config.safe_address,
         &wallet_addr,
     )?;
 
+    // Merging and splitting UP+DOWN pairs are Safe transactions, so they need the builder relayer
+    let ctf: Option<Arc<CtfExecutor>> = match config.check_builder_api() {
+        config::BuilderApiStatus::Enabled if !executor.is_simulation_mode() => {
+            let relayer = BuilderRelayer::new(
+                config.api.key.clone().unwrap_or_default(),
+                config.api.secret.clone().unwrap_or_default(),
+                config.api.passphrase.clone().unwrap_or_default(),
+            ).with_base_url(&config.endpoints.relayer);
+            let signer = PrivateKeyWallet::from_private_key(&config.pk, 137)?.signer().clone();
+            info!("🔀 Merges and splits enabled through the builder relayer");
+            Some(Arc::new(CtfExecutor::new(relayer, safe, signer)))
+        }
+        _ => {
+            warn!("⚠️ Builder API not available, UP+DOWN pairs will not be merged or split");
+            None
+        }
+    };
+
+    // Spot feed for the fair value model; a replay file stands in for the live feed
+    let (fair_value, _spot_ws): (Option<Arc<FairValueModel>>, Option<SpotWebSocket>) = if config.fair_value.enabled {
+        let model = Arc::new(FairValueModel::new(config.fair_value.clone(), &config.markets, price_history.clone()));
+        let adapter = websocket::spot_adapter(&config.fair_value.adapter)
+            .ok_or_else(|| anyhow::anyhow!("Unknown fair_value adapter {}", config.fair_value.adapter))?;
+        let (mut ticks, spot_ws) = match &config.fair_value.replay_file {
+            Some(path) => (SpotReplay::new(path, adapter).with_realtime(true).start()?, None),
+            None => {
+                let mut spot_ws = SpotWebSocket::new(adapter);
+                if let Some(url) = &config.fair_value.url {
+                    spot_ws = spot_ws.with_url(url);
+                }
+                (spot_ws.start(model.symbols()).await, Some(spot_ws))
+            }
+        };
+        let recorder = model.clone();
+        tokio::spawn(async move {
+            while let Some(tick) = ticks.recv().await {
+                recorder.record(tick).await;
+            }
+        });
+        info!("📈 Fair value quoting enabled ({})", model.symbols().join(", "));
+        (Some(model), spot_ws)
+    } else {
+        (None, None)
+    };
+
     let (shutdown_tx, mut shutdown_rx) = tokio::sync::mpsc::channel(1);
 
     tokio::spawn(async move {
@@ -120,6 +265,27 @@ async fn main() -> Result<()> {
     // to avoid conflicts with fill detection logic
     // The main loop's 45-second cycle with proper fill detection is sufficient
 
+    // Sample fill markouts as their horizons come due
+    {
+        let markouts = markouts.clone();
+        let price_history = price_history.clone();
+        let stats = stats.clone();
+        let clock = clock.clone();
+        tokio::spawn(async move {
+            let mut tick = interval(Duration::from_millis(MARKOUT_SAMPLE_INTERVAL_MS));
+            loop {
+                tick.tick().await;
+                let samples = markouts.write().await.sample(&*price_history.read().await, clock.now());
+                if !samples.is_empty() {
+                    let mut stats = stats.write().await;
+                    for sample in &samples {
+                        stats.markouts.record(sample);
+                    }
+                }
+            }
+        });
+    }
+
     let stats_logger = stats.clone();
     tokio::spawn(async move {
         let mut stats_interval = interval(Duration::from_secs(300));
@@ -133,200 +299,67 @@ async fn main() -> Result<()> {
         }
     });
 
-    // Main event loop - matches Python polymaker_5m.py
-    // - Find a 5-minute market (e.g., BTC)
-    // - Trade on this market every 45 seconds
-    // - When market expires, find new 5-minute market
-    let trading_interval = Duration::from_secs(config.trading.refresh_interval); // 45 seconds
-    let market_check_interval = Duration::from_secs(60); // Check for new market every 60 seconds
-    let mut trading_tick = interval(trading_interval);
+    // Main event loop
+    // - Every 60s resolve the current market of each configured series and stage the next slot's
+    // - Staged markets are subscribed early and switched in exactly at their slot boundary
+    // - Each open market trades in its own task every refresh_interval
+    let market_check_interval = Duration::from_secs(60); // Check for new markets every 60 seconds
     let mut market_check_tick = interval(market_check_interval);
     
-    // Clone ws_subscriber for trading cycle
-    let ws_subscriber_trading = ws_subscriber.clone();
-    
-    // Current active 5-minute market
-    let mut current_market: Option<MarketInfo> = None;
-    
-    // Initial market search on startup
-    info!("🔍 Initial market search on startup...");
-    match find_btc_5min_market(&executor).await {
-        Some(market) => {
-            let condition_id = market.get("conditionId").and_then(|v| v.as_str()).unwrap_or("").to_string();
-            info!("✅ Found initial 5-minute market: {}", condition_id);
-            
-            // Subscribe to WebSocket for initial market and get token IDs
-            if !condition_id.is_empty() {
-                // Retry getting token IDs up to 3 times
-                let mut token_ids = None;
-                for attempt in 1..=3 {
-                    token_ids = subscribe_to_market_ws(&condition_id, ws_subscriber.clone()).await;
-                    if token_ids.is_some() {
-                        break;
-                    }
-                    warn!("⚠️ Could not get token IDs (attempt {}), retrying...", attempt);
-                    tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
-                }
-                
-                if let Some((up_token, down_token)) = token_ids {
-                    current_market = Some(MarketInfo {
-                        condition_id,
-                        up_token,
-                        down_token,
-                        end_date: market.get("endDate").and_then(|v| v.as_str()).map(|s| s.to_string()),
-                    });
-                } else {
-                    error!("🛑 Failed to get token IDs after 3 attempts, skipping this market");
-                    // Don't use condition_id as fallback - it's not a valid token ID
-                    // The market will be skipped and we'll retry in the next cycle
-                }
-            }
-        }
-        None => {
-            warn!("⚠️ No 5-minute market found on startup, will retry in 60s");
-        }
-    }
+    // Market discovery over the configured series
+    let discovery = MarketDiscovery::new(
+        GammaApiClient::new().with_base_url(&config.endpoints.gamma),
+        config.markets.series.clone(),
+    );
+    info!("📋 Trading {} series: {}", discovery.series().len(),
+        discovery.series().iter().map(|s| s.slug.as_str()).collect::<Vec<_>>().join(", "));
     
-    // Flag to skip first trading cycle to allow WebSocket to connect
-    let mut first_cycle = true;
+    let services = MarketServices {
+        executor: executor.clone(),
+        ws: ws_subscriber.clone(),
+        stats: stats.clone(),
+        price_warning_tracker: price_warning_tracker.clone(),
+        rate_limiter: rate_limiter.clone(),
+        trading_config: Arc::new(config.trading.clone()),
+        clock: clock.clone(),
+        strategy,
+        ctf,
+        arbitrage: Arc::new(config.arbitrage.clone()),
+        fair_value,
+        price_history,
+        markouts,
+        gamma: Arc::new(GammaApiClient::new().with_base_url(&config.endpoints.gamma)),
+    };
+    let mut rollover = RolloverScheduler::new();
     
     loop {
+        let switch_at = rollover.next_switch().map(|at| clock.instant_at(at));
         tokio::select! {
-            // Check for new market periodically
+            // Start tasks for newly open markets and stage the next slot
             _ = market_check_tick.tick() => {
-                // If no current market or current market expired, find new one
-                let need_new_market = if let Some(ref market_info) = current_market {
-                    if let Some(ref end_date_str) = market_info.end_date {
-                        if let Some(end_date) = parse_market_end_time(end_date_str) {
-                            let now = chrono::Utc::now();
-                            let time_to_expiry = end_date.signed_duration_since(now).num_seconds();
-                            time_to_expiry <= 60 // Need new market if expiring in 60s
-                        } else {
-                            true
-                        }
-                    } else {
-                        true
-                    }
-                } else {
-                    true
-                };
-                
-                if need_new_market {
-                    info!("🔍 Looking for BTC updown 5m market...");
-                    
-                    // Wrap market finding in panic catcher
-                    let market_result = std::panic::AssertUnwindSafe(async {
-                        find_btc_5min_market(&executor).await
-                    }).catch_unwind().await;
-                    
-                    match market_result {
-                        Ok(Some(new_market)) => {
-                            let new_condition_id = new_market.get("conditionId").and_then(|v| v.as_str()).unwrap_or("").to_string();
-                            let old_condition_id = current_market.as_ref().map(|m| m.condition_id.clone());
-                            
-                            if old_condition_id.as_ref() != Some(&new_condition_id) {
-                                info!("✅ Found new 5-minute market: {}", new_condition_id);
-                                
-                                // Cancel orders on old market if exists
-                                if let Some(ref old_market_info) = current_market {
-                                    info!("📤 Unsubscribing from old market: {}", old_market_info.up_token);
-                                    let _ = executor.cancel_orders_for_market(&old_market_info.up_token).await;
-                                }
-                                
-                                // Update WebSocket subscription for new market and get token IDs
-                                if !new_condition_id.is_empty() {
-                                    // Retry getting token IDs up to 3 times
-                                    let mut token_ids = None;
-                                    for attempt in 1..=3 {
-                                        token_ids = subscribe_to_market_ws(&new_condition_id, ws_subscriber.clone()).await;
-                                        if token_ids.is_some() {
-                                            break;
-                                        }
-                                        warn!("⚠️ Could not get token IDs for new market (attempt {}), retrying...", attempt);
-                                        tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
-                                    }
-                                    
-                                    if let Some((up_token, down_token)) = token_ids {
-                                        current_market = Some(MarketInfo {
-                                            condition_id: new_condition_id,
-                                            up_token,
-                                            down_token,
-                                            end_date: new_market.get("endDate").and_then(|v| v.as_str()).map(|s| s.to_string()),
-                                        });
-                                    } else {
-                                        error!("🛑 Failed to get token IDs for new market after 3 attempts, keeping old market");
-                                        // Don't update current_market - keep trading on old market
-                                    }
-                                }
-                            }
-                        }
-                        Ok(None) => {
-                            warn!("⚠️ No 5-minute market found");
-                        }
-                        Err(_) => {
-                            error!("🛑 Panic caught while finding new market, continuing...");
-                        }
-                    }
+                sync_server_clock(&executor, &clock).await;
+                // Wrap market discovery in panic catcher
+                let result = std::panic::AssertUnwindSafe(
+                    sync_markets(&engine, &discovery, &mut rollover, &services)
+                ).catch_unwind().await;
+                if result.is_err() {
+                    error!("🛑 Panic caught while syncing markets, continuing...");
                 }
             }
-            
-            // Trading cycle every 45 seconds
-            _ = trading_tick.tick() => {
-                // Check if we have an active market
-                let market_info = match &current_market {
-                    Some(m) => m.clone(),
-                    None => {
-                        warn!("⚠️ No active 5-minute market, skipping trading cycle");
-                        continue;
-                    }
-                };
-                
-                // Check if market is still valid
-                if let Some(ref end_date_str) = market_info.end_date {
-                    if let Some(end_date) = parse_market_end_time(end_date_str) {
-                        let now = chrono::Utc::now();
-                        let time_to_expiry = end_date.signed_duration_since(now).num_seconds();
-                        
-                        if time_to_expiry <= 0 {
-                            info!("⏭️ Current market expired, will find new one");
-                            current_market = None;
-                            continue;
-                        }
-                        
-                        info!("⏰ Trading on 5-minute market, expires in {}s", time_to_expiry);
-                    }
-                }
-                
-                // Skip first cycle to allow WebSocket to connect and receive prices
-                if first_cycle {
-                    info!("⏳ Skipping first trading cycle to allow WebSocket initialization...");
-                    first_cycle = false;
-                    continue;
-                }
-                
-                // Apply rate limiting
-                rate_limiter.wait().await;
-
-                // Run trading cycle on current 5-minute market
-                let ws_ref = ws_subscriber_trading.clone();
-                if let Err(e) = run_trading_cycle_single_market(
-                    executor.clone(),
-                    ws_ref,
-                    position_tracker.clone(),
-                    order_tracker.clone(),
-                    trade_history.clone(),
-                    stats.clone(),
-                    price_freshness.clone(),
-                    price_warning_tracker.clone(),
-                    &config.trading,
-                    &market_info,
-                ).await {
-                    error!("Trading cycle error: {}", e);
-                    stats.write().await.record_error();
+            // Slot boundary of a staged market
+            _ = async {
+                match switch_at {
+                    Some(at) => tokio::time::sleep_until(at).await,
+                    None => std::future::pending().await,
                 }
+            } => {
+                roll_over(&engine, &mut rollover, &services).await;
             }
             _ = shutdown_rx.recv() => {
-                info!("Shutdown signal received, saving state...");
+                info!("Shutdown signal received, stopping markets and saving state...");
+
+                // Pull quotes on every market before exiting
+                engine.stop_all().await;
 
                 // Save stats
                 if let Err(e) = stats.read().await.save_to_file() {
@@ -392,6 +425,9 @@ async fn load_config() -> Result<Config> {
         config.api.passphrase = env_config.api.passphrase;
     }
     
+    // Endpoints and other settings only replace file values when their variable is set
+    config.apply_env_overrides();
+    
     // Validate final configuration
     config.validate()?;
     
@@ -416,108 +452,627 @@ fn parse_log_level(level: &str) -> Level {
     }
 }
 
-/// Market info with token IDs
-#[derive(Debug, Clone)]
-struct MarketInfo {
-    condition_id: String,
-    up_token: String,
-    down_token: String,
-    end_date: Option<String>,
+/// Cancel resting orders on both tokens of a market
+async fn pull_quotes(
+    executor: &TradeExecutor,
+    order_tracker: &RwLock<OrderTracker>,
+    market_info: &SeriesMarket,
+) {
+    for token_id in [&market_info.up_token, &market_info.down_token] {
+        match executor.cancel_orders_for_market(token_id).await.map_err(|e| e.to_string()) {
+            Ok(_) => {
+                order_tracker.write().await.request_cancel_for_token(token_id);
+            }
+            Err(e) => warn!("Failed to pull quotes for {}: {}", token_id, e),
+        }
+    }
 }
 
-/// Subscribe to WebSocket for a market and return token IDs
-async fn subscribe_to_market_ws(
-    condition_id: &str,
-    ws_subscriber: Option<Arc<PolymarketWebSocket>>,
-) -> Option<(String, String)> {
-    if let Some(ref ws) = ws_subscriber {
-        info!("📡 Fetching token IDs for market: {}", condition_id);
-        
-        // Get UP and DOWN token IDs from Gamma API
-        match get_market_token_ids(condition_id).await {
-            Some((up_token, down_token)) => {
-                info!("📡 Subscribing to UP: {}, DOWN: {}", 
-                    &up_token[..20.min(up_token.len())], 
-                    &down_token[..20.min(down_token.len())]);
-                
-                let token_ids = vec![up_token.clone(), down_token.clone()];
-                ws.update_subscription(token_ids).await;
-                
-                // Set token labels - use FIRST 20 chars to match Gamma API behavior
-                let mut labels = std::collections::HashMap::new();
-                let up_short = &up_token[..20.min(up_token.len())];
-                let down_short = &down_token[..20.min(down_token.len())];
-                labels.insert(up_short.to_string(), "UP".to_string());
-                labels.insert(down_short.to_string(), "DOWN".to_string());
-                ws.set_token_labels(labels).await;
-                
-                info!("✅ WebSocket subscription updated successfully");
-                return Some((up_token, down_token));
+/// Shared services handed to every market task
+#[derive(Clone)]
+struct MarketServices {
+    executor: Arc<TradeExecutor>,
+    ws: Option<Arc<PolymarketWebSocket>>,
+    stats: Arc<RwLock<TradingStats>>,
+    price_warning_tracker: Arc<RwLock<PriceWarningTracker>>,
+    rate_limiter: Arc<RateLimiter>,
+    trading_config: Arc<TradingConfig>,
+    clock: Arc<ServerClock>,
+    strategy: Arc<dyn Strategy>,
+    /// Merges and splits UP+DOWN pairs; None without builder credentials
+    ctf: Option<Arc<CtfExecutor>>,
+    arbitrage: Arc<ArbitrageConfig>,
+    /// Spot-implied fair value; None quotes off the book
+    fair_value: Option<Arc<FairValueModel>>,
+    /// Shared price history, keyed by token id and spot symbol
+    price_history: Arc<RwLock<PriceHistory>>,
+    /// Fills waiting for their markouts
+    markouts: Arc<RwLock<MarkoutTracker>>,
+    /// Resolution lookups for settlement markouts
+    gamma: Arc<GammaApiClient>,
+}
+
+/// Re-measure the offset to the CLOB server clock
+async fn sync_server_clock(executor: &TradeExecutor, clock: &ServerClock) {
+    let sent = chrono::Utc::now();
+    match executor.server_time().await.map_err(|e| e.to_string()) {
+        Ok(time) => {
+            let offset = clock.sync(time, sent, chrono::Utc::now());
+            debug!("Server time: {} (offset {}ms)", time, offset);
+        }
+        Err(e) => warn!("Failed to get server time: {}", e),
+    }
+}
+
+/// Start tasks for series without an open market and stage each series' next slot
+async fn sync_markets(
+    engine: &MarketEngine,
+    discovery: &MarketDiscovery,
+    rollover: &mut RolloverScheduler,
+    services: &MarketServices,
+) {
+    for market in engine.reap_finished().await {
+        info!("🏁 Market task finished: {}", market.slug);
+    }
+    
+    let now = services.clock.now();
+    let running = engine.markets().await;
+    for series in discovery.series() {
+        let current = running.iter().find(|m| m.series == series.slug);
+        // The running market is replaced by the rollover at its boundary, not here
+        if current.is_none_or(|m| m.seconds_to_expiry(now) <= 0) {
+            match discovery.current_market(series, now).await {
+                Ok(Some(market)) if current.is_none_or(|m| m.condition_id != market.condition_id) => {
+                    info!("✅ Found market: {} ({})", market.slug, market.condition_id);
+                    if let Some(old) = current {
+                        info!("📤 Leaving old market: {}", old.slug);
+                        engine.stop(&old.condition_id).await;
+                    }
+                    let services = services.clone();
+                    engine.start(market, move |ctx| run_market(ctx, services)).await;
+                }
+                Ok(Some(_)) => {}
+                Ok(None) => warn!("⚠️ No open {} market", series.slug),
+                Err(e) => warn!("⚠️ Failed to resolve {} market: {}", series.slug, e),
             }
-            None => {
-                warn!("⚠️ Could not fetch token IDs for {}, using condition_id as fallback", condition_id);
-                let token_ids = vec![condition_id.to_string()];
-                ws.update_subscription(token_ids).await;
-                return None;
+        }
+        
+        // Stage the next slot so its tokens are subscribed before the boundary
+        let next_slot = slot_start(series.slot_seconds, now.timestamp()) + series.slot_seconds as i64;
+        if rollover.staged(&series.slug).is_some_and(|m| m.slot_start == next_slot) {
+            continue;
+        }
+        match discovery.next_market(series, now).await {
+            Ok(Some(market)) => {
+                info!("📥 Staged next market: {} (starts in {}s)",
+                    market.slug, (market.starts_at() - now).num_seconds());
+                rollover.stage(market);
             }
+            Ok(None) => debug!("Next {} market not listed yet", series.slug),
+            Err(e) => warn!("⚠️ Failed to resolve next {} market: {}", series.slug, e),
         }
-    } else {
-        warn!("⚠️ WebSocket not available, cannot subscribe to market");
-        None
     }
+    
+    update_market_subscription(engine, rollover, services).await;
 }
 
-/// Parse market end time from string
-fn parse_market_end_time(end_date: &str) -> Option<chrono::DateTime<chrono::Utc>> {
-    // Try RFC3339 format first
-    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(end_date) {
-        return Some(dt.with_timezone(&chrono::Utc));
+/// Switch every series whose staged market's slot has begun
+async fn roll_over(engine: &Arc<MarketEngine>, rollover: &mut RolloverScheduler, services: &MarketServices) {
+    let now = services.clock.now();
+    let due = rollover.take_due(now);
+    if due.is_empty() {
+        return;
+    }
+    
+    // Start the new slots first so no boundary waits on another market winding down
+    let running = engine.markets().await;
+    let mut retired = Vec::new();
+    for market in due {
+        if market.seconds_to_expiry(now) <= 0 {
+            warn!("⚠️ Staged market {} already expired, dropping", market.slug);
+            continue;
+        }
+        info!("🔁 Rolling {} over to {}", market.series, market.slug);
+        retired.extend(running.iter()
+            .filter(|m| m.series == market.series && m.condition_id != market.condition_id)
+            .map(|m| m.condition_id.clone()));
+        let services = services.clone();
+        engine.start(market, move |ctx| run_market(ctx, services)).await;
     }
     
-    // Try other common formats
-    let formats = [
-        "%Y-%m-%dT%H:%M:%S%.3fZ",
-        "%Y-%m-%dT%H:%M:%SZ",
-        "%Y-%m-%d %H:%M:%S",
-        "%Y-%m-%d",
-    ];
+    let stops = retired.into_iter().map(|condition_id| {
+        let engine = engine.clone();
+        async move { engine.stop(&condition_id).await }
+    });
+    futures::future::join_all(stops).await;
+    
+    update_market_subscription(engine, rollover, services).await;
+}
+
+/// Subscribe the market WebSocket to running and staged markets
+async fn update_market_subscription(engine: &MarketEngine, rollover: &RolloverScheduler, services: &MarketServices) {
+    let Some(ref ws) = services.ws else {
+        return;
+    };
+    let running = engine.markets().await;
+    let staged: Vec<&SeriesMarket> = rollover.markets().collect();
     
-    for format in &formats {
-        if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(end_date, format) {
-            return Some(chrono::DateTime::from_naive_utc_and_offset(dt, chrono::Utc));
+    let mut token_ids = Vec::with_capacity((running.len() + staged.len()) * 2);
+    // Token labels - keyed by full asset ID like the quote cache
+    let mut labels = std::collections::HashMap::new();
+    let markets = running.iter().map(|m| (m, "")).chain(staged.into_iter().map(|m| (m, " NEXT")));
+    for (market, tag) in markets {
+        token_ids.push(market.up_token.clone());
+        token_ids.push(market.down_token.clone());
+        labels.insert(market.up_token.clone(), format!("{}{} UP", market.series, tag));
+        labels.insert(market.down_token.clone(), format!("{}{} DOWN", market.series, tag));
+    }
+    
+    ws.update_subscription(token_ids).await;
+    ws.set_token_labels(labels).await;
+}
+
+/// Trade one market until it expires or the engine stops it
+async fn run_market(ctx: MarketContext, services: MarketServices) {
+    let market = &ctx.market;
+    let mut trading_tick = interval(Duration::from_secs(services.trading_config.refresh_interval));
+    
+    // Market data stream - requote early when the mid moves instead of waiting for the tick
+    let mut market_events = services.ws.as_ref().map(|ws| ws.subscribe());
+    let mut latest_mids: std::collections::HashMap<String, f64> = std::collections::HashMap::new();
+    let mut quoted_mids: std::collections::HashMap<String, f64> = std::collections::HashMap::new();
+    let mut last_cycle = Instant::now();
+    let mut last_arb_scan = Instant::now();
+
+    // Mint both outcomes up front so asks can rest from the first cycle
+    let mut state = MarketState {
+        seeded: seed_inventory(&services, &ctx).await,
+        pending_merge: None,
+        exits: PositionExitTracker::new(ExitManager::from(services.trading_config.as_ref())),
+        exiting: std::collections::HashMap::new(),
+    };
+
+    // Run a cycle as soon as quoting stops or an expiry breakpoint is crossed, not up to a tick later
+    let config = services.trading_config.as_ref();
+    let mut schedule_changes: Vec<chrono::DateTime<chrono::Utc>> = std::iter::once(config.exit_before_expiry)
+        .chain(config.expiry_schedule.iter().map(|b| b.seconds_left))
+        .map(|secs| market.end_time - chrono::Duration::seconds(secs as i64))
+        .filter(|at| *at > services.clock.now())
+        .collect();
+    schedule_changes.sort_unstable_by(|a, b| b.cmp(a));
+
+    loop {
+        let schedule_change = schedule_changes.last().map(|at| services.clock.instant_at(*at));
+        tokio::select! {
+            _ = ctx.cancel.cancelled() => break,
+            _ = async {
+                match schedule_change {
+                    Some(at) => tokio::time::sleep_until(at).await,
+                    None => std::future::pending().await,
+                }
+            } => {
+                schedule_changes.pop();
+                trading_tick.reset_immediately();
+            }
+            event = async {
+                match market_events.as_mut() {
+                    Some(rx) => rx.recv().await,
+                    None => std::future::pending().await,
+                }
+            } => {
+                match event {
+                    Ok(MarketEvent::Price(update)) => {
+                        if !ctx.owns_asset(&update.token_id) {
+                            continue;
+                        }
+                        if services.arbitrage.enabled
+                            && last_arb_scan.elapsed() >= Duration::from_millis(ARB_SCAN_INTERVAL_MS)
+                        {
+                            last_arb_scan = Instant::now();
+                            check_arbitrage(&services, &ctx).await;
+                        }
+                        let mid = update.mid();
+                        if update.bid > 0.0 && update.ask > 0.0 {
+                            services.price_history.write().await.record(&update.token_id, update.timestamp, mid);
+                        }
+                        latest_mids.insert(update.token_id.clone(), mid);
+                        let moved = quoted_mids
+                            .get(&update.token_id)
+                            .is_some_and(|quoted| (mid - quoted).abs() >= REQUOTE_MID_MOVE);
+                        if moved && last_cycle.elapsed() >= Duration::from_secs(REQUOTE_MIN_INTERVAL_SECS) {
+                            info!("⚡ [{}] Mid moved to {:.3}, requoting early", market.slug, mid);
+                            trading_tick.reset_immediately();
+                        }
+                    }
+                    Ok(MarketEvent::Connection(state)) => {
+                        match state {
+                            ConnectionState::Reconnecting { .. } | ConnectionState::GaveUp { .. } => {
+                                // Quotes can't be managed blind - pull them until the feed is back
+                                warn!("📴 [{}] Market feed down ({:?}), pulling quotes", market.slug, state);
+                                pull_quotes(&services.executor, &ctx.order_tracker, market).await;
+                                if matches!(state, ConnectionState::GaveUp { .. }) {
+                                    error!("🛑 Market feed gave up reconnecting; trading paused until the feed is restarted");
+                                }
+                            }
+                            ConnectionState::Connected => info!("📶 [{}] Market feed connected", market.slug),
+                            _ => {}
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                        debug!("Market event stream lagged, skipped {} events", skipped);
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => {
+                        market_events = None;
+                    }
+                }
+            }
+            
+            // Trading cycle every refresh_interval
+            _ = trading_tick.tick() => {
+                // Check if market is still valid
+                let time_to_expiry = market.seconds_to_expiry(services.clock.now());
+                if time_to_expiry <= 0 {
+                    info!("⏭️ Market {} expired", market.slug);
+                    break;
+                }
+                info!("⏰ Trading on {}, expires in {}s", market.slug, time_to_expiry);
+                
+                // Never quote while the market feed is down
+                if let Some(ref ws) = services.ws {
+                    let state = ws.connection_state().await;
+                    if !state.is_connected() {
+                        warn!("⚠️ Market feed not connected ({:?}), skipping trading cycle", state);
+                        continue;
+                    }
+                    // Pre-subscribed markets already have books at the boundary; fresh ones wait for theirs
+                    if ws.get_quote(&market.up_token).await.is_none() || ws.get_quote(&market.down_token).await.is_none() {
+                        info!("⏳ [{}] Waiting for first quotes, skipping trading cycle", market.slug);
+                        continue;
+                    }
+                }
+
+                // Apply rate limiting
+                services.rate_limiter.wait().await;
+
+                quoted_mids = latest_mids.clone();
+                last_cycle = Instant::now();
+
+                if let Err(e) = run_trading_cycle_single_market(&services, &ctx, &mut state).await {
+                    error!("[{}] Trading cycle error: {}", market.slug, e);
+                    services.stats.write().await.record_error();
+                }
+            }
         }
     }
     
-    None
+    // Leave the market: pull resting quotes; the budget keeps only what held shares tie up
+    pull_quotes(&services.executor, &ctx.order_tracker, market).await;
+    sync_order_stats(&ctx.order_tracker, &services.stats).await;
+    let held = ctx.position_tracker.read().await.get_total_exposure().await;
+    if held > 0.0 {
+        ctx.budget.set_exposure(&market.condition_id, held);
+    } else {
+        ctx.budget.release(&market.condition_id);
+    }
+    tokio::spawn(settle_market(services.clone(), ctx.clone()));
+}
+
+/// Once Gamma reports a finished market's resolution, record its settlement markouts
+/// and redeem the shares still held, handing their budget share back
+async fn settle_market(services: MarketServices, ctx: MarketContext) {
+    let market = &ctx.market;
+    let tokens = [&market.up_token, &market.down_token];
+    for _ in 0..SETTLEMENT_MAX_POLLS {
+        tokio::time::sleep(Duration::from_secs(SETTLEMENT_POLL_SECS)).await;
+        let held = ctx.position_tracker.read().await.get_total_exposure().await;
+        {
+            // No fills and nothing held: nothing to settle
+            let markouts = services.markouts.read().await;
+            if held <= 0.0 && !tokens.iter().any(|t| markouts.is_tracking(t)) {
+                break;
+            }
+        }
+        let resolved = match services.gamma.fetch_market_by_slug(&market.slug).await {
+            Ok(Some(resolved)) => resolved,
+            Ok(None) => continue,
+            Err(e) => {
+                debug!("Resolution check for {} failed: {}", market.slug, e);
+                continue;
+            }
+        };
+        let Some(prices) = tokens.iter().map(|t| resolved.settlement_price(t)).collect::<Option<Vec<f64>>>() else {
+            continue;
+        };
+        let mut samples = Vec::new();
+        {
+            let mut markouts = services.markouts.write().await;
+            for (token, price) in tokens.iter().zip(prices) {
+                samples.extend(markouts.settle(token, price));
+            }
+        }
+        if !samples.is_empty() {
+            info!("🏁 {} resolved, {} settlement markouts", market.slug, samples.len());
+            let mut stats = services.stats.write().await;
+            for sample in &samples {
+                stats.markouts.record(sample);
+            }
+        }
+        if held > 0.0 && !redeem_held(&services, &ctx).await {
+            continue;
+        }
+        break;
+    }
+    // Tokens of a finished market never trade again
+    ctx.settled.cancel();
+    let mut markouts = services.markouts.write().await;
+    let mut history = services.price_history.write().await;
+    for token in tokens {
+        markouts.forget(token);
+        history.remove(token);
+    }
+}
+
+/// Redeem a resolved market's held shares; true once its budget share is released
+async fn redeem_held(services: &MarketServices, ctx: &MarketContext) -> bool {
+    let market = &ctx.market;
+    let shares = {
+        let tracker = ctx.position_tracker.read().await;
+        tracker.held_shares(&market.up_token) + tracker.held_shares(&market.down_token)
+    };
+    match services.ctf.as_ref() {
+        Some(ctf) => match ctf.redeem(&market.condition_id, shares).await {
+            Ok(result) => info!("💵 [{}] Redeemed {:.2} shares (tx {})", market.slug, result.shares,
+                result.transaction_hash.as_deref().unwrap_or("pending")),
+            Err(e) => {
+                error!("❌ [{}] Redeem failed, budget stays reserved: {}", market.slug, e);
+                return false;
+            }
+        },
+        // Without the relayer the shares are redeemed by hand; stop counting them here
+        None => warn!("⚠️ [{}] Resolved with {:.2} shares held; redeem them manually", market.slug, shares),
+    }
+    let mut tracker = ctx.position_tracker.write().await;
+    for token in [&market.up_token, &market.down_token] {
+        tracker.clear_position(token).await;
+    }
+    ctx.budget.release(&market.condition_id);
+    true
+}
+
+/// Split part of the USDC balance into UP+DOWN pairs, booked at 0.50 each; returns the pairs minted
+async fn seed_inventory(services: &MarketServices, ctx: &MarketContext) -> f64 {
+    let Some(ctf) = services.ctf.as_ref() else {
+        return 0.0;
+    };
+    let split_budget = services.trading_config.split_budget;
+    let market = &ctx.market;
+    let seconds_left = (market.end_time - services.clock.now()).num_seconds();
+    if split_budget <= 0.0 || seconds_left <= services.trading_config.exit_before_expiry as i64 {
+        return 0.0;
+    }
+
+    let balance = match services.executor.get_usdc_balance().await {
+        Ok(balance) => balance,
+        Err(e) => {
+            warn!("[{}] Skipping split, balance unavailable: {}", market.slug, e);
+            return 0.0;
+        }
+    };
+    // Pairs are worth $1 each, so the split counts fully against the shared budget
+    let amount = ctx.budget.reserve(&market.condition_id, split_budget.min(balance));
+    if amount < 1.0 {
+        ctx.budget.refund(&market.condition_id, amount);
+        info!("[{}] Not enough balance or budget to split (${:.2})", market.slug, amount);
+        return 0.0;
+    }
+
+    match ctf.split(&market.condition_id, amount).await {
+        Ok(result) => {
+            info!("✅ [{}] Split ${:.2} into UP+DOWN pairs (tx {})", market.slug, result.shares,
+                result.transaction_hash.as_deref().unwrap_or("pending"));
+            let mut tracker = ctx.position_tracker.write().await;
+            tracker.apply_split(&market.up_token, &market.down_token, result.shares).await;
+            ctx.budget.set_exposure(&market.condition_id, tracker.get_total_exposure().await);
+            services.stats.write().await.record_split();
+            result.shares
+        }
+        Err(e) => {
+            ctx.budget.refund(&market.condition_id, amount);
+            error!("❌ [{}] Split failed: {}", market.slug, e);
+            services.stats.write().await.record_error();
+            0.0
+        }
+    }
+}
+
+/// Scan the cached books for a complementary-pair edge and trade it
+async fn check_arbitrage(services: &MarketServices, ctx: &MarketContext) {
+    let Some(ws) = services.ws.as_ref() else {
+        return;
+    };
+    let market = &ctx.market;
+    let (Some(up_book), Some(down_book)) = (
+        ws.get_order_book(&market.up_token).await,
+        ws.get_order_book(&market.down_token).await,
+    ) else {
+        return;
+    };
+    let (held_up, held_down) = {
+        let tracker = ctx.position_tracker.read().await;
+        (tracker.held_shares(&market.up_token), tracker.held_shares(&market.down_token))
+    };
+    // A pair costs under $1, so the remaining budget in dollars bounds the pairs we can buy
+    let Some(opp) = detect_arbitrage(&up_book, &down_book, held_up, held_down,
+        ctx.budget.remaining(), &services.arbitrage) else {
+        return;
+    };
+
+    let reserved = match opp.kind {
+        ArbKind::BuyPair => ctx.budget.reserve(&market.condition_id, opp.size * (opp.up_price + opp.down_price)),
+        ArbKind::SellPair => 0.0,
+    };
+    let arb = ArbContext {
+        executor: services.executor.as_ref(),
+        order_tracker: &ctx.order_tracker,
+        position_tracker: &ctx.position_tracker,
+        stats: &services.stats,
+    };
+    let outcome = execute_arbitrage(&arb, &opp, &services.arbitrage).await;
+    track_markouts(services, market, &outcome.fills).await;
+    ctx.budget.refund(&market.condition_id, reserved);
+    let exposure = ctx.position_tracker.read().await.get_total_exposure().await;
+    ctx.budget.set_exposure(&market.condition_id, exposure);
+}
+
+/// Merge complete pairs beyond those kept from the split at open
+/// A merge that timed out may still be mined, so it is settled before another is sent.
+async fn merge_pairs(services: &MarketServices, ctx: &MarketContext, state: &mut MarketState, ctf: &CtfExecutor) {
+    let market = &ctx.market;
+    if let Some((transaction_id, pairs)) = state.pending_merge.clone() {
+        match ctf.transaction(&transaction_id).await {
+            Ok(tx) if tx.is_confirmed() => {
+                info!("✅ [{}] Earlier merge of {:.2} pairs mined (tx {})", market.slug, pairs,
+                    tx.transaction_hash.as_deref().unwrap_or("pending"));
+                state.pending_merge = None;
+                book_merge(services, ctx, pairs).await;
+            }
+            Ok(tx) if tx.is_failed() => {
+                warn!("⚠️ [{}] Earlier merge {} failed ({}), pairs still held", market.slug, transaction_id, tx.state);
+                state.pending_merge = None;
+            }
+            Ok(tx) => {
+                info!("⏳ [{}] Merge {} still {}, not merging again", market.slug, transaction_id, tx.state);
+                return;
+            }
+            Err(e) => {
+                warn!("⚠️ [{}] Could not check merge {}: {}", market.slug, transaction_id, e);
+                return;
+            }
+        }
+    }
+
+    // Seeded pairs are kept as ask inventory; the ones sold or merged since no longer hold any back
+    let mergeable = {
+        let tracker = ctx.position_tracker.read().await;
+        let pairs = tracker.held_shares(&market.up_token).min(tracker.held_shares(&market.down_token));
+        state.seeded = state.seeded.min(pairs);
+        tracker
+            .check_merge_opportunity(&market.up_token, &market.down_token,
+                services.trading_config.merge_threshold + state.seeded)
+            .map(|pairs| pairs - state.seeded)
+    };
+    let Some(merge_amount) = mergeable else {
+        return;
+    };
+    info!("💡 Merge opportunity: {:.2} pairs for {}", merge_amount, market.slug);
+    match ctf.merge(&market.condition_id, merge_amount).await {
+        Ok(result) => {
+            info!("✅ Merged {:.2} pairs into USDC (tx {})",
+                result.shares, result.transaction_hash.as_deref().unwrap_or("pending"));
+            book_merge(services, ctx, result.shares).await;
+        }
+        Err(RelayerError::Timeout(transaction_id)) => {
+            warn!("⏳ [{}] Merge {} not confirmed in time, checking it next cycle", market.slug, transaction_id);
+            state.pending_merge = Some((transaction_id, from_base_units(to_base_units(merge_amount))));
+        }
+        Err(e) => error!("❌ Merge failed for {}: {}", market.slug, e),
+    }
+}
+
+/// Take merged pairs out of both legs
+async fn book_merge(services: &MarketServices, ctx: &MarketContext, pairs: f64) {
+    let market = &ctx.market;
+    let mut tracker = ctx.position_tracker.write().await;
+    tracker.apply_merge(&market.up_token, &market.down_token, pairs).await;
+    ctx.budget.set_exposure(&market.condition_id, tracker.get_total_exposure().await);
+    services.stats.write().await.record_merge();
+}
+
+/// Per-market state carried across trading cycles
+struct MarketState {
+    /// Pairs split at open still held as ask inventory
+    seeded: f64,
+    /// Merge submitted but not confirmed in time: (relayer transaction ID, pairs)
+    pending_merge: Option<(String, f64)>,
+    /// Hold clocks and exit rules for held tokens
+    exits: PositionExitTracker,
+    /// Tokens with an exit in progress, counted once in stats
+    exiting: std::collections::HashMap<String, ExitReason>,
+}
+
+/// Check held tokens against the exit rules and place SELL orders for those to exit
+/// Complete UP+DOWN pairs are hedged and left to merge, so only the excess leg is checked
+/// Returns the tokens an exit order was placed for
+async fn run_exits(
+    services: &MarketServices,
+    ctx: &MarketContext,
+    state: &mut MarketState,
+    snapshot: &MarketSnapshot,
+) -> Vec<String> {
+    let config = services.trading_config.as_ref();
+    {
+        let order_tracker = ctx.order_tracker.read().await;
+        ctx.exit_orders.write().await.retain(|order_id| order_tracker.get_by_id(order_id).is_some());
+    }
+    let pairs = snapshot.up.shares.min(snapshot.down.shares);
+    let mut mids = std::collections::HashMap::new();
+    for outcome in [Outcome::Up, Outcome::Down] {
+        let token = snapshot.token(outcome);
+        let unpaired = token.shares - pairs;
+        let avg_price = ctx.position_tracker.read().await
+            .get_position(&token.token_id).await
+            .map(|p| p.avg_price)
+            .unwrap_or(0.0);
+        if sellable_size(unpaired, unpaired) > 0.0 {
+            state.exits.update_position(token.token_id.clone(), outcome.label().to_string(), unpaired, avg_price);
+            mids.insert(token.token_id.clone(), token.mid());
+        } else {
+            state.exits.remove_position(&token.token_id);
+            state.exiting.remove(&token.token_id);
+        }
+    }
+
+    let time_to_expiry = Duration::from_secs(snapshot.seconds_to_expiry.max(0) as u64);
+    let mut exited = Vec::new();
+    for (token_id, check) in state.exits.check_all_positions(&mids, Some(time_to_expiry)) {
+        let Some(reason) = check.exit_reason else {
+            continue;
+        };
+        state.exits.exit_manager().log_check(&token_id, &check);
+        let outcome = if token_id == snapshot.up.token_id { Outcome::Up } else { Outcome::Down };
+        let token = snapshot.token(outcome);
+        let price = config.exit_styles.for_reason(reason)
+            .sell_price(token.bid, token.ask)
+            .max(config.min_price);
+        let Some(position) = state.exits.get_position(&token_id) else {
+            continue;
+        };
+        let (size, avg_price) = (sellable_size(position.size, position.size), position.avg_price);
+        let quote = DesiredQuote::sell(snapshot, outcome, price, size);
+        let Some(order_id) = place_quote(&services.executor, &ctx.order_tracker, &ctx.budget, config,
+            &ctx.market.condition_id, &quote, size).await else {
+            continue;
+        };
+        // PnL is booked as the order fills, at the execution price
+        ctx.exit_orders.write().await.insert(order_id, reason, avg_price);
+        // Unfilled exits are cancelled and repriced next cycle; count each position once
+        if state.exiting.insert(token_id.clone(), reason).is_none() {
+            services.stats.write().await.record_exit(reason.label());
+        }
+        exited.push(token_id);
+    }
+    // A position that recovered back into the hold band starts over
+    state.exiting.retain(|token_id, _| exited.contains(token_id));
+    exited
 }
 
 /// Run trading cycle on a single market (for 5-minute market strategy)
-#[allow(clippy::too_many_arguments)]
-async fn run_trading_cycle_single_market(
-    executor: Arc<TradeExecutor>,
-    ws: Option<Arc<PolymarketWebSocket>>,
-    position_tracker: Arc<RwLock<PositionTracker>>,
-    order_tracker: Arc<RwLock<OrderTracker>>,
-    trade_history: Arc<TradeHistory>,
-    stats: Arc<RwLock<TradingStats>>,
-    price_freshness: Arc<RwLock<PriceFreshness>>,
-    price_warning_tracker: Arc<RwLock<PriceWarningTracker>>,
-    trading_config: &TradingConfig,
-    market_info: &MarketInfo,
-) -> Result<()> {
+async fn run_trading_cycle_single_market(services: &MarketServices, ctx: &MarketContext, state: &mut MarketState) -> Result<()> {
     // Wrap the actual implementation with panic catching
-    let result = std::panic::AssertUnwindSafe(run_trading_cycle_single_market_inner(
-        executor,
-        ws,
-        position_tracker,
-        order_tracker,
-        trade_history,
-        stats,
-        price_freshness,
-        price_warning_tracker,
-        trading_config,
-        market_info,
-    )).catch_unwind().await;
+    let result = std::panic::AssertUnwindSafe(run_trading_cycle_single_market_inner(services, ctx, state))
+        .catch_unwind()
+        .await;
     
     match result {
         Ok(inner_result) => inner_result,
@@ -530,19 +1085,16 @@ async fn run_trading_cycle_single_market(
 }
 
 /// Inner implementation of trading cycle
-#[allow(clippy::too_many_arguments)]
-async fn run_trading_cycle_single_market_inner(
-    executor: Arc<TradeExecutor>,
-    ws: Option<Arc<PolymarketWebSocket>>,
-    position_tracker: Arc<RwLock<PositionTracker>>,
-    order_tracker: Arc<RwLock<OrderTracker>>,
-    trade_history: Arc<TradeHistory>,
-    stats: Arc<RwLock<TradingStats>>,
-    _price_freshness: Arc<RwLock<PriceFreshness>>,
-    price_warning_tracker: Arc<RwLock<PriceWarningTracker>>,
-    trading_config: &TradingConfig,
-    market_info: &MarketInfo,
-) -> Result<()> {
+async fn run_trading_cycle_single_market_inner(services: &MarketServices, ctx: &MarketContext, state: &mut MarketState) -> Result<()> {
+    let executor = services.executor.clone();
+    let ws = services.ws.clone();
+    let stats = services.stats.clone();
+    let price_warning_tracker = services.price_warning_tracker.clone();
+    let trading_config = services.trading_config.as_ref();
+    let position_tracker = ctx.position_tracker.clone();
+    let order_tracker = ctx.order_tracker.clone();
+    let budget = ctx.budget.as_ref();
+    let market_info = &ctx.market;
     info!("Running trading cycle on single market...");
     let cycle_start = Instant::now();
 
@@ -555,11 +1107,10 @@ async fn run_trading_cycle_single_market_inner(
     info!("DEBUG: down_token_id length: {}, value: {}", down_token_id.len(), &down_token_id[..down_token_id.len().min(30)]);
     
     // Check WebSocket prices - if not available, skip this cycle (non-blocking)
-    let ws_prices_available = if let Some(ref ws) = ws {
+    if let Some(ref ws) = ws {
         let prices = ws.get_all_prices().await;
         if !prices.is_empty() {
             info!("✅ WebSocket prices available: {} tokens", prices.len());
-            true
         } else {
             warn!("⚠️ WebSocket prices not available, skipping trading cycle");
             return Ok(());
@@ -567,35 +1118,22 @@ async fn run_trading_cycle_single_market_inner(
     } else {
         warn!("⚠️ WebSocket not available, skipping trading cycle");
         return Ok(());
-    };
+    }
 
-    // Calculate inventory skew and status once, reuse throughout the function
-    let (inventory_skew, status, should_return) = {
-        let tracker = position_tracker.read().await;
-        let skew = tracker.calculate_inventory_skew().await;
-        let status = tracker.get_inventory_status().await;
-        let should_return = status.total_value >= trading_config.max_total_position;
-        (skew, status, should_return)
+    // Open orders were pulled last cycle, so this market's exposure is its position value
+    let (exposure, should_return) = {
+        let exposure = position_tracker.read().await.get_total_exposure().await;
+        budget.set_exposure(&market_info.condition_id, exposure);
+        (exposure, budget.remaining() <= 0.0)
     };
-    info!("Current inventory skew: {:.2}", inventory_skew);
-    
-    info!("📊 Inventory: UP=${:.2} | DOWN=${:.2} | Total=${:.2} | Skew={:.1}%",
-        status.up_value, status.down_value, status.total_value, status.skew * 100.0);
 
     // Check total position limit
     if should_return {
-        warn!("Total position limit reached: ${:.2} >= ${:.2}",
-            status.total_value, trading_config.max_total_position);
+        warn!("Total position limit reached: ${:.2} >= ${:.2} (this market ${:.2})",
+            budget.total(), budget.limit(), exposure);
         return Ok(());
     }
 
-    // Check merge opportunity first (Python feature)
-    if let Some(merge_amount) = position_tracker.read().await.check_merge_opportunity(
-        &up_token_id, trading_config.merge_threshold) {
-        info!("💡 Merge opportunity: {:.2} shares for {}", merge_amount, up_token_id);
-        stats.write().await.record_merge();
-    }
-
     // Get prices from WebSocket for both UP and DOWN tokens
     let (up_price, down_price) = if let Some(ref ws) = ws {
         let up = ws.get_price(&up_token_id).await.map(|(bid, ask)| (bid + ask) / 2.0);
@@ -623,6 +1161,11 @@ async fn run_trading_cycle_single_market_inner(
 
     info!("💰 Prices - UP: {:.4}, DOWN: {:.4}", up_price, down_price);
 
+    let status = position_tracker.read().await
+        .market_inventory(&up_token_id, &down_token_id, up_price, down_price);
+    info!("📊 Inventory: UP=${:.2} | DOWN=${:.2} | Total=${:.2} | Skew={:.1}%",
+        status.up_value, status.down_value, status.total_value, status.skew * 100.0);
+
     // Validate price range with min/max price (Python style)
     if up_price < trading_config.min_price || up_price > trading_config.max_price {
         warn!("UP price {:.4} outside valid range [{:.2}, {:.2}]",
@@ -658,114 +1201,19 @@ async fn run_trading_cycle_single_market_inner(
         );
     }
 
-    // CRITICAL FIX: Check for fills BEFORE cancelling to avoid double-counting
-    // Step 1: Get tracked order IDs for both tokens
-    let tracked_orders_up: Vec<String> = {
-        let tracker = order_tracker.read().await;
-        tracker.get_all_orders()
-            .values()
-            .filter(|o| o.token == up_token_id)
-            .map(|o| o.order_id.clone())
-            .collect()
-    };
-    
-    let tracked_orders_down: Vec<String> = {
-        let tracker = order_tracker.read().await;
-        tracker.get_all_orders()
-            .values()
-            .filter(|o| o.token == down_token_id)
-            .map(|o| o.order_id.clone())
-            .collect()
-    };
-    
-    // Step 2: Check which orders have filled (no longer in open orders)
-    let filled_order_ids_up = if !tracked_orders_up.is_empty() {
-        match executor.get_filled_orders(&up_token_id, &tracked_orders_up).await {
-            Ok(filled) => {
-                if !filled.is_empty() {
-                    info!("🎯 Detected {} filled orders for UP: {:?}", 
-                        filled.len(), filled);
-                }
-                filled
-            }
-            Err(e) => {
-                warn!("⚠️ Failed to check filled orders for UP: {}", e);
-                vec![]
-            }
-        }
-    } else {
-        vec![]
-    };
-    
-    let filled_order_ids_down = if !tracked_orders_down.is_empty() {
-        match executor.get_filled_orders(&down_token_id, &tracked_orders_down).await {
-            Ok(filled) => {
-                if !filled.is_empty() {
-                    info!("🎯 Detected {} filled orders for DOWN: {:?}", 
-                        filled.len(), filled);
-                }
-                filled
-            }
-            Err(e) => {
-                warn!("⚠️ Failed to check filled orders for DOWN: {}", e);
-                vec![]
-            }
-        }
-    } else {
-        vec![]
-    };
-    
-    // Step 3: Update positions for filled orders
-    for filled_id in &filled_order_ids_up {
-        if let Some(order) = order_tracker.read().await.get_all_orders()
-            .values()
-            .find(|o| o.order_id == *filled_id && o.token == up_token_id) {
-            
-            let side = if order.side == "BUY" { Side::Buy } else { Side::Sell };
-            let size = order.size;
-            let price = order.price;
-            
-            info!("📈 Updating position for filled UP order {}: {:?} {} @ {}", 
-                filled_id, side, size, price);
-            
-            position_tracker.write().await.update_position(
-                &up_token_id,
-                side,
-                size,
-                price,
-            ).await;
-            
-            stats.write().await.record_order_filled(size);
-        }
-    }
-    
-    for filled_id in &filled_order_ids_down {
-        if let Some(order) = order_tracker.read().await.get_all_orders()
-            .values()
-            .find(|o| o.order_id == *filled_id && o.token == down_token_id) {
-            
-            let side = if order.side == "BUY" { Side::Buy } else { Side::Sell };
-            let size = order.size;
-            let price = order.price;
-            
-            info!("📈 Updating position for filled DOWN order {}: {:?} {} @ {}", 
-                filled_id, side, size, price);
-            
-            position_tracker.write().await.update_position(
-                &down_token_id,
-                side,
-                size,
-                price,
-            ).await;
-            
-            stats.write().await.record_order_filled(size);
-        }
+    // Step 1-3: Book fills from CLOB trades before cancelling
+    // Only newly matched size is booked, so re-checking is safe
+    for token_id in [&up_token_id, &down_token_id] {
+        let fills = reconcile_order_fills(&executor, &order_tracker, &position_tracker, &stats, token_id).await;
+        record_exit_fills(&stats, ctx, &fills).await;
+        track_markouts(services, market_info, &fills).await;
     }
     
     // Step 4: Now cancel remaining open orders for both tokens
-    match executor.cancel_orders_for_market(&up_token_id).await {
+    match executor.cancel_orders_for_market(&up_token_id).await.map_err(|e| e.to_string()) {
         Ok(result) => {
             info!("✅ Cancelled {} existing orders for UP {}", result.cancelled, up_token_id);
+            order_tracker.write().await.request_cancel_for_token(&up_token_id);
         }
         Err(e) => {
             error!("❌ Failed to cancel orders for UP {}: {}", up_token_id, e);
@@ -774,9 +1222,10 @@ async fn run_trading_cycle_single_market_inner(
         }
     }
     
-    match executor.cancel_orders_for_market(&down_token_id).await {
+    match executor.cancel_orders_for_market(&down_token_id).await.map_err(|e| e.to_string()) {
         Ok(result) => {
             info!("✅ Cancelled {} existing orders for DOWN {}", result.cancelled, down_token_id);
+            order_tracker.write().await.request_cancel_for_token(&down_token_id);
         }
         Err(e) => {
             error!("❌ Failed to cancel orders for DOWN {}: {}", down_token_id, e);
@@ -785,10 +1234,6 @@ async fn run_trading_cycle_single_market_inner(
         }
     }
 
-    // Step 5: Clear tracked orders for both tokens (including filled ones)
-    order_tracker.write().await.clear_orders_for_token(&up_token_id);
-    order_tracker.write().await.clear_orders_for_token(&down_token_id);
-
     // Wait for cancellation to propagate and verify
     tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
     
@@ -822,277 +1267,225 @@ async fn run_trading_cycle_single_market_inner(
         tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
     }
 
-    // CRITICAL FIX: Recalculate inventory skew after processing fills
-    let inventory_skew = position_tracker.read().await.calculate_inventory_skew().await;
-    info!("🔄 Recalculated inventory skew after fills: {:.2}", inventory_skew);
+    // Step 5: Book fills that landed before the cancel and close out cancelled orders
+    // Orders the CLOB has not reported as cancelled stay tracked for the next cycle
+    for token_id in [&up_token_id, &down_token_id] {
+        let fills = reconcile_order_fills(&executor, &order_tracker, &position_tracker, &stats, token_id).await;
+        record_exit_fills(&stats, ctx, &fills).await;
+        track_markouts(services, market_info, &fills).await;
+    }
 
-    // Check skip sides and get position limits for UP and DOWN separately
-    // Matches Python: only limit BUY orders, SELL is always allowed
-    let (
-        (skip_buy_up, reason_buy_up),
-        (skip_buy_down, reason_buy_down),
-        buy_limit_up,
-        buy_limit_down,
-    ) = {
-        let skew = inventory_skew; // Use the pre-calculated skew
-        
-        // UP logic: Skip buying UP when skew is high (UP too much)
-        // Python: max_skew = order_size * 0.4 (normalized to ~0.4)
-        let max_skew_threshold = 0.4;  // Changed from 0.7 to match Python
-        let skip_buy_up = skew > max_skew_threshold;
-        let reason_buy_up = if skip_buy_up {
-            format!("UP inventory too high ({:.1}%), skip buying UP", skew * 100.0)
-        } else {
-            "OK to buy UP".to_string()
-        };
-        
-        // DOWN logic: Skip buying DOWN when skew is low (DOWN too much)
-        let skip_buy_down = skew < -max_skew_threshold;
-        let reason_buy_down = if skip_buy_down {
-            format!("DOWN inventory too high ({:.1}%), skip buying DOWN", skew.abs() * 100.0)
-        } else {
-            "OK to buy DOWN".to_string()
-        };
-        
-        // Calculate limits based on skew (Python: get_position_limit)
-        let base_limit = trading_config.max_position / 2.0;
-        
-        let buy_limit_up = if skew > 0.5 {
-            base_limit * (1.0 - skew)  // Stricter when UP high
-        } else {
-            base_limit * (1.0 + skew.abs())  // Looser when UP low
-        };
-        
-        let buy_limit_down = if skew < -0.5 {
-            base_limit * (1.0 - skew.abs())  // Stricter when DOWN high
-        } else {
-            base_limit * (1.0 + skew.abs())  // Looser when DOWN low
-        };
-        
+    // Step 6: Merge complete UP+DOWN pairs back into USDC (Python feature)
+    if let Some(ctf) = services.ctf.as_ref() {
+        merge_pairs(services, ctx, state, ctf).await;
+    }
+
+    // CRITICAL FIX: Recalculate inventory after processing fills
+    let (inventory, up_shares, down_shares) = {
+        let tracker = position_tracker.read().await;
         (
-            (skip_buy_up, reason_buy_up),
-            (skip_buy_down, reason_buy_down),
-            buy_limit_up,
-            buy_limit_down,
+            tracker.market_inventory(&up_token_id, &down_token_id, up_price, down_price),
+            tracker.held_shares(&up_token_id),
+            tracker.held_shares(&down_token_id),
         )
     };
-    
-    if skip_buy_up {
-        info!("Skipping Buy for UP {}: {}", up_token_id, reason_buy_up);
-    }
-    
-    if skip_buy_down {
-        info!("Skipping Buy for DOWN {}: {}", down_token_id, reason_buy_down);
-    }
+    let inventory_skew = inventory.skew;
+    info!("🔄 Recalculated inventory skew after fills: {:.2}", inventory_skew);
 
-    // Calculate prices with spread adjustment based on inventory skew
-    let spread = trading_config.spread;
-    
-    // Adjust prices based on inventory skew
-    // Matches Python: inventory_adjust = inventory_skew * 0.01
-    // Both bid and ask move in same direction with skew
-    let skew_adjustment = inventory_skew * 0.01; // 1% adjustment per unit of skew
-    
-    // UP token prices - Python: bid = mid - half_spread + inventory_adjust
-    let up_bid_price = ((up_price - spread / 2.0 + skew_adjustment) * 100.0).round() / 100.0;
-    let up_bid_price = up_bid_price.max(trading_config.safe_range_low).min(trading_config.safe_range_high);
-    let up_ask_price = ((up_price + spread / 2.0 + skew_adjustment) * 100.0).round() / 100.0;
-    let up_ask_price = up_ask_price.max(trading_config.safe_range_low).min(trading_config.safe_range_high);
-    
-    // DOWN token prices - same adjustment as UP
-    let down_bid_price = ((down_price - spread / 2.0 + skew_adjustment) * 100.0).round() / 100.0;
-    let down_bid_price = down_bid_price.max(trading_config.safe_range_low).min(trading_config.safe_range_high);
-    let down_ask_price = ((down_price + spread / 2.0 + skew_adjustment) * 100.0).round() / 100.0;
-    let down_ask_price = down_ask_price.max(trading_config.safe_range_low).min(trading_config.safe_range_high);
-
-    info!("💰 Order prices - UP: bid={:.4}, ask={:.4} | DOWN: bid={:.4}, ask={:.4}",
-        up_bid_price, up_ask_price, down_bid_price, down_ask_price);
-
-    // Python-style balance check with 15% buffer
-    let up_need = up_price * trading_config.order_size;
-    let down_need = down_price * trading_config.order_size;
-    
-    // Calculate actual needs based on skew (only buy the side that needs it)
-    let (actual_up_need, actual_down_need) = if inventory_skew > 0.4 {
-        // UP too much, only buy DOWN
-        (0.0, down_need)
-    } else if inventory_skew < -0.4 {
-        // DOWN too much, only buy UP
-        (up_need, 0.0)
-    } else {
-        // Balanced, buy both
-        (up_need, down_need)
-    };
-    
-    const BUFFER_RATIO: f64 = 1.15; // 15% buffer like Python
-    let total_need = (actual_up_need + actual_down_need) * BUFFER_RATIO;
-    
-    // Check balance
-    let balance = executor.get_usdc_balance().await.unwrap_or(0.0);
-    if balance < total_need {
-        warn!("⚠️ Insufficient balance (with buffer): {:.2} < {:.2} (need UP:{:.2} + DOWN:{:.2} × {:.2})",
-            balance, total_need, actual_up_need, actual_down_need, BUFFER_RATIO);
-        warn!("⏹️ Skipping orders - need both sides for hedge");
+    // Snapshot for the strategy
+    let Some(ref ws) = ws else {
         return Ok(());
+    };
+    let fair_up = match &services.fair_value {
+        Some(model) => model.fair_up(market_info, services.clock.now()).await,
+        None => None,
+    };
+    if let Some(fair) = &fair_up {
+        info!("📈 Fair UP {:.3} (spot {:.2} vs open {:.2}, vol {:.0}%, {:.0}s left)",
+            fair.probability, fair.spot, fair.strike, fair.volatility * 100.0, fair.seconds_left);
     }
-    info!("✅ Balance sufficient (with buffer): {:.2} >= {:.2}", balance, total_need);
+    let fair = [fair_up.as_ref().map(|f| f.probability), fair_up.as_ref().map(|f| 1.0 - f.probability)];
+    let mid_vol = |token_id: &str, history: &PriceHistory| history.close_to_close_volatility(
+        token_id,
+        chrono::Duration::seconds(MID_VOL_INTERVAL_SECS),
+        chrono::Duration::seconds(MID_VOL_WINDOW_SECS),
+    );
+    let mut tokens = Vec::with_capacity(2);
+    for ((token_id, shares), fair) in [(&up_token_id, up_shares), (&down_token_id, down_shares)].into_iter().zip(fair) {
+        let Some((bid, ask)) = ws.get_quote(token_id).await.and_then(|q| q.bid_ask()) else {
+            warn!("No two-sided quote for {}, skipping", token_id);
+            return Ok(());
+        };
+        tokens.push(TokenSnapshot {
+            token_id: token_id.clone(),
+            bid,
+            ask,
+            book: ws.get_order_book(token_id).await,
+            shares,
+            fair,
+            volatility: mid_vol(token_id, &*services.price_history.read().await),
+        });
+    }
+    let down = tokens.pop().expect("two tokens");
+    let up = tokens.pop().expect("two tokens");
+    let snapshot = MarketSnapshot {
+        market: market_info.clone(),
+        up,
+        down,
+        inventory,
+        inventory_skew,
+        seconds_to_expiry: market_info.seconds_to_expiry(services.clock.now()),
+        balance: executor.get_usdc_balance().await.unwrap_or(0.0),
+        budget_remaining: budget.remaining(),
+        recent_markout: stats.read().await.markouts.recent(&market_info.series),
+    };
 
-    // Buy-and-hold strategy: Only place BUY orders on UP and DOWN tokens
-    // Wait for market settlement, no active market making
-    
-    // Calculate actual sizes based on skew
-    let base_size = trading_config.order_size;
-    let remaining = trading_config.max_total_position - status.total_value;
-    
-    let (up_size, down_size) = if inventory_skew > 0.4 {
-        // UP too much, only buy DOWN
-        warn!("⚠️ UP skew too high ({:.1}), buying only DOWN to balance", inventory_skew);
-        (0.0, base_size.min(remaining))
-    } else if inventory_skew < -0.4 {
-        // DOWN too much, only buy UP
-        warn!("⚠️ DOWN skew too high ({:.1}), buying only UP to balance", inventory_skew.abs());
-        (base_size.min(remaining), 0.0)
-    } else {
-        // Balanced, buy both
-        (base_size.min(remaining / 2.0), base_size.min(remaining / 2.0))
+    // Step 7: Exit positions the exit manager flags; those tokens sit out of quoting this cycle
+    let exited = run_exits(services, ctx, state, &snapshot).await;
+
+    // Inside exit_before_expiry only the exits above run; otherwise quote on the expiry schedule
+    let Some(quote_config) = trading_config.for_time_to_expiry(snapshot.seconds_to_expiry) else {
+        info!("🏁 [{}] {}s to expiry, quoting stopped and flattening", market_info.slug, snapshot.seconds_to_expiry);
+        sync_order_stats(&order_tracker, &stats).await;
+        return Ok(());
     };
-    
-    if up_size == 0.0 && down_size == 0.0 {
-        warn!("⏹️ No size to trade");
+    if let Some(breakpoint) = trading_config.expiry_breakpoint(snapshot.seconds_to_expiry) {
+        info!("⏳ [{}] Expiry schedule: spread x{:.2}, size x{:.2}",
+            market_info.slug, breakpoint.spread_multiplier, breakpoint.size_multiplier);
+    }
+    let quotes: Vec<DesiredQuote> = services.strategy.quote(&snapshot, &quote_config)
+        .into_iter()
+        .filter(|q| !exited.contains(&q.token_id))
+        .collect();
+    if quotes.is_empty() {
+        warn!("⏹️ [{}] No quotes this cycle", services.strategy.name());
+        sync_order_stats(&order_tracker, &stats).await;
         return Ok(());
     }
-    
-    info!("📊 Will place: UP={:.1}, DOWN={:.1}", up_size, down_size);
+    info!("📊 [{}] Will place: {}", services.strategy.name(), quotes.iter()
+        .map(|q| format!("{} {:?} {:.1}@{:.2}", q.outcome.label(), q.side, q.size, q.price))
+        .collect::<Vec<_>>().join(", "));
 
-    // Place UP buy order first (skip balance check as per Python)
-    let mut placed_up = 0.0;
-    let mut placed_down = 0.0;
-    
-    if up_size > 0.0 && !skip_buy_up {
-        info!("🔍 UP: BUY @ {:.4} size={:.1}", up_bid_price, up_size);
-        match executor.place_order_complete(
-            &up_token_id,
-            Side::Buy,
-            up_bid_price,
-            up_size,
-            trading_config.safe_range_low,
-            trading_config.safe_range_high,
-        ).await {
-            Ok(Some(order_id)) => {
-                info!("✅ UP order placed: {}", order_id);
-                placed_up = up_size;
-                stats.write().await.record_order_placed(up_size);
-                order_tracker.write().await.track_order(
-                    up_token_id.clone(), order_id, "BUY".to_string(), up_bid_price, up_size);
-            }
-            Ok(None) => {
-                warn!("❌ UP order failed (returned None)");
-            }
-            Err(e) => {
-                warn!("❌ UP order failed: {}", e);
+    let mut placed = 0;
+    let mut first_buy = true;
+    for quote in &quotes {
+        let size = if quote.side == Side::Buy {
+            // Reserve notional from the shared budget; other markets may have taken some since
+            let size = reserve_size(budget, &market_info.condition_id, quote.price, quote.size);
+            // Re-check balance after the first BUY (as per Python)
+            if size > 0.0 && !first_buy {
+                let balance_now = executor.get_usdc_balance().await.unwrap_or(0.0);
+                let need_now = quote.price * size * BALANCE_BUFFER_RATIO;
+                if balance_now < need_now {
+                    warn!("⚠️ Insufficient balance for {} after previous order: {:.2} < {:.2}",
+                        quote.outcome.label(), balance_now, need_now);
+                    budget.refund(&market_info.condition_id, quote.price * size);
+                    continue;
+                }
             }
-        }
-    }
-
-    // Place DOWN buy order (check balance again as per Python)
-    if down_size > 0.0 && !skip_buy_down {
-        // Re-check balance after UP order
-        let balance_after_up = executor.get_usdc_balance().await.unwrap_or(0.0);
-        let down_need_now = down_bid_price * down_size * BUFFER_RATIO;
-        
-        if balance_after_up < down_need_now {
-            warn!("⚠️ Insufficient balance for DOWN after UP order: {:.2} < {:.2}",
-                balance_after_up, down_need_now);
+            first_buy = false;
+            size
         } else {
-            info!("🔍 DOWN: BUY @ {:.4} size={:.1}", down_bid_price, down_size);
-            match executor.place_order_complete(
-                &down_token_id,
-                Side::Buy,
-                down_bid_price,
-                down_size,
-                trading_config.safe_range_low,
-                trading_config.safe_range_high,
-            ).await {
-                Ok(Some(order_id)) => {
-                    info!("✅ DOWN order placed: {}", order_id);
-                    placed_down = down_size;
-                    stats.write().await.record_order_placed(down_size);
-                    order_tracker.write().await.track_order(
-                        down_token_id.clone(), order_id, "BUY".to_string(), down_bid_price, down_size);
-                }
-                Ok(None) => {
-                    warn!("❌ DOWN order failed (returned None)");
-                }
-                Err(e) => {
-                    warn!("❌ DOWN order failed: {}", e);
-                }
+            // Asks only against shares we still own; fills may have landed since the snapshot
+            let held = position_tracker.read().await.held_shares(&quote.token_id);
+            let size = sellable_size(held, quote.size);
+            if size <= 0.0 {
+                info!("Skipping {} ask: no shares held", quote.outcome.label());
             }
+            size
+        };
+        if size <= 0.0 {
+            continue;
+        }
+        if place_quote(&executor, &order_tracker, budget, trading_config, &market_info.condition_id, quote, size).await.is_some() {
+            placed += 1;
         }
     }
 
-    info!("✅ Trading cycle completed: UP={:.1}, DOWN={:.1}", placed_up, placed_down);
+    sync_order_stats(&order_tracker, &stats).await;
+
+    info!("✅ Trading cycle completed: {}/{} quotes placed", placed, quotes.len());
     info!("⏱️ Trading cycle took: {:?}", cycle_start.elapsed());
     Ok(())
 }
 
-/// Place order for a specific side
-#[allow(clippy::too_many_arguments)]
-async fn place_side_order(
+/// Book exit PnL for fills of exit orders
+async fn record_exit_fills(stats: &RwLock<TradingStats>, ctx: &MarketContext, fills: &[Fill]) {
+    let exit_orders = ctx.exit_orders.read().await;
+    for fill in fills {
+        if let Some((reason, pnl)) = exit_orders.pnl_for(fill) {
+            info!("📤 [{}] Exit fill ({}): {:.2} @ {:.4}, PnL {:.4}",
+                ctx.market.slug, reason.label(), fill.size, fill.price, pnl);
+            stats.write().await.record_exit_fill(pnl);
+        }
+    }
+}
+
+/// Follow newly booked fills for markouts
+async fn track_markouts(services: &MarketServices, market: &SeriesMarket, fills: &[Fill]) {
+    if fills.is_empty() {
+        return;
+    }
+    let now = services.clock.now();
+    let mut markouts = services.markouts.write().await;
+    for fill in fills {
+        markouts.track(&market.series, fill, now);
+    }
+}
+
+/// Place one strategy quote and return its order ID; BUY notional is refunded to the budget if the order fails
+async fn place_quote(
     executor: &TradeExecutor,
     order_tracker: &RwLock<OrderTracker>,
-    trade_history: &TradeHistory,
-    stats: &RwLock<TradingStats>,
-    token_id: &str,
-    side: Side,
-    price: f64,
+    budget: &PositionBudget,
+    trading_config: &TradingConfig,
+    market: &str,
+    quote: &DesiredQuote,
     size: f64,
-    safe_low: f64,
-    safe_high: f64,
-    outcome: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
-    // Use the complete order placement with validation
-    match executor.place_order_complete(
-        token_id,
-        side,
-        price,
+) -> Option<String> {
+    let label = quote.outcome.label();
+    let side = format!("{:?}", quote.side).to_uppercase();
+    info!("🔍 {}: {} @ {:.4} size={:.1}", label, side, quote.price, size);
+    let local_id = order_tracker.write().await.begin_submit(
+        quote.token_id.clone(), side, quote.price, size);
+    let error = match executor.place_order_complete(
+        &quote.token_id,
+        quote.side,
+        quote.price,
         size,
-        safe_low,
-        safe_high,
-    ).await {
+        trading_config.safe_range_low,
+        trading_config.safe_range_high,
+    ).await.map_err(|e| e.to_string()) {
         Ok(Some(order_id)) => {
-            stats.write().await.record_order_placed(size);
-            
-            order_tracker.write().await.track_order(
-                token_id.to_string(),
-                order_id.clone(),
-                format!("{:?}", side).to_uppercase(),
-                price,
-                size,
-            );
-            
-            let _ = trade_history.add_trade(
-                token_id.to_string(),
-                order_id.clone(),
-                format!("{:?}", side).to_uppercase(),
-                outcome.to_string(),
-                size,
-                price,
-            );
-            
-            info!("✅ Order placed: {} {:?} {} @ {} for {}",
-                order_id.clone(), side, size, price, outcome);
-        }
-        Ok(None) => {
-            info!("⚠️ Order validation failed for {} {:?} @ {}", outcome, side, price);
-            // Return error to stop trading cycle - partial fills can cause imbalance
-            return Err(format!("Order validation failed for {} {:?}", outcome, side).into());
-        }
-        Err(e) => {
-            error!("❌ {:?} order failed: {}", side, e);
-            stats.write().await.record_error();
-            // Return error to stop trading cycle - partial fills can cause imbalance
-            return Err(format!("Order placement failed: {}", e).into());
+            info!("✅ {} order placed: {}", label, order_id);
+            if let Err(e) = order_tracker.write().await.confirm_submit(&local_id, order_id.clone()) {
+                warn!("⚠️ Failed to track {} order: {}", label, e);
+            }
+            return Some(order_id);
         }
+        Ok(None) => "returned None".to_string(),
+        Err(e) => e,
+    };
+    warn!("❌ {} order failed: {}", label, error);
+    let _ = order_tracker.write().await.reject_submit(&local_id);
+    if quote.side == Side::Buy {
+        budget.refund(market, quote.price * size);
     }
-    Ok(())
+    None
+}
+
+/// Reserve budget for a BUY and return the size it covers (0 if the budget is exhausted)
+fn reserve_size(budget: &PositionBudget, market: &str, price: f64, size: f64) -> f64 {
+    if price <= 0.0 {
+        return 0.0;
+    }
+    let granted = budget.reserve(market, price * size);
+    let covered = (granted / price * 100.0).floor() / 100.0;
+    // Hand back the rounding remainder
+    budget.refund(market, granted - covered * price);
+    if covered < size {
+        warn!("⚠️ Shared position budget covers only {:.2} of {:.2} shares", covered, size);
+    }
+    covered
 }
+
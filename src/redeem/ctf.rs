@@ -0,0 +1,426 @@
+//! Conditional Tokens (CTF) calls executed through the Safe
+//! Builds `splitPosition`/`mergePositions`/`redeemPositions` calldata, signs it as a Safe transaction and submits it via the relayer
+
+use alloy::primitives::{address, Address, Bytes, B256, U256};
+use alloy::signers::local::PrivateKeySigner;
+use alloy::signers::Signer;
+use alloy::sol;
+use alloy::sol_types::{eip712_domain, SolCall, SolStruct};
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tracing::info;
+
+use super::{BuilderRelayer, RelayerError, RelayerTransaction, SafeTransactionRequest};
+use crate::wallet::SafeWallet;
+
+/// Polygon chain ID
+pub const POLYGON_CHAIN_ID: u64 = 137;
+
+/// Conditional Tokens contract on Polygon
+pub const CTF_ADDRESS: Address = address!("0x4D97DCd97eC945f40cF65F87097ACe5EA0476045");
+
+/// USDC.e collateral on Polygon
+pub const USDC_ADDRESS: Address = address!("0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174");
+
+/// Collateral and outcome tokens use 6 decimals
+const TOKEN_DECIMALS: f64 = 1_000_000.0;
+
+sol! {
+    interface IConditionalTokens {
+        function splitPosition(
+            address collateralToken,
+            bytes32 parentCollectionId,
+            bytes32 conditionId,
+            uint256[] partition,
+            uint256 amount
+        ) external;
+
+        function mergePositions(
+            address collateralToken,
+            bytes32 parentCollectionId,
+            bytes32 conditionId,
+            uint256[] partition,
+            uint256 amount
+        ) external;
+
+        function redeemPositions(
+            address collateralToken,
+            bytes32 parentCollectionId,
+            bytes32 conditionId,
+            uint256[] indexSets
+        ) external;
+    }
+
+    /// Gnosis Safe transaction (EIP-712 `SafeTx`)
+    struct SafeTx {
+        address to;
+        uint256 value;
+        bytes data;
+        uint8 operation;
+        uint256 safeTxGas;
+        uint256 baseGas;
+        uint256 gasPrice;
+        address gasToken;
+        address refundReceiver;
+        uint256 nonce;
+    }
+}
+
+/// Shares to on-chain base units (rounded down)
+pub fn to_base_units(shares: f64) -> u64 {
+    (shares * TOKEN_DECIMALS).floor().max(0.0) as u64
+}
+
+/// Base units to shares
+pub fn from_base_units(amount: u64) -> f64 {
+    amount as f64 / TOKEN_DECIMALS
+}
+
+fn parse_condition_id(condition_id: &str) -> Result<B256, RelayerError> {
+    condition_id
+        .parse::<B256>()
+        .map_err(|e| RelayerError::ParseError(format!("invalid condition ID {}: {}", condition_id, e)))
+}
+
+/// Binary market partition: UP (index set 1) and DOWN (index set 2)
+fn binary_partition() -> Vec<U256> {
+    vec![U256::from(1), U256::from(2)]
+}
+
+/// `splitPosition` calldata minting `amount` UP+DOWN pairs from USDC
+pub fn split_position_calldata(condition_id: &str, amount: u64) -> Result<Vec<u8>, RelayerError> {
+    let call = IConditionalTokens::splitPositionCall {
+        collateralToken: USDC_ADDRESS,
+        parentCollectionId: B256::ZERO,
+        conditionId: parse_condition_id(condition_id)?,
+        partition: binary_partition(),
+        amount: U256::from(amount),
+    };
+    Ok(call.abi_encode())
+}
+
+/// `mergePositions` calldata turning `amount` UP+DOWN pairs back into USDC
+pub fn merge_positions_calldata(condition_id: &str, amount: u64) -> Result<Vec<u8>, RelayerError> {
+    let call = IConditionalTokens::mergePositionsCall {
+        collateralToken: USDC_ADDRESS,
+        parentCollectionId: B256::ZERO,
+        conditionId: parse_condition_id(condition_id)?,
+        partition: binary_partition(),
+        amount: U256::from(amount),
+    };
+    Ok(call.abi_encode())
+}
+
+/// `redeemPositions` calldata paying out every UP and DOWN share of a resolved condition
+pub fn redeem_positions_calldata(condition_id: &str) -> Result<Vec<u8>, RelayerError> {
+    let call = IConditionalTokens::redeemPositionsCall {
+        collateralToken: USDC_ADDRESS,
+        parentCollectionId: B256::ZERO,
+        conditionId: parse_condition_id(condition_id)?,
+        indexSets: binary_partition(),
+    };
+    Ok(call.abi_encode())
+}
+
+/// EIP-712 hash of a plain CALL from the Safe, signed by its owner
+pub fn safe_tx_hash(safe: Address, to: Address, data: &[u8], nonce: u64) -> B256 {
+    let tx = SafeTx {
+        to,
+        value: U256::ZERO,
+        data: Bytes::copy_from_slice(data),
+        operation: 0,
+        safeTxGas: U256::ZERO,
+        baseGas: U256::ZERO,
+        gasPrice: U256::ZERO,
+        gasToken: Address::ZERO,
+        refundReceiver: Address::ZERO,
+        nonce: U256::from(nonce),
+    };
+    let domain = eip712_domain! {
+        chain_id: POLYGON_CHAIN_ID,
+        verifying_contract: safe,
+    };
+    tx.eip712_signing_hash(&domain)
+}
+
+/// Outcome of a confirmed CTF call
+#[derive(Debug, Clone)]
+pub struct CtfResult {
+    pub condition_id: String,
+    /// Pairs merged or split, or shares redeemed
+    pub shares: f64,
+    pub transaction_hash: Option<String>,
+}
+
+/// Executes CTF calls from the Safe through the builder relayer
+pub struct CtfExecutor {
+    relayer: BuilderRelayer,
+    /// Held for the whole submit so concurrent markets never reuse a nonce
+    safe: Mutex<SafeWallet>,
+    signer: PrivateKeySigner,
+    poll_interval: Duration,
+    confirm_timeout: Duration,
+}
+
+impl CtfExecutor {
+    /// Create executor; `signer` must own the Safe
+    pub fn new(relayer: BuilderRelayer, safe: SafeWallet, signer: PrivateKeySigner) -> Self {
+        Self {
+            relayer,
+            safe: Mutex::new(safe),
+            signer,
+            poll_interval: Duration::from_secs(2),
+            confirm_timeout: Duration::from_secs(60),
+        }
+    }
+
+    /// Override how confirmation is polled
+    pub fn with_confirmation(mut self, poll_interval: Duration, timeout: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self.confirm_timeout = timeout;
+        self
+    }
+
+    /// Split `usdc` into as many UP+DOWN pairs and wait for confirmation
+    pub async fn split(&self, condition_id: &str, usdc: f64) -> Result<CtfResult, RelayerError> {
+        let amount = to_base_units(usdc);
+        let data = split_position_calldata(condition_id, amount)?;
+        info!("✂️ Splitting ${:.2} into UP+DOWN pairs of {}", from_base_units(amount), condition_id);
+        let transaction = self.execute(CTF_ADDRESS, data).await?;
+        Ok(CtfResult {
+            condition_id: condition_id.to_string(),
+            shares: from_base_units(amount),
+            transaction_hash: transaction.transaction_hash,
+        })
+    }
+
+    /// Merge `shares` UP+DOWN pairs of a condition back into USDC and wait for confirmation
+    pub async fn merge(&self, condition_id: &str, shares: f64) -> Result<CtfResult, RelayerError> {
+        let amount = to_base_units(shares);
+        let data = merge_positions_calldata(condition_id, amount)?;
+        info!("🔀 Merging {:.2} pairs of {}", from_base_units(amount), condition_id);
+        let transaction = self.execute(CTF_ADDRESS, data).await?;
+        Ok(CtfResult {
+            condition_id: condition_id.to_string(),
+            shares: from_base_units(amount),
+            transaction_hash: transaction.transaction_hash,
+        })
+    }
+
+    /// Redeem the held `shares` of a resolved condition for USDC and wait for confirmation
+    pub async fn redeem(&self, condition_id: &str, shares: f64) -> Result<CtfResult, RelayerError> {
+        let data = redeem_positions_calldata(condition_id)?;
+        info!("💵 Redeeming {:.2} shares of {}", shares, condition_id);
+        let transaction = self.execute(CTF_ADDRESS, data).await?;
+        Ok(CtfResult {
+            condition_id: condition_id.to_string(),
+            shares,
+            transaction_hash: transaction.transaction_hash,
+        })
+    }
+
+    /// Current state of a submitted transaction, e.g. one that timed out waiting to be mined
+    pub async fn transaction(&self, transaction_id: &str) -> Result<RelayerTransaction, RelayerError> {
+        self.relayer.get_transaction(transaction_id).await
+    }
+
+    /// Sign a CALL from the Safe, submit it and wait until it is mined
+    async fn execute(&self, to: Address, data: Vec<u8>) -> Result<RelayerTransaction, RelayerError> {
+        let mut safe = self.safe.lock().await;
+        let owner = safe.owner().to_string();
+        // The relayer knows the on-chain nonce; ours may be stale after a restart
+        let nonce = self.relayer.get_safe_nonce(&owner).await?;
+        safe.set_nonce(nonce);
+
+        let safe_address = Address::from_slice(safe.address().as_slice());
+        let hash = safe_tx_hash(safe_address, to, &data, nonce);
+        let signature = self
+            .signer
+            .sign_hash(&hash)
+            .await
+            .map_err(|e| RelayerError::WalletError(e.to_string()))?;
+
+        let request = SafeTransactionRequest {
+            from: owner,
+            to: to.to_string(),
+            proxy_wallet: safe_address.to_string(),
+            data: format!("0x{}", hex::encode(&data)),
+            nonce: nonce.to_string(),
+            signature: format!("0x{}", hex::encode(signature.as_bytes())),
+            kind: "SAFE".to_string(),
+        };
+        let submitted = self.relayer.submit_safe_transaction(&request).await?;
+        safe.increment_nonce();
+        info!("📨 Relayer accepted transaction {} ({})", submitted.transaction_id, submitted.state);
+
+        self.relayer
+            .wait_for_transaction(&submitted.transaction_id, self.poll_interval, self.confirm_timeout)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONDITION_ID: &str = "0x5f65177b394277fd294cd75650044e32ba009a95022d88a0c1d565897d72f8f1";
+
+    #[test]
+    fn test_merge_positions_calldata() {
+        let data = merge_positions_calldata(CONDITION_ID, to_base_units(2.5)).unwrap();
+        // selector + 5 head words + partition length and two entries
+        assert_eq!(&data[..4], &IConditionalTokens::mergePositionsCall::SELECTOR);
+        assert_eq!(data.len(), 4 + 32 * 8);
+
+        let call = IConditionalTokens::mergePositionsCall::abi_decode(&data).unwrap();
+        assert_eq!(call.collateralToken, USDC_ADDRESS);
+        assert_eq!(call.conditionId, CONDITION_ID.parse::<B256>().unwrap());
+        assert_eq!(call.partition, vec![U256::from(1), U256::from(2)]);
+        assert_eq!(call.amount, U256::from(2_500_000u64));
+
+        assert!(merge_positions_calldata("0x1234", 1).is_err());
+    }
+
+    #[test]
+    fn test_split_position_calldata() {
+        let data = split_position_calldata(CONDITION_ID, to_base_units(10.0)).unwrap();
+        assert_eq!(&data[..4], &IConditionalTokens::splitPositionCall::SELECTOR);
+        assert_ne!(
+            IConditionalTokens::splitPositionCall::SELECTOR,
+            IConditionalTokens::mergePositionsCall::SELECTOR
+        );
+
+        let call = IConditionalTokens::splitPositionCall::abi_decode(&data).unwrap();
+        assert_eq!(call.collateralToken, USDC_ADDRESS);
+        assert_eq!(call.parentCollectionId, B256::ZERO);
+        assert_eq!(call.partition, vec![U256::from(1), U256::from(2)]);
+        assert_eq!(call.amount, U256::from(10_000_000u64));
+    }
+
+    #[test]
+    fn test_redeem_positions_calldata() {
+        let data = redeem_positions_calldata(CONDITION_ID).unwrap();
+        assert_eq!(&data[..4], &IConditionalTokens::redeemPositionsCall::SELECTOR);
+
+        let call = IConditionalTokens::redeemPositionsCall::abi_decode(&data).unwrap();
+        assert_eq!(call.collateralToken, USDC_ADDRESS);
+        assert_eq!(call.conditionId, CONDITION_ID.parse::<B256>().unwrap());
+        assert_eq!(call.indexSets, vec![U256::from(1), U256::from(2)]);
+    }
+
+    #[test]
+    fn test_safe_tx_hash_binds_nonce_and_safe() {
+        let safe: Address = "0x45dCeb24119296fB57D06d83c1759cC191c3c96E".parse().unwrap();
+        let data = merge_positions_calldata(CONDITION_ID, 1_000_000).unwrap();
+        let hash = safe_tx_hash(safe, CTF_ADDRESS, &data, 7);
+        assert_eq!(hash, safe_tx_hash(safe, CTF_ADDRESS, &data, 7));
+        assert_ne!(hash, safe_tx_hash(safe, CTF_ADDRESS, &data, 8));
+        assert_ne!(hash, safe_tx_hash(Address::ZERO, CTF_ADDRESS, &data, 7));
+    }
+
+    #[tokio::test]
+    async fn test_merge_submits_signed_safe_transaction() {
+        use wiremock::matchers::{method, path, query_param};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/nonce"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({"nonce": "3"})))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/submit"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "transactionID": "tx-1", "state": "STATE_NEW"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/transaction"))
+            .and(query_param("id", "tx-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([{
+                "transactionID": "tx-1", "transactionHash": "0xabc", "state": "STATE_CONFIRMED"
+            }])))
+            .mount(&server)
+            .await;
+
+        let signer: PrivateKeySigner =
+            "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef".parse().unwrap();
+        let safe = SafeWallet::new(
+            "0x45dCeb24119296fB57D06d83c1759cC191c3c96E",
+            &signer.address().to_string(),
+        ).unwrap();
+        let relayer = BuilderRelayer::new("key".into(), "secret".into(), "pass".into())
+            .with_base_url(&server.uri());
+        let executor = CtfExecutor::new(relayer, safe, signer)
+            .with_confirmation(Duration::from_millis(10), Duration::from_secs(1));
+
+        let result = executor.merge(CONDITION_ID, 2.5).await.unwrap();
+        assert!((result.shares - 2.5).abs() < 1e-9);
+        assert_eq!(result.transaction_hash.as_deref(), Some("0xabc"));
+
+        let requests = server.received_requests().await.unwrap();
+        let submit = requests.iter().find(|r| r.url.path() == "/submit").unwrap();
+        let body: SafeTransactionRequest = serde_json::from_slice(&submit.body).unwrap();
+        assert_eq!(body.nonce, "3");
+        assert_eq!(body.kind, "SAFE");
+        assert_eq!(body.to.parse::<Address>().unwrap(), CTF_ADDRESS);
+        assert_eq!(body.signature.len(), 2 + 65 * 2);
+        assert_eq!(executor.safe.lock().await.nonce(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_merge_timeout_keeps_transaction_id() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/nonce"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({"nonce": "0"})))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/submit"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "transactionID": "tx-2", "state": "STATE_NEW"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/transaction"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([{
+                "transactionID": "tx-2", "state": "STATE_EXECUTED"
+            }])))
+            .mount(&server)
+            .await;
+
+        let signer: PrivateKeySigner =
+            "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef".parse().unwrap();
+        let safe = SafeWallet::new(
+            "0x45dCeb24119296fB57D06d83c1759cC191c3c96E",
+            &signer.address().to_string(),
+        ).unwrap();
+        let relayer = BuilderRelayer::new("key".into(), "secret".into(), "pass".into())
+            .with_base_url(&server.uri());
+        let executor = CtfExecutor::new(relayer, safe, signer)
+            .with_confirmation(Duration::from_millis(10), Duration::from_millis(30));
+
+        // Still in flight when the wait ends: the ID comes back for a later check
+        match executor.merge(CONDITION_ID, 1.0).await {
+            Err(RelayerError::Timeout(id)) => assert_eq!(id, "tx-2"),
+            other => panic!("expected timeout, got {:?}", other),
+        }
+        let tx = executor.transaction("tx-2").await.unwrap();
+        assert!(!tx.is_confirmed() && !tx.is_failed());
+    }
+
+    #[test]
+    fn test_base_units() {
+        assert_eq!(to_base_units(1.2345678), 1_234_567);
+        assert_eq!(to_base_units(-1.0), 0);
+        assert!((from_base_units(2_500_000) - 2.5).abs() < 1e-12);
+    }
+}
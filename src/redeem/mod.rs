@@ -4,7 +4,11 @@ use serde::{Deserialize, Serialize};
 use crate::wallet::SafeWallet;
 use std::sync::Arc;
 
-const BUILDER_RELAYER_URL: &str = "https://relayer.polymarket.com";
+pub mod ctf;
+
+pub use ctf::CtfExecutor;
+
+pub(crate) const BUILDER_RELAYER_URL: &str = "https://relayer.polymarket.com";
 
 /// Builder Relayer client for gasless transactions
 pub struct BuilderRelayer {
@@ -31,6 +35,12 @@ impl BuilderRelayer {
         }
     }
     
+    /// Override the relayer base URL (e.g. a local mock server)
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+    
     /// Get authentication headers
     fn get_headers(&self) -> anyhow::Result<reqwest::header::HeaderMap> {
         let mut headers = reqwest::header::HeaderMap::new();
@@ -136,6 +146,154 @@ impl BuilderRelayer {
         
         Ok(status)
     }
+
+    /// Current Safe nonce as seen by the relayer
+    pub async fn get_safe_nonce(&self, owner: &str) -> Result<u64, RelayerError> {
+        let url = format!("{}/nonce?address={}&type=SAFE", self.base_url, owner);
+        let response: NonceResponse = self.get_json(&url).await?;
+        response.nonce.parse()
+            .map_err(|_| RelayerError::ParseError(format!("invalid nonce {}", response.nonce)))
+    }
+
+    /// Submit a signed Safe transaction
+    pub async fn submit_safe_transaction(
+        &self,
+        request: &SafeTransactionRequest,
+    ) -> Result<RelayerTransaction, RelayerError> {
+        let url = format!("{}/submit", self.base_url);
+        
+        let response = self.client
+            .post(&url)
+            .headers(self.get_headers().map_err(|e| RelayerError::ApiError {
+                status: 400,
+                message: e.to_string(),
+            })?)
+            .json(request)
+            .send()
+            .await
+            .map_err(|e| RelayerError::HttpError(e.to_string()))?;
+        
+        let status_code = response.status();
+        if !status_code.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(RelayerError::ApiError {
+                status: status_code.as_u16(),
+                message: error_text,
+            });
+        }
+        
+        response
+            .json()
+            .await
+            .map_err(|e| RelayerError::ParseError(e.to_string()))
+    }
+
+    /// Look up a submitted transaction
+    pub async fn get_transaction(&self, transaction_id: &str) -> Result<RelayerTransaction, RelayerError> {
+        let url = format!("{}/transaction?id={}", self.base_url, transaction_id);
+        let transactions: Vec<RelayerTransaction> = self.get_json(&url).await?;
+        transactions.into_iter().next().ok_or_else(|| RelayerError::ApiError {
+            status: 404,
+            message: format!("transaction {} not found", transaction_id),
+        })
+    }
+
+    /// Poll a transaction until it is confirmed, fails or `timeout` elapses
+    pub async fn wait_for_transaction(
+        &self,
+        transaction_id: &str,
+        poll_interval: std::time::Duration,
+        timeout: std::time::Duration,
+    ) -> Result<RelayerTransaction, RelayerError> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let transaction = self.get_transaction(transaction_id).await?;
+            if transaction.is_confirmed() {
+                return Ok(transaction);
+            }
+            if transaction.is_failed() {
+                return Err(RelayerError::TransactionFailed {
+                    id: transaction_id.to_string(),
+                    state: transaction.state,
+                });
+            }
+            if tokio::time::Instant::now() + poll_interval > deadline {
+                return Err(RelayerError::Timeout(transaction_id.to_string()));
+            }
+            tokio::time::sleep(poll_interval).await;
+        }
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T, RelayerError> {
+        let response = self.client
+            .get(url)
+            .headers(self.get_headers().map_err(|e| RelayerError::ApiError {
+                status: 400,
+                message: e.to_string(),
+            })?)
+            .send()
+            .await
+            .map_err(|e| RelayerError::HttpError(e.to_string()))?;
+        
+        let status_code = response.status();
+        if !status_code.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(RelayerError::ApiError {
+                status: status_code.as_u16(),
+                message: error_text,
+            });
+        }
+        
+        response
+            .json()
+            .await
+            .map_err(|e| RelayerError::ParseError(e.to_string()))
+    }
+}
+
+/// Safe nonce response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NonceResponse {
+    pub nonce: String,
+}
+
+/// Signed Safe transaction for the relayer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SafeTransactionRequest {
+    /// Safe owner that signed
+    pub from: String,
+    /// Target contract
+    pub to: String,
+    #[serde(rename = "proxyWallet")]
+    pub proxy_wallet: String,
+    /// Hex calldata
+    pub data: String,
+    pub nonce: String,
+    pub signature: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+}
+
+/// Relayer transaction state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelayerTransaction {
+    #[serde(rename = "transactionID")]
+    pub transaction_id: String,
+    #[serde(rename = "transactionHash", default)]
+    pub transaction_hash: Option<String>,
+    pub state: String,
+}
+
+impl RelayerTransaction {
+    /// Mined or confirmed on chain
+    pub fn is_confirmed(&self) -> bool {
+        matches!(self.state.as_str(), "STATE_MINED" | "STATE_CONFIRMED")
+    }
+
+    /// Rejected or reverted
+    pub fn is_failed(&self) -> bool {
+        matches!(self.state.as_str(), "STATE_FAILED" | "STATE_INVALID")
+    }
 }
 
 /// Redemption request
@@ -274,6 +432,10 @@ pub enum RelayerError {
     ParseError(String),
     #[error("Wallet error: {0}")]
     WalletError(String),
+    #[error("Transaction {id} failed: {state}")]
+    TransactionFailed { id: String, state: String },
+    #[error("Transaction {0} not confirmed in time")]
+    Timeout(String),
 }
 
 #[cfg(test)]
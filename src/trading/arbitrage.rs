@@ -0,0 +1,552 @@
+//! Complementary-pair arbitrage
+//! One UP plus one DOWN share always pays out $1, so asks summing below $1 (or bids summing
+//! above it while we hold both) lock in an edge. Detection walks the WebSocket book cache;
+//! execution sends the first leg FOK and the second FAK, then repairs a one-legged fill.
+
+use async_trait::async_trait;
+use polymarket_client_sdk::clob::types::OrderType;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use crate::api::Side;
+use crate::config::ArbitrageConfig;
+use crate::trading::executor::{ImmediateFill, TradeExecutor};
+use crate::trading::order_tracker::{book_fill, sync_order_stats, Fill, OrderTracker};
+use crate::trading::orderbook::{L2OrderBook, OrderBookLevel};
+use crate::trading::position::PositionTracker;
+use crate::trading::stats::TradingStats;
+use crate::trading::strategy::Outcome;
+
+/// Price tick
+const TICK: f64 = 0.01;
+
+/// Book levels walked per side
+const MAX_LEVELS: usize = 20;
+
+/// Which way the pair is traded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArbKind {
+    /// Asks sum below $1: buy both outcomes
+    BuyPair,
+    /// Bids sum above $1: sell pairs we hold
+    SellPair,
+}
+
+impl ArbKind {
+    /// Order side of both legs
+    pub fn side(&self) -> Side {
+        match self {
+            ArbKind::BuyPair => Side::Buy,
+            ArbKind::SellPair => Side::Sell,
+        }
+    }
+}
+
+/// A locked-in edge, sized by displayed depth
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArbOpportunity {
+    pub kind: ArbKind,
+    pub up_token: String,
+    pub down_token: String,
+    /// Worst level used on each leg; sent as the limit
+    pub up_price: f64,
+    pub down_price: f64,
+    /// Pairs
+    pub size: f64,
+    /// Profit after fees if both legs fill at the walked levels
+    pub expected_profit: f64,
+    /// Leg sent first (FOK): the one with less displayed size at its limit
+    pub first: Outcome,
+}
+
+impl ArbOpportunity {
+    /// Token and limit of one leg
+    pub fn leg(&self, outcome: Outcome) -> (&str, f64) {
+        match outcome {
+            Outcome::Up => (&self.up_token, self.up_price),
+            Outcome::Down => (&self.down_token, self.down_price),
+        }
+    }
+}
+
+/// What an attempt ended up doing
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ArbOutcome {
+    /// Complete pairs traded
+    pub paired: f64,
+    /// First-leg shares traded back after the second leg came up short
+    pub unwound: f64,
+    /// First-leg shares left unhedged (kept as inventory)
+    pub residual: f64,
+    /// Realised edge after fees, including any unwind loss
+    pub profit: f64,
+    /// Executions booked across all legs
+    pub fills: Vec<Fill>,
+}
+
+impl ArbOutcome {
+    /// Whether the second leg failed to cover the first
+    pub fn one_legged(&self) -> bool {
+        self.unwound > 0.0 || self.residual > 0.0
+    }
+}
+
+/// Shares matched while walking two books against each other
+#[derive(Debug, Default)]
+struct Walk {
+    size: f64,
+    /// Sum of size times (up price + down price)
+    value: f64,
+    up_limit: f64,
+    down_limit: f64,
+    up_levels: usize,
+    down_levels: usize,
+}
+
+/// Walk both books level by level while the pair stays profitable, up to `cap` pairs
+fn walk(
+    up: &[OrderBookLevel],
+    down: &[OrderBookLevel],
+    cap: f64,
+    profitable: impl Fn(f64, f64) -> bool,
+) -> Walk {
+    let mut w = Walk::default();
+    let (mut i, mut j) = (0, 0);
+    let (mut up_left, mut down_left) = match (up.first(), down.first()) {
+        (Some(a), Some(b)) => (a.size, b.size),
+        _ => return w,
+    };
+    while i < up.len() && j < down.len() && w.size < cap {
+        let (pu, pd) = (up[i].price, down[j].price);
+        if !profitable(pu, pd) {
+            break;
+        }
+        let q = up_left.min(down_left).min(cap - w.size);
+        w.size += q;
+        w.value += q * (pu + pd);
+        w.up_limit = pu;
+        w.down_limit = pd;
+        w.up_levels = i + 1;
+        w.down_levels = j + 1;
+        up_left -= q;
+        down_left -= q;
+        if up_left <= 1e-9 {
+            i += 1;
+            up_left = up.get(i).map_or(0.0, |l| l.size);
+        }
+        if down_left <= 1e-9 {
+            j += 1;
+            down_left = down.get(j).map_or(0.0, |l| l.size);
+        }
+    }
+    w
+}
+
+/// Look for a buy-pair or sell-pair edge; `max_size` caps the pairs (e.g. by remaining budget)
+pub fn detect_arbitrage(
+    up: &L2OrderBook,
+    down: &L2OrderBook,
+    held_up: f64,
+    held_down: f64,
+    max_size: f64,
+    config: &ArbitrageConfig,
+) -> Option<ArbOpportunity> {
+    let fee = config.fee_rate;
+
+    let (up_asks, down_asks) = (up.asks(MAX_LEVELS), down.asks(MAX_LEVELS));
+    let cap = config.max_size.min(max_size);
+    let buy = walk(&up_asks, &down_asks, cap, |pu, pd| {
+        (pu + pd) * (1.0 + fee) <= 1.0 - config.min_edge
+    });
+    if let Some(opp) = opportunity(ArbKind::BuyPair, up, down, &up_asks, &down_asks, &buy, config) {
+        return Some(opp);
+    }
+
+    let (up_bids, down_bids) = (up.bids(MAX_LEVELS), down.bids(MAX_LEVELS));
+    let cap = config.max_size.min(held_up).min(held_down);
+    let sell = walk(&up_bids, &down_bids, cap, |pu, pd| {
+        (pu + pd) * (1.0 - fee) >= 1.0 + config.min_edge
+    });
+    opportunity(ArbKind::SellPair, up, down, &up_bids, &down_bids, &sell, config)
+}
+
+/// Turn a walk into an opportunity if it is big enough
+fn opportunity(
+    kind: ArbKind,
+    up: &L2OrderBook,
+    down: &L2OrderBook,
+    up_levels: &[OrderBookLevel],
+    down_levels: &[OrderBookLevel],
+    w: &Walk,
+    config: &ArbitrageConfig,
+) -> Option<ArbOpportunity> {
+    // Orders are sized on the 0.01 share step
+    let size = (w.size * 100.0).floor() / 100.0;
+    if size < config.min_size || w.size <= 0.0 {
+        return None;
+    }
+    let value = w.value * size / w.size;
+    let expected_profit = match kind {
+        ArbKind::BuyPair => size - value * (1.0 + config.fee_rate),
+        ArbKind::SellPair => value * (1.0 - config.fee_rate) - size,
+    };
+    let depth = |levels: &[OrderBookLevel], n: usize| levels[..n].iter().map(|l| l.size).sum::<f64>();
+    let first = if depth(up_levels, w.up_levels) <= depth(down_levels, w.down_levels) {
+        Outcome::Up
+    } else {
+        Outcome::Down
+    };
+    Some(ArbOpportunity {
+        kind,
+        up_token: up.asset_id.clone(),
+        down_token: down.asset_id.clone(),
+        up_price: w.up_limit,
+        down_price: w.down_limit,
+        size,
+        expected_profit,
+        first,
+    })
+}
+
+/// Worst second-leg price at which the pair still breaks even after fees
+pub fn breakeven_price(kind: ArbKind, first_price: f64, fee_rate: f64) -> f64 {
+    match kind {
+        ArbKind::BuyPair => ((1.0 / (1.0 + fee_rate) - first_price) / TICK + 1e-9).floor() * TICK,
+        ArbKind::SellPair => ((1.0 / (1.0 - fee_rate) - first_price) / TICK - 1e-9).ceil() * TICK,
+    }
+}
+
+/// Sends the immediate (FOK/FAK) orders of each leg
+#[async_trait]
+pub trait LegSender: Send + Sync {
+    async fn send_immediate(
+        &self,
+        token_id: &str,
+        side: Side,
+        price: f64,
+        size: f64,
+        order_type: OrderType,
+    ) -> Result<ImmediateFill, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+#[async_trait]
+impl LegSender for TradeExecutor {
+    async fn send_immediate(
+        &self,
+        token_id: &str,
+        side: Side,
+        price: f64,
+        size: f64,
+        order_type: OrderType,
+    ) -> Result<ImmediateFill, Box<dyn std::error::Error + Send + Sync>> {
+        self.place_immediate_order(token_id, side, price, size, order_type).await
+    }
+}
+
+/// Leg sender and the order, position and stats state an arbitrage books into
+pub struct ArbContext<'a> {
+    pub executor: &'a dyn LegSender,
+    pub order_tracker: &'a RwLock<OrderTracker>,
+    pub position_tracker: &'a RwLock<PositionTracker>,
+    pub stats: &'a RwLock<TradingStats>,
+}
+
+/// Send one immediate leg through the order tracker and book whatever matched
+async fn send_leg(
+    arb: &ArbContext<'_>,
+    fills: &mut Vec<Fill>,
+    token_id: &str,
+    side: Side,
+    price: f64,
+    size: f64,
+    order_type: OrderType,
+) -> ImmediateFill {
+    let local_id = arb.order_tracker.write().await.begin_submit(
+        token_id.to_string(), format!("{:?}", side).to_uppercase(), price, size);
+    let result = match arb.executor.send_immediate(token_id, side, price, size, order_type.clone()).await {
+        Ok(fill) if !fill.order_id.is_empty() => Ok(fill),
+        Ok(_) => Err("no order ID returned".to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    let fill = match result {
+        Ok(fill) => fill,
+        Err(e) => {
+            // FOK/FAK orders that find nothing to match come back as errors
+            warn!("⚠️ {} {:?} {:.2}@{:.2} on {} not filled: {}", order_type, side, size, price, token_id, e);
+            let _ = arb.order_tracker.write().await.reject_submit(&local_id);
+            sync_order_stats(arb.order_tracker, arb.stats).await;
+            return ImmediateFill { order_id: String::new(), filled_size: 0.0, avg_price: price };
+        }
+    };
+
+    let booked = {
+        let mut tracker = arb.order_tracker.write().await;
+        match tracker.confirm_submit(&local_id, fill.order_id.clone()) {
+            Ok(()) => tracker.apply_immediate_fill(&fill.order_id, fill.filled_size, fill.avg_price),
+            Err(e) => {
+                warn!("⚠️ Failed to track {} order {}: {}", order_type, fill.order_id, e);
+                None
+            }
+        }
+    };
+    if let Some(booked) = booked {
+        info!("⚡ {} {:?} {:.2}@{:.4} on {} ({})", order_type, side, booked.size, booked.price,
+            token_id, booked.order_id);
+        book_fill(arb.position_tracker, arb.stats, &booked).await;
+        fills.push(booked);
+    }
+    sync_order_stats(arb.order_tracker, arb.stats).await;
+    fill
+}
+
+/// Execute both legs; a second leg that comes up short is retried at breakeven, then the
+/// first leg's excess is unwound within `unwind_slippage`. Anything left stays as inventory.
+pub async fn execute_arbitrage(
+    arb: &ArbContext<'_>,
+    opp: &ArbOpportunity,
+    config: &ArbitrageConfig,
+) -> ArbOutcome {
+    let side = opp.kind.side();
+    let second = match opp.first {
+        Outcome::Up => Outcome::Down,
+        Outcome::Down => Outcome::Up,
+    };
+    let (first_token, first_price) = opp.leg(opp.first);
+    let (second_token, second_price) = opp.leg(second);
+    info!("🎯 Arbitrage {:?}: {:.2} pairs at UP {:.2} + DOWN {:.2} (expected +${:.3})",
+        opp.kind, opp.size, opp.up_price, opp.down_price, opp.expected_profit);
+
+    let mut fills = Vec::new();
+
+    // Leg 1 all-or-nothing so a miss costs nothing
+    let leg1 = send_leg(arb, &mut fills, first_token, side, first_price, opp.size, OrderType::FOK).await;
+    if leg1.filled_size <= 0.0 {
+        info!("Arbitrage missed: first leg {} not filled", opp.first.label());
+        return ArbOutcome::default();
+    }
+
+    // Leg 2 takes what it can at the walked limit
+    let leg2 = send_leg(arb, &mut fills, second_token, side, second_price,
+        leg1.filled_size, OrderType::FAK).await;
+    let mut second_filled = leg2.filled_size;
+    let mut second_notional = leg2.filled_size * leg2.avg_price;
+
+    let mut missing = ((leg1.filled_size - second_filled) * 100.0).floor() / 100.0;
+    if missing > 0.0 {
+        // One-legged: chase the second leg as far as the pair still breaks even
+        let chase = breakeven_price(opp.kind, leg1.avg_price, config.fee_rate);
+        let worse = match opp.kind {
+            ArbKind::BuyPair => chase > second_price,
+            ArbKind::SellPair => chase < second_price,
+        };
+        if worse && (TICK..=1.0 - TICK).contains(&chase) {
+            warn!("⚠️ Second leg short by {:.2}, retrying at breakeven {:.2}", missing, chase);
+            let retry = send_leg(arb, &mut fills, second_token, side, chase, missing, OrderType::FAK).await;
+            second_filled += retry.filled_size;
+            second_notional += retry.filled_size * retry.avg_price;
+            missing = ((leg1.filled_size - second_filled) * 100.0).floor() / 100.0;
+        }
+    }
+
+    let mut outcome = ArbOutcome {
+        paired: second_filled.min(leg1.filled_size),
+        ..ArbOutcome::default()
+    };
+    let second_avg = if second_filled > 0.0 { second_notional / second_filled } else { 0.0 };
+    outcome.profit = match opp.kind {
+        ArbKind::BuyPair => outcome.paired * (1.0 - (leg1.avg_price + second_avg) * (1.0 + config.fee_rate)),
+        ArbKind::SellPair => outcome.paired * ((leg1.avg_price + second_avg) * (1.0 - config.fee_rate) - 1.0),
+    };
+
+    if missing > 0.0 {
+        // Still unhedged: trade the first leg's excess back
+        let (unwind_side, unwind_price) = match opp.kind {
+            ArbKind::BuyPair => (Side::Sell, (leg1.avg_price - config.unwind_slippage).max(TICK)),
+            ArbKind::SellPair => (Side::Buy, (leg1.avg_price + config.unwind_slippage).min(1.0 - TICK)),
+        };
+        let unwind_price = (unwind_price / TICK).round() * TICK;
+        warn!("⚠️ One-legged fill: unwinding {:.2} {} at {:.2}", missing, opp.first.label(), unwind_price);
+        let unwind = send_leg(arb, &mut fills, first_token, unwind_side, unwind_price,
+            missing, OrderType::FAK).await;
+        outcome.unwound = unwind.filled_size;
+        outcome.residual = ((missing - unwind.filled_size) * 100.0).round() / 100.0;
+        let per_share = match opp.kind {
+            ArbKind::BuyPair => unwind.avg_price * (1.0 - config.fee_rate) - leg1.avg_price,
+            ArbKind::SellPair => leg1.avg_price - unwind.avg_price * (1.0 + config.fee_rate),
+        };
+        outcome.profit += unwind.filled_size * per_share;
+        if outcome.residual > 0.0 {
+            warn!("⚠️ {:.2} {} left unhedged, holding as inventory", outcome.residual, opp.first.label());
+        }
+    }
+
+    info!("🎯 Arbitrage done: paired {:.2}, unwound {:.2}, residual {:.2}, PnL ${:.3}",
+        outcome.paired, outcome.unwound, outcome.residual, outcome.profit);
+    arb.stats.write().await.record_arbitrage(outcome.profit, outcome.one_legged());
+    outcome.fills = fills;
+    outcome
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trading::order_tracker::OrderState;
+
+    fn book(asset: &str, bids: &[(f64, f64)], asks: &[(f64, f64)]) -> L2OrderBook {
+        let levels = |v: &[(f64, f64)]| {
+            v.iter().map(|&(price, size)| OrderBookLevel { price, size }).collect::<Vec<_>>()
+        };
+        let mut book = L2OrderBook::new(asset);
+        book.apply_snapshot(&levels(bids), &levels(asks));
+        book
+    }
+
+    fn config() -> ArbitrageConfig {
+        ArbitrageConfig {
+            enabled: true,
+            min_edge: 0.01,
+            min_size: 5.0,
+            max_size: 100.0,
+            ..ArbitrageConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_buy_pair_sized_by_depth() {
+        // 0.45 + 0.50 = 0.95 for 10, then 0.47 + 0.50 = 0.97 for 5 more, 0.47 + 0.53 = 1.00 stops
+        let up = book("up", &[(0.44, 50.0)], &[(0.45, 10.0), (0.47, 20.0)]);
+        let down = book("down", &[(0.49, 50.0)], &[(0.50, 15.0), (0.53, 40.0)]);
+
+        let opp = detect_arbitrage(&up, &down, 0.0, 0.0, f64::MAX, &config()).unwrap();
+        assert_eq!(opp.kind, ArbKind::BuyPair);
+        assert!((opp.size - 15.0).abs() < 1e-9);
+        assert!((opp.up_price - 0.47).abs() < 1e-9);
+        assert!((opp.down_price - 0.50).abs() < 1e-9);
+        // 10 * 0.05 + 5 * 0.03
+        assert!((opp.expected_profit - 0.65).abs() < 1e-9);
+        // UP shows 30 through its limit, DOWN only 15
+        assert_eq!(opp.first, Outcome::Down);
+
+        // Budget caps the size; too small to send
+        assert!(detect_arbitrage(&up, &down, 0.0, 0.0, 4.0, &config()).is_none());
+
+        // Fees eat the second level
+        let fees = ArbitrageConfig { fee_rate: 0.03, ..config() };
+        let opp = detect_arbitrage(&up, &down, 0.0, 0.0, f64::MAX, &fees).unwrap();
+        assert!((opp.size - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_sell_pair_needs_inventory() {
+        let up = book("up", &[(0.55, 20.0)], &[(0.60, 20.0)]);
+        let down = book("down", &[(0.50, 20.0)], &[(0.55, 20.0)]);
+
+        assert!(detect_arbitrage(&up, &down, 0.0, 8.0, f64::MAX, &config()).is_none());
+        let opp = detect_arbitrage(&up, &down, 12.0, 8.0, f64::MAX, &config()).unwrap();
+        assert_eq!(opp.kind, ArbKind::SellPair);
+        assert!((opp.size - 8.0).abs() < 1e-9);
+        assert!((opp.expected_profit - 0.40).abs() < 1e-9);
+
+        // No edge either way
+        let flat = book("down", &[(0.40, 20.0)], &[(0.45, 20.0)]);
+        assert!(detect_arbitrage(&up, &flat, 12.0, 8.0, f64::MAX, &config()).is_none());
+    }
+
+    /// Answers each leg from a script, in order
+    struct ScriptedLegs {
+        fills: std::sync::Mutex<std::collections::VecDeque<Option<(f64, f64)>>>,
+        sent: std::sync::Mutex<Vec<(String, Side, f64, f64)>>,
+    }
+
+    #[async_trait]
+    impl LegSender for ScriptedLegs {
+        async fn send_immediate(
+            &self,
+            token_id: &str,
+            side: Side,
+            price: f64,
+            size: f64,
+            _order_type: OrderType,
+        ) -> Result<ImmediateFill, Box<dyn std::error::Error + Send + Sync>> {
+            let mut sent = self.sent.lock().unwrap();
+            sent.push((token_id.to_string(), side, price, size));
+            match self.fills.lock().unwrap().pop_front().flatten() {
+                Some((filled_size, avg_price)) => Ok(ImmediateFill {
+                    order_id: format!("leg_{}", sent.len()),
+                    filled_size,
+                    avg_price,
+                }),
+                None => Err("no match".into()),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_short_second_leg_is_unwound() {
+        let legs = ScriptedLegs {
+            // DOWN fills, UP only 4 of 10, the breakeven retry misses, the unwind sells the rest
+            fills: std::sync::Mutex::new(vec![
+                Some((10.0, 0.50)), Some((4.0, 0.45)), None, Some((6.0, 0.48)),
+            ].into()),
+            sent: std::sync::Mutex::new(Vec::new()),
+        };
+        let order_tracker = RwLock::new(OrderTracker::new());
+        let position_tracker = RwLock::new(PositionTracker::new());
+        let stats = RwLock::new(TradingStats::new());
+        let arb = ArbContext {
+            executor: &legs,
+            order_tracker: &order_tracker,
+            position_tracker: &position_tracker,
+            stats: &stats,
+        };
+        let opp = ArbOpportunity {
+            kind: ArbKind::BuyPair,
+            up_token: "up".to_string(),
+            down_token: "down".to_string(),
+            up_price: 0.45,
+            down_price: 0.50,
+            size: 10.0,
+            expected_profit: 0.5,
+            first: Outcome::Down,
+        };
+
+        let outcome = execute_arbitrage(&arb, &opp, &config()).await;
+
+        let sent = legs.sent.lock().unwrap().clone();
+        let expected = [
+            ("down", Side::Buy, 0.50, 10.0),
+            ("up", Side::Buy, 0.45, 10.0),
+            ("up", Side::Buy, 0.50, 6.0),
+            ("down", Side::Sell, 0.47, 6.0),
+        ];
+        assert_eq!(sent.len(), expected.len());
+        for ((token, side, price, size), (e_token, e_side, e_price, e_size)) in sent.iter().zip(expected) {
+            assert_eq!((token.as_str(), *side, *size), (e_token, e_side, e_size));
+            assert!((price - e_price).abs() < 1e-9);
+        }
+        assert_eq!((outcome.paired, outcome.unwound, outcome.residual), (4.0, 6.0, 0.0));
+        // 4 pairs at 0.95, 6 DOWN bought at 0.50 and sold at 0.48
+        assert!((outcome.profit - 0.08).abs() < 1e-9);
+        assert_eq!(outcome.fills.len(), 3);
+
+        let stats = stats.read().await;
+        assert_eq!((stats.arbitrages, stats.arb_one_legged, stats.fills), (1, 1, 3));
+        assert!((stats.arb_pnl - 0.08).abs() < 1e-9);
+        let positions = position_tracker.read().await;
+        assert!((positions.held_shares("up") - 4.0).abs() < 1e-9);
+        assert!((positions.held_shares("down") - 4.0).abs() < 1e-9);
+        // Every leg went through the order tracker, the missed retry as a rejection
+        let history = order_tracker.read().await;
+        assert_eq!(history.history().len(), 4);
+        assert_eq!(history.history()[2].state, OrderState::Rejected);
+    }
+
+    #[test]
+    fn test_breakeven_price() {
+        assert!((breakeven_price(ArbKind::BuyPair, 0.45, 0.0) - 0.55).abs() < 1e-9);
+        // 1 / 1.02 - 0.45 = 0.5304 -> 0.53
+        assert!((breakeven_price(ArbKind::BuyPair, 0.45, 0.02) - 0.53).abs() < 1e-9);
+        // 1 / 0.98 - 0.55 = 0.4704 -> 0.48
+        assert!((breakeven_price(ArbKind::SellPair, 0.55, 0.02) - 0.48).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,108 @@
+//! Avellaneda-Stoikov inventory-risk quoting
+//! Reservation price leans away from held inventory; the optimal spread trades fill odds against risk
+
+use serde::{Deserialize, Serialize};
+
+/// How the market maker turns inventory into prices
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuoteModel {
+    /// Center shifted by inventory_skew * 0.01, bids gated by a fixed skew threshold
+    #[default]
+    Linear,
+    /// Reservation price and optimal spread from inventory, volatility and time left
+    AvellanedaStoikov,
+}
+
+/// Avellaneda-Stoikov parameters
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AvellanedaStoikovConfig {
+    /// Risk aversion gamma, per order_size of inventory (default: 1.0)
+    pub risk_aversion: f64,
+    /// Order arrival decay k: fill intensity falls by e every 1/k of price away from the mid (default: 100)
+    pub order_intensity: f64,
+    /// Mid volatility in price per square-root second until enough history is collected (default: 0.003)
+    pub fallback_volatility: f64,
+}
+
+impl Default for AvellanedaStoikovConfig {
+    fn default() -> Self {
+        Self {
+            risk_aversion: 1.0,
+            order_intensity: 100.0,
+            fallback_volatility: 0.003,
+        }
+    }
+}
+
+impl AvellanedaStoikovConfig {
+    /// Validate parameters
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.risk_aversion <= 0.0 || self.order_intensity <= 0.0 || self.fallback_volatility < 0.0 {
+            anyhow::bail!("Avellaneda-Stoikov risk_aversion and order_intensity must be positive, fallback_volatility non-negative");
+        }
+        Ok(())
+    }
+
+    /// Quote center and width for `inventory` (in order_size units) with `seconds_left` to expiry
+    pub fn quote(&self, mid: f64, inventory: f64, volatility: f64, seconds_left: f64) -> StoikovQuote {
+        let tau = seconds_left.max(0.0);
+        StoikovQuote {
+            reservation: reservation_price(mid, inventory, self.risk_aversion, volatility, tau),
+            spread: optimal_spread(self.risk_aversion, volatility, tau, self.order_intensity),
+        }
+    }
+}
+
+/// Reservation price and total spread
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StoikovQuote {
+    pub reservation: f64,
+    pub spread: f64,
+}
+
+impl StoikovQuote {
+    pub fn bid(&self) -> f64 {
+        self.reservation - self.spread / 2.0
+    }
+
+    pub fn ask(&self) -> f64 {
+        self.reservation + self.spread / 2.0
+    }
+}
+
+/// r = s - q * gamma * sigma^2 * (T - t)
+pub fn reservation_price(mid: f64, inventory: f64, gamma: f64, sigma: f64, tau: f64) -> f64 {
+    mid - inventory * gamma * sigma * sigma * tau
+}
+
+/// delta = gamma * sigma^2 * (T - t) + (2 / gamma) * ln(1 + gamma / k)
+pub fn optimal_spread(gamma: f64, sigma: f64, tau: f64, k: f64) -> f64 {
+    gamma * sigma * sigma * tau + (2.0 / gamma) * (1.0 + gamma / k).ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reservation_price_and_spread() {
+        let config = AvellanedaStoikovConfig::default();
+        // sigma^2 * tau = 0.000009 * 300 = 0.0027
+        let flat = config.quote(0.50, 0.0, 0.003, 300.0);
+        assert!((flat.reservation - 0.50).abs() < 1e-12);
+        let expected = 0.0027 + 2.0 * 1.01f64.ln();
+        assert!((flat.spread - expected).abs() < 1e-12);
+        assert!((flat.ask() - flat.bid() - expected).abs() < 1e-12);
+
+        // Long inventory leans both quotes down, short leans them up
+        let long = config.quote(0.50, 2.0, 0.003, 300.0);
+        assert!((long.reservation - (0.50 - 2.0 * 0.0027)).abs() < 1e-12);
+        assert!(config.quote(0.50, -2.0, 0.003, 300.0).reservation > 0.50);
+
+        // The inventory penalty fades as expiry approaches
+        let late = config.quote(0.50, 2.0, 0.003, 30.0);
+        assert!(late.reservation > long.reservation && late.spread < long.spread);
+    }
+}
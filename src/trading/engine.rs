@@ -0,0 +1,405 @@
+//! Multi-market engine
+//! Runs one task per market with its own order/position slice and a shared capital budget
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
+
+use crate::api::discovery::SeriesMarket;
+use crate::trading::exit_manager::ExitOrders;
+use crate::trading::order_tracker::OrderTracker;
+use crate::trading::position::PositionTracker;
+
+/// How long `stop` waits for a market task to wind down before aborting it
+const MARKET_STOP_TIMEOUT_SECS: u64 = 10;
+
+/// Capital budget shared by all markets (`max_total_position`)
+#[derive(Debug)]
+pub struct PositionBudget {
+    limit: f64,
+    /// Market key -> exposure (position value plus reserved order notional)
+    exposure: Mutex<HashMap<String, f64>>,
+}
+
+impl PositionBudget {
+    /// Create budget with a total limit
+    pub fn new(limit: f64) -> Self {
+        Self {
+            limit,
+            exposure: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Total limit
+    pub fn limit(&self) -> f64 {
+        self.limit
+    }
+
+    /// Reset a market's exposure to its current position value
+    pub fn set_exposure(&self, market: &str, value: f64) {
+        self.exposure.lock().unwrap().insert(market.to_string(), value.max(0.0));
+    }
+
+    /// Reserve up to `amount` for a market; returns the granted amount
+    pub fn reserve(&self, market: &str, amount: f64) -> f64 {
+        let mut exposure = self.exposure.lock().unwrap();
+        let used: f64 = exposure.values().sum();
+        let granted = amount.min(self.limit - used).max(0.0);
+        if granted > 0.0 {
+            *exposure.entry(market.to_string()).or_insert(0.0) += granted;
+        }
+        granted
+    }
+
+    /// Give back part of a reservation (e.g. an order that was rejected)
+    pub fn refund(&self, market: &str, amount: f64) {
+        if let Some(value) = self.exposure.lock().unwrap().get_mut(market) {
+            *value = (*value - amount).max(0.0);
+        }
+    }
+
+    /// Drop a market's share entirely, once its capital is back (merged or redeemed)
+    pub fn release(&self, market: &str) {
+        self.exposure.lock().unwrap().remove(market);
+    }
+
+    /// Exposure of one market
+    pub fn exposure(&self, market: &str) -> f64 {
+        self.exposure.lock().unwrap().get(market).copied().unwrap_or(0.0)
+    }
+
+    /// Exposure across all markets
+    pub fn total(&self) -> f64 {
+        self.exposure.lock().unwrap().values().sum()
+    }
+
+    /// Budget still available
+    pub fn remaining(&self) -> f64 {
+        (self.limit - self.total()).max(0.0)
+    }
+}
+
+/// Per-market state handed to a market task
+#[derive(Clone)]
+pub struct MarketContext {
+    pub market: SeriesMarket,
+    pub order_tracker: Arc<RwLock<OrderTracker>>,
+    pub position_tracker: Arc<RwLock<PositionTracker>>,
+    pub budget: Arc<PositionBudget>,
+    /// Exit SELLs placed by the exit manager
+    pub exit_orders: Arc<RwLock<ExitOrders>>,
+    /// Cancelled when the engine stops this market
+    pub cancel: CancellationToken,
+    /// Cancelled once a stopped or finished market is settled; until then its
+    /// events are still routed here
+    pub settled: CancellationToken,
+}
+
+impl MarketContext {
+    /// Whether an asset belongs to this market
+    pub fn owns_asset(&self, asset_id: &str) -> bool {
+        self.market.up_token == asset_id || self.market.down_token == asset_id
+    }
+}
+
+/// Running market task
+struct MarketTask {
+    context: MarketContext,
+    handle: JoinHandle<()>,
+}
+
+/// Engine that owns one task per market
+pub struct MarketEngine {
+    budget: Arc<PositionBudget>,
+    /// condition_id -> task
+    tasks: RwLock<HashMap<String, MarketTask>>,
+    /// condition_id -> context of a stopped or finished market awaiting settlement
+    settling: RwLock<HashMap<String, MarketContext>>,
+}
+
+impl MarketEngine {
+    /// Create engine with a global `max_total_position` budget
+    pub fn new(max_total_position: f64) -> Self {
+        Self {
+            budget: Arc::new(PositionBudget::new(max_total_position)),
+            tasks: RwLock::new(HashMap::new()),
+            settling: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Shared budget
+    pub fn budget(&self) -> Arc<PositionBudget> {
+        self.budget.clone()
+    }
+
+    /// Start a task for a market; false if it is already running
+    pub async fn start<F, Fut>(&self, market: SeriesMarket, run: F) -> bool
+    where
+        F: FnOnce(MarketContext) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let mut tasks = self.tasks.write().await;
+        if tasks.get(&market.condition_id).is_some_and(|t| !t.handle.is_finished()) {
+            return false;
+        }
+
+        let context = MarketContext {
+            market: market.clone(),
+            order_tracker: Arc::new(RwLock::new(OrderTracker::new())),
+            position_tracker: Arc::new(RwLock::new(PositionTracker::new())),
+            budget: self.budget.clone(),
+            exit_orders: Arc::new(RwLock::new(ExitOrders::default())),
+            cancel: CancellationToken::new(),
+            settled: CancellationToken::new(),
+        };
+        // A panic or error inside the task ends only this market
+        let handle = tokio::spawn(run(context.clone()));
+        info!("▶️ Started market task {} ({} running)", market.slug, tasks.len() + 1);
+        tasks.insert(market.condition_id.clone(), MarketTask { context, handle });
+        true
+    }
+
+    /// Stop a market task and wait for it to wind down
+    pub async fn stop(&self, condition_id: &str) -> bool {
+        let Some(task) = self.tasks.write().await.remove(condition_id) else {
+            return false;
+        };
+        task.context.cancel.cancel();
+        let abort = task.handle.abort_handle();
+        match tokio::time::timeout(Duration::from_secs(MARKET_STOP_TIMEOUT_SECS), task.handle).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => error!("🛑 Market task {} failed: {}", task.context.market.slug, e),
+            Err(_) => {
+                warn!("Market task {} did not stop within {}s, aborting",
+                    task.context.market.slug, MARKET_STOP_TIMEOUT_SECS);
+                abort.abort();
+            }
+        }
+        self.hold_exposure(condition_id, &task.context).await;
+        info!("⏹️ Stopped market task {}", task.context.market.slug);
+        self.begin_settling(task.context).await;
+        true
+    }
+
+    /// Stop every market task
+    pub async fn stop_all(&self) {
+        let ids: Vec<String> = self.tasks.read().await.keys().cloned().collect();
+        for id in ids {
+            self.stop(&id).await;
+        }
+    }
+
+    /// Remove tasks that exited on their own (expired market, panic)
+    pub async fn reap_finished(&self) -> Vec<SeriesMarket> {
+        let finished: Vec<MarketTask> = {
+            let mut tasks = self.tasks.write().await;
+            let ids: Vec<String> = tasks
+                .iter()
+                .filter(|(_, t)| t.handle.is_finished())
+                .map(|(id, _)| id.clone())
+                .collect();
+            ids.iter().filter_map(|id| tasks.remove(id)).collect()
+        };
+        self.settling.write().await.retain(|_, ctx| !ctx.settled.is_cancelled());
+        let mut markets = Vec::with_capacity(finished.len());
+        for task in finished {
+            self.hold_exposure(&task.context.market.condition_id, &task.context).await;
+            markets.push(task.context.market.clone());
+            self.begin_settling(task.context).await;
+        }
+        markets
+    }
+
+    /// Keep a stopped market's context routable until it is settled
+    async fn begin_settling(&self, context: MarketContext) {
+        if !context.settled.is_cancelled() {
+            self.settling.write().await.insert(context.market.condition_id.clone(), context);
+        }
+    }
+
+    /// Re-measure a settling market's held exposure after a late fill
+    /// Running markets set their own exposure each cycle.
+    pub async fn refresh_settling(&self, context: &MarketContext) {
+        let condition_id = &context.market.condition_id;
+        if self.settling.read().await.contains_key(condition_id) {
+            self.hold_exposure(condition_id, context).await;
+        }
+    }
+
+    /// Shrink a stopped market's budget share to the shares it still holds
+    /// Held shares only turn back into capital when merged or redeemed.
+    async fn hold_exposure(&self, condition_id: &str, context: &MarketContext) {
+        let held = context.position_tracker.read().await.get_total_exposure().await;
+        if held > 0.0 {
+            self.budget.set_exposure(condition_id, held);
+        } else {
+            self.budget.release(condition_id);
+        }
+    }
+
+    /// Markets with a live task
+    pub async fn markets(&self) -> Vec<SeriesMarket> {
+        self.tasks
+            .read()
+            .await
+            .values()
+            .filter(|t| !t.handle.is_finished())
+            .map(|t| t.context.market.clone())
+            .collect()
+    }
+
+    /// Context of the market trading an asset, or of a stopped one still settling
+    pub async fn context_for_asset(&self, asset_id: &str) -> Option<MarketContext> {
+        let running = self.tasks
+            .read()
+            .await
+            .values()
+            .find(|t| t.context.owns_asset(asset_id))
+            .map(|t| t.context.clone());
+        if running.is_some() {
+            return running;
+        }
+        self.settling
+            .read()
+            .await
+            .values()
+            .find(|ctx| ctx.owns_asset(asset_id) && !ctx.settled.is_cancelled())
+            .cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::gamma::Market;
+
+    fn market(series: &str, id: &str) -> SeriesMarket {
+        SeriesMarket {
+            series: series.to_string(),
+            slot_start: 0,
+            slot_seconds: 300,
+            slug: format!("{}-0", series),
+            condition_id: id.to_string(),
+            up_token: format!("{}1", id),
+            down_token: format!("{}2", id),
+            end_time: chrono::Utc::now() + chrono::Duration::seconds(300),
+            market: Market::default(),
+        }
+    }
+
+    #[test]
+    fn test_budget_shared_across_markets() {
+        let budget = PositionBudget::new(30.0);
+        budget.set_exposure("btc", 12.0);
+        assert_eq!(budget.reserve("eth", 10.0), 10.0);
+        // Only 8 left for anyone
+        assert_eq!(budget.reserve("sol", 10.0), 8.0);
+        assert_eq!(budget.reserve("btc", 1.0), 0.0);
+
+        budget.refund("sol", 3.0);
+        assert!((budget.remaining() - 3.0).abs() < 1e-9);
+        budget.release("eth");
+        assert!((budget.total() - 17.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_engine_isolates_market_tasks() {
+        let engine = MarketEngine::new(30.0);
+
+        // One market's task panics, the other keeps running until stopped
+        engine.start(market("btc-updown-5m", "0xa"), |_ctx| async { panic!("boom") }).await;
+        engine
+            .start(market("eth-updown-5m", "0xb"), |ctx| async move { ctx.cancel.cancelled().await })
+            .await;
+        assert!(!engine.start(market("eth-updown-5m", "0xb"), |_ctx| async {}).await);
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let reaped = engine.reap_finished().await;
+        assert_eq!(reaped.len(), 1);
+        assert_eq!(reaped[0].condition_id, "0xa");
+
+        let ctx = engine.context_for_asset("0xb2").await.unwrap();
+        assert_eq!(ctx.market.series, "eth-updown-5m");
+        // The panicked market still routes until it is settled
+        let panicked = engine.context_for_asset("0xa1").await.unwrap();
+        panicked.settled.cancel();
+        assert!(engine.context_for_asset("0xa1").await.is_none());
+
+        assert!(engine.stop("0xb").await);
+        assert!(engine.markets().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_stopped_market_keeps_held_exposure() {
+        use crate::api::Side;
+
+        let engine = MarketEngine::new(30.0);
+        engine
+            .start(market("btc-updown-5m", "0xa"), |ctx| async move {
+                ctx.position_tracker.write().await.update_position("0xa1", Side::Buy, 10.0, 0.6).await;
+                ctx.budget.set_exposure("0xa", 6.0);
+                ctx.cancel.cancelled().await
+            })
+            .await;
+        engine.start(market("eth-updown-5m", "0xb"), |_ctx| async {}).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // Shares held until settlement still count against the cap
+        assert!(engine.stop("0xa").await);
+        assert!((engine.budget().exposure("0xa") - 6.0).abs() < 1e-9);
+        assert_eq!(engine.reap_finished().await.len(), 1);
+        assert!((engine.budget().remaining() - 24.0).abs() < 1e-9);
+
+        // Redeemed: the capital is back
+        engine.budget().release("0xa");
+        assert_eq!(engine.budget().remaining(), 30.0);
+    }
+
+    #[tokio::test]
+    async fn test_fill_after_stop_reaches_settling_market() {
+        use crate::trading::order_tracker::apply_user_event;
+        use crate::trading::stats::TradingStats;
+        use crate::websocket::user::{UserEvent, UserTradeEvent};
+
+        let engine = MarketEngine::new(30.0);
+        engine
+            .start(market("btc-updown-5m", "0xa"), |ctx| async move {
+                ctx.order_tracker.write().await.track_order(
+                    "0xa1".to_string(), "order_1".to_string(), "BUY".to_string(), 0.5, 10.0);
+                ctx.cancel.cancelled().await
+            })
+            .await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(engine.stop("0xa").await);
+        assert_eq!(engine.budget().exposure("0xa"), 0.0);
+
+        // The bid matched just before the cancel landed; the trade arrives afterwards
+        let ctx = engine.context_for_asset("0xa1").await.unwrap();
+        let stats = RwLock::new(TradingStats::new());
+        let event = UserEvent::Trade(UserTradeEvent {
+            trade_id: "t1".to_string(),
+            asset_id: "0xa1".to_string(),
+            status: "MATCHED".to_string(),
+            size: 10.0,
+            price: 0.5,
+            taker_order_id: "order_1".to_string(),
+            maker_orders: vec![],
+        });
+        let fills = apply_user_event(&event, &ctx.order_tracker, &ctx.position_tracker, &stats).await;
+        assert_eq!(fills.len(), 1);
+        engine.refresh_settling(&ctx).await;
+        assert_eq!(ctx.position_tracker.read().await.held_shares("0xa1"), 10.0);
+        assert!((engine.budget().exposure("0xa") - 5.0).abs() < 1e-9);
+
+        // Settled: no longer routed, and reaping drops it
+        ctx.settled.cancel();
+        assert!(engine.context_for_asset("0xa1").await.is_none());
+        engine.reap_finished().await;
+        assert!(engine.settling.read().await.is_empty());
+    }
+}
@@ -50,6 +50,10 @@ pub enum TradingError {
     #[error("Invalid order ID: {order_id}")]
     InvalidOrderId { order_id: String },
     
+    /// Order lifecycle transition not allowed
+    #[error("Invalid order transition for {order_id}: {from} -> {to}")]
+    InvalidOrderTransition { order_id: String, from: String, to: String },
+    
     /// Wallet not authenticated
     #[error("Wallet not authenticated")]
     NotAuthenticated,
@@ -86,6 +90,7 @@ impl TradingError {
             TradingError::NetworkError { .. } => "NETWORK",
             TradingError::TimeoutError { .. } => "TIMEOUT",
             TradingError::InvalidOrderId { .. } => "INVALID_ORDER_ID",
+            TradingError::InvalidOrderTransition { .. } => "ORDER_STATE",
             TradingError::NotAuthenticated => "AUTH",
             TradingError::Unknown { .. } => "UNKNOWN",
         }
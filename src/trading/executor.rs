@@ -1,23 +1,42 @@
 //! Trading execution using polymarket-client-sdk 0.4
-//! Uses dynamic client creation per operation to handle type states
+//! Holds one long-lived authenticated CLOB client shared by all operations
 
 use polymarket_client_sdk::{
+    auth::{state::Authenticated, ExposeSecret, Normal},
     clob::{
         Client,
         Config,
-        types::{Side, OrderType},
-        types::request::OrdersRequest,
-        types::response::PostOrderResponse,
+        types::{Side, OrderType, OrderStatusType, TradeStatusType},
+        types::request::{OrdersRequest, TradesRequest},
+        types::response::{OpenOrderResponse, PostOrderResponse, TradeResponse},
     },
+    error::Status,
     types::{Decimal, U256},
 };
 use alloy::signers::local::PrivateKeySigner;
 use alloy::signers::Signer;
+use std::future::Future;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
 use tracing::{info, error, warn};
+use crate::config::EndpointsConfig;
+use crate::websocket::UserChannelAuth;
+use crate::trading::order_tracker::{OrderFillUpdate, OrderState, TradeFill};
+use rust_decimal::prelude::ToPrimitive;
 use crate::utils::retry::{retry_with_backoff, RetryConfig};
 use std::time::{Duration, Instant};
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, RwLock};
+
+/// Authenticated CLOB client type (L2 credentials attached)
+type AuthenticatedClient = Client<Authenticated<Normal>>;
+
+/// Cached authenticated client with a generation counter
+/// The generation lets concurrent callers avoid discarding a client
+/// that another task has already renewed.
+struct CachedClient {
+    client: AuthenticatedClient,
+    generation: u64,
+}
 
 /// Result of canceling orders for a market
 #[derive(Debug, Clone)]
@@ -26,6 +45,22 @@ pub struct CancelOrdersResult {
     pub filled_orders: Vec<String>,
 }
 
+/// Immediate (FOK/FAK) order outcome
+#[derive(Debug, Clone)]
+pub struct ImmediateFill {
+    pub order_id: String,
+    /// Shares matched on arrival; the rest was killed
+    pub filled_size: f64,
+    /// Average execution price of the matched shares
+    pub avg_price: f64,
+}
+
+/// Last page marker returned by paginated CLOB endpoints
+const END_CURSOR: &str = "LTE=";
+
+/// Upper bound on trade pages fetched per reconciliation
+const MAX_TRADE_PAGES: usize = 10;
+
 /// Rate limiter for API calls
 pub struct RateLimiter {
     last_request: Mutex<Instant>,
@@ -56,7 +91,11 @@ impl RateLimiter {
 
 /// Trading executor
 pub struct TradeExecutor {
-    private_key: String,
+    signer: PrivateKeySigner,
+    clob_url: String,
+    gamma_url: String,
+    client: RwLock<Option<CachedClient>>,
+    generation: AtomicU64,
     simulation_mode: bool,
     rate_limiter: RateLimiter,
 }
@@ -68,7 +107,11 @@ impl TradeExecutor {
         _api_key: Option<String>,
         _api_secret: Option<String>,
         _api_passphrase: Option<String>,
-    ) -> Result<Self, Box<dyn std::error::Error>> {
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let mut signer = PrivateKeySigner::from_str(private_key)?;
+        // Set chain ID for Polygon mainnet (required by SDK)
+        signer.set_chain_id(Some(137));
+
         info!("✅ TradeExecutor created successfully");
 
         let simulation_mode = std::env::var("SIMULATION_MODE").unwrap_or_default() == "true";
@@ -77,18 +120,81 @@ impl TradeExecutor {
         }
         
         Ok(Self { 
-            private_key: private_key.to_string(),
+            signer,
+            clob_url: crate::api::clob::CLOB_API_URL.to_string(),
+            gamma_url: crate::api::gamma::GAMMA_API_URL.to_string(),
+            client: RwLock::new(None),
+            generation: AtomicU64::new(0),
             simulation_mode,
             rate_limiter: RateLimiter::new(200),
         })
     }
 
-    /// Get signer from private key
-    fn get_signer(&self) -> Result<PrivateKeySigner, Box<dyn std::error::Error>> {
-        let mut signer = PrivateKeySigner::from_str(&self.private_key)?;
-        // Set chain ID for Polygon mainnet (required by SDK)
-        signer.set_chain_id(Some(137));
-        Ok(signer)
+    /// Use endpoint URLs from config instead of the production defaults
+    pub fn with_endpoints(mut self, endpoints: &EndpointsConfig) -> Self {
+        self.clob_url = endpoints.clob.trim_end_matches('/').to_string();
+        self.gamma_url = endpoints.gamma.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Gamma API base URL
+    pub fn gamma_url(&self) -> &str {
+        &self.gamma_url
+    }
+
+    /// Get the cached authenticated client, authenticating on first use
+    async fn authenticated_client(
+        &self,
+    ) -> Result<(AuthenticatedClient, u64), Box<dyn std::error::Error + Send + Sync>> {
+        if let Some(cached) = self.client.read().await.as_ref() {
+            return Ok((cached.client.clone(), cached.generation));
+        }
+
+        let mut guard = self.client.write().await;
+        // Another task may have authenticated while we waited for the lock
+        if let Some(cached) = guard.as_ref() {
+            return Ok((cached.client.clone(), cached.generation));
+        }
+
+        let config = Config::builder().use_server_time(true).build();
+        let client = Client::new(&self.clob_url, config)?
+            .authentication_builder(&self.signer)
+            .authenticate()
+            .await?;
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        info!("🔑 CLOB client authenticated (session #{})", generation);
+
+        *guard = Some(CachedClient {
+            client: client.clone(),
+            generation,
+        });
+        Ok((client, generation))
+    }
+
+    /// Drop the cached client if it is still the one that was rejected
+    async fn invalidate_client(&self, generation: u64) {
+        let mut guard = self.client.write().await;
+        if guard.as_ref().map(|c| c.generation) == Some(generation) {
+            *guard = None;
+        }
+    }
+
+    /// Run an authenticated call, renewing credentials once if the server rejects them
+    async fn with_client<T, F, Fut>(&self, operation: F) -> Result<T, Box<dyn std::error::Error + Send + Sync>>
+    where
+        F: Fn(AuthenticatedClient) -> Fut,
+        Fut: Future<Output = polymarket_client_sdk::Result<T>>,
+    {
+        let (client, generation) = self.authenticated_client().await?;
+        match operation(client).await {
+            Err(e) if is_auth_rejection(&e) => {
+                warn!("🔑 CLOB credentials rejected, re-authenticating: {}", e);
+                self.invalidate_client(generation).await;
+                let (client, _) = self.authenticated_client().await?;
+                Ok(operation(client).await?)
+            }
+            result => Ok(result?),
+        }
     }
 
     /// Set simulation mode
@@ -106,12 +212,20 @@ impl TradeExecutor {
         self.simulation_mode
     }
 
+    /// L2 API credentials for the authenticated user WebSocket channel
+    pub async fn user_channel_auth(&self) -> Result<UserChannelAuth, Box<dyn std::error::Error + Send + Sync>> {
+        let (client, _) = self.authenticated_client().await?;
+        let credentials = client.credentials();
+        Ok(UserChannelAuth {
+            api_key: credentials.key().to_string(),
+            secret: credentials.secret().expose_secret().to_string(),
+            passphrase: credentials.passphrase().expose_secret().to_string(),
+        })
+    }
+
     /// Get the signer address
     pub fn address(&self) -> String {
-        match self.get_signer() {
-            Ok(signer) => format!("{:?}", signer.address()),
-            Err(_) => "0x0".to_string(),
-        }
+        format!("{:?}", self.signer.address())
     }
 
     /// Check if price is in safe range
@@ -120,13 +234,14 @@ impl TradeExecutor {
     }
 
     /// Get USDC balance from Gamma API
-    pub async fn get_usdc_balance(&self) -> Result<f64, Box<dyn std::error::Error>> {
+    pub async fn get_usdc_balance(&self) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
         let address = self.address();
         // Try different API endpoints
-        let urls = vec![
-            format!("https://gamma-api.polymarket.com/users/{}/balances", address),
-            format!("https://api.polymarket.com/users/{}/balances", address),
-        ];
+        let mut urls = vec![format!("{}/users/{}/balances", self.gamma_url, address)];
+        // Legacy fallback only applies to production; never leak calls past a configured mock
+        if self.gamma_url == crate::api::gamma::GAMMA_API_URL {
+            urls.push(format!("https://api.polymarket.com/users/{}/balances", address));
+        }
 
         for url in urls {
             info!("Fetching balance from: {}", url);
@@ -135,7 +250,7 @@ impl TradeExecutor {
                 "get_usdc_balance",
                 RetryConfig::new(3, 200),
                 || async {
-                    reqwest::get(&url).await.map_err(|e| Box::new(e) as Box<dyn std::error::Error>)
+                    reqwest::get(&url).await.map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)
                 },
             ).await;
 
@@ -174,7 +289,64 @@ impl TradeExecutor {
         side: Side,
         price: f64,
         size: f64,
-    ) -> Result<PostOrderResponse, Box<dyn std::error::Error>> {
+    ) -> Result<PostOrderResponse, Box<dyn std::error::Error + Send + Sync>> {
+        self.post_limit_order(token_id, side, price, size, OrderType::GTC).await
+    }
+
+    /// Place a FOK or FAK limit order and report what matched immediately
+    pub async fn place_immediate_order(
+        &self,
+        token_id: &str,
+        side: Side,
+        price: f64,
+        size: f64,
+        order_type: OrderType,
+    ) -> Result<ImmediateFill, Box<dyn std::error::Error + Send + Sync>> {
+        if !matches!(order_type, OrderType::FOK | OrderType::FAK) {
+            return Err(format!("{} is not an immediate order type", order_type).into());
+        }
+        if self.simulation_mode {
+            // Simulated orders never fill
+            info!("🎮 [SIMULATION] {} {:?} {} @ {} (not filled)", order_type, side, size, price);
+            return Ok(ImmediateFill {
+                order_id: format!("simulated_{}", uuid::Uuid::new_v4()),
+                filled_size: 0.0,
+                avg_price: price,
+            });
+        }
+        self.rate_limiter.wait().await;
+        let response = self.post_limit_order(token_id, side, price, size, order_type).await?;
+        if !response.success {
+            return Err(response.error_msg.unwrap_or_else(|| "order not accepted".to_string()).into());
+        }
+
+        // BUY makes USDC and takes shares; SELL the other way round
+        let (shares, usdc) = match side {
+            Side::Buy => (response.taking_amount, response.making_amount),
+            _ => (response.making_amount, response.taking_amount),
+        };
+        let filled_size = shares.to_f64().unwrap_or(0.0);
+        let avg_price = if filled_size > 0.0 {
+            usdc.to_f64().unwrap_or(0.0) / filled_size
+        } else {
+            price
+        };
+        Ok(ImmediateFill {
+            order_id: response.order_id,
+            filled_size,
+            avg_price,
+        })
+    }
+
+    /// Build, sign and post a limit order with the given time in force
+    async fn post_limit_order(
+        &self,
+        token_id: &str,
+        side: Side,
+        price: f64,
+        size: f64,
+        order_type: OrderType,
+    ) -> Result<PostOrderResponse, Box<dyn std::error::Error + Send + Sync>> {
         if self.simulation_mode {
             info!("🎮 [SIMULATION] {:?} {} @ {}", side, size, price);
             // Create mock response via JSON
@@ -187,15 +359,6 @@ impl TradeExecutor {
             return Ok(response);
         }
         
-        let signer = self.get_signer()?;
-        let config = Config::builder().use_server_time(true).build();
-        
-        // Create and authenticate client inline
-        let client = Client::new("https://clob.polymarket.com", config)?
-            .authentication_builder(&signer)
-            .authenticate()
-            .await?;
-        
         let token_id_u256 = U256::from_str(token_id)?;
         // Round price to 2 decimal places to match tick size 0.01
         // Use Decimal for precise rounding
@@ -205,18 +368,22 @@ impl TradeExecutor {
         let size_decimal = Decimal::from_f64_retain(size).unwrap_or(Decimal::ZERO);
         
         // Build, sign and post order
-        let order = client
-            .limit_order()
-            .token_id(token_id_u256)
-            .size(size_decimal)
-            .price(price_decimal)
-            .side(side)
-            .order_type(OrderType::GTC)
-            .build()
-            .await?;
-        
-        let signed_order = client.sign(&signer, order).await?;
-        let response = client.post_order(signed_order).await?;
+        let signer = &self.signer;
+        let order_type = &order_type;
+        let response = self.with_client(|client| async move {
+            let order = client
+                .limit_order()
+                .token_id(token_id_u256)
+                .size(size_decimal)
+                .price(price_decimal)
+                .side(side)
+                .order_type(order_type.clone())
+                .build()
+                .await?;
+
+            let signed_order = client.sign(signer, order).await?;
+            client.post_order(signed_order).await
+        }).await?;
         
         info!("✅ Order placed: {} (success: {})", response.order_id, response.success);
         Ok(response)
@@ -228,7 +395,7 @@ impl TradeExecutor {
         token_id: &str,
         price: f64,
         size: f64,
-    ) -> Result<PostOrderResponse, Box<dyn std::error::Error>> {
+    ) -> Result<PostOrderResponse, Box<dyn std::error::Error + Send + Sync>> {
         self.place_limit_order(token_id, Side::Buy, price, size).await
     }
 
@@ -238,35 +405,29 @@ impl TradeExecutor {
         token_id: &str,
         price: f64,
         size: f64,
-    ) -> Result<PostOrderResponse, Box<dyn std::error::Error>> {
+    ) -> Result<PostOrderResponse, Box<dyn std::error::Error + Send + Sync>> {
         self.place_limit_order(token_id, Side::Sell, price, size).await
     }
 
     /// Get open orders
     pub async fn get_open_orders(
         &self,
-    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error>> {
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>> {
         if self.simulation_mode {
             return Ok(vec![]);
         }
         
-        let signer = self.get_signer()?;
-        let config = Config::builder().use_server_time(true).build();
-        
-        let client = Client::new("https://clob.polymarket.com", config)?
-            .authentication_builder(&signer)
-            .authenticate()
-            .await?;
-        
-        let request = OrdersRequest::default();
-        let response = client.orders(&request, None).await?;
+        let response = self.with_client(|client| async move {
+            client.orders(&OrdersRequest::default(), None).await
+        }).await?;
         
         // Convert to JSON values for flexibility
         let orders: Vec<serde_json::Value> = response.data
             .into_iter()
             .map(|order| serde_json::json!({
                 "id": order.id,
-                "asset_id": order.asset_id,
+                // Decimal form, same as token IDs from Gamma
+                "asset_id": order.asset_id.to_string(),
                 "side": order.side,
                 "price": order.price,
                 "size": order.original_size,
@@ -278,41 +439,29 @@ impl TradeExecutor {
     }
 
     /// Cancel a specific order by ID
-    pub async fn cancel_order(&self, order_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+    pub async fn cancel_order(&self, order_id: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         if self.simulation_mode {
             info!("🎮 [SIMULATION] Would cancel order: {}", order_id);
             return Ok(());
         }
         
-        let signer = self.get_signer()?;
-        let config = Config::builder().use_server_time(true).build();
-        
-        let client = Client::new("https://clob.polymarket.com", config)?
-            .authentication_builder(&signer)
-            .authenticate()
-            .await?;
-        
-        client.cancel_order(order_id).await?;
+        self.with_client(|client| async move {
+            client.cancel_order(order_id).await
+        }).await?;
         info!("✅ Cancelled order: {}", order_id);
         Ok(())
     }
 
     /// Cancel all orders
-    pub async fn cancel_all(&self) -> Result<(), Box<dyn std::error::Error>> {
+    pub async fn cancel_all(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         if self.simulation_mode {
             info!("🎮 [SIMULATION] Would cancel all orders");
             return Ok(());
         }
         
-        let signer = self.get_signer()?;
-        let config = Config::builder().use_server_time(true).build();
-        
-        let client = Client::new("https://clob.polymarket.com", config)?
-            .authentication_builder(&signer)
-            .authenticate()
-            .await?;
-        
-        client.cancel_all_orders().await?;
+        self.with_client(|client| async move {
+            client.cancel_all_orders().await
+        }).await?;
         info!("✅ Cancelled all orders");
         Ok(())
     }
@@ -321,7 +470,7 @@ impl TradeExecutor {
     pub async fn cancel_orders_for_market(
         &self,
         token_id: &str,
-    ) -> Result<CancelOrdersResult, Box<dyn std::error::Error>> {
+    ) -> Result<CancelOrdersResult, Box<dyn std::error::Error + Send + Sync>> {
         info!("Cancelling orders for market {}", token_id);
         
         let orders = self.get_open_orders().await?;
@@ -350,37 +499,96 @@ impl TradeExecutor {
         })
     }
 
-    /// Get filled orders
+    /// Get fully filled orders
     pub async fn get_filled_orders(
         &self,
-        _token_id: &str,
+        token_id: &str,
         tracked_order_ids: &[String],
-    ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
-        let open_orders = self.get_open_orders().await?;
-        let open_ids: std::collections::HashSet<String> = open_orders
-            .iter()
-            .filter_map(|o| o.get("id").and_then(|v| v.as_str()).map(|s| s.to_string()))
-            .collect();
-        
-        let filled: Vec<String> = tracked_order_ids
+    ) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+        let updates = self.get_order_fills(token_id, tracked_order_ids).await?;
+        Ok(updates
+            .into_iter()
+            .filter(|u| u.state == OrderState::Filled)
+            .map(|u| u.order_id)
+            .collect())
+    }
+
+    /// Reconcile tracked orders against the CLOB
+    /// Matched size comes from each order's `size_matched`; execution prices
+    /// and trade IDs come from the trades endpoint.
+    pub async fn get_order_fills(
+        &self,
+        token_id: &str,
+        tracked_order_ids: &[String],
+    ) -> Result<Vec<OrderFillUpdate>, Box<dyn std::error::Error + Send + Sync>> {
+        if self.simulation_mode || tracked_order_ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut orders = Vec::with_capacity(tracked_order_ids.len());
+        for order_id in tracked_order_ids {
+            self.rate_limiter.wait().await;
+            match self.with_client(|client| async move { client.order(order_id).await }).await {
+                Ok(order) => orders.push(order),
+                Err(e) => warn!("Failed to fetch order {}: {}", order_id, e),
+            }
+        }
+
+        // Only matched orders need trade lookups
+        let oldest_matched = orders
             .iter()
-            .filter(|id| !open_ids.contains(*id))
-            .cloned()
-            .collect();
-        
-        Ok(filled)
+            .filter(|o| !o.size_matched.is_zero())
+            .map(|o| o.created_at.timestamp())
+            .min();
+        let trades = match oldest_matched {
+            Some(oldest) => self.get_trades_since(token_id, oldest - 1).await.unwrap_or_else(|e| {
+                warn!("Failed to fetch trades for {}: {}", token_id, e);
+                vec![]
+            }),
+            None => vec![],
+        };
+
+        Ok(orders.iter().map(|order| order_fill_update(order, &trades)).collect())
+    }
+
+    /// Fetch our trades on a token matched after a unix timestamp
+    async fn get_trades_since(
+        &self,
+        token_id: &str,
+        after: i64,
+    ) -> Result<Vec<TradeResponse>, Box<dyn std::error::Error + Send + Sync>> {
+        let asset_id = U256::from_str(token_id)?;
+        let request = TradesRequest::builder().asset_id(asset_id).after(after).build();
+
+        let mut trades = Vec::new();
+        let mut cursor: Option<String> = None;
+        for _ in 0..MAX_TRADE_PAGES {
+            let next = cursor.clone();
+            let request = &request;
+            let page = self.with_client(|client| {
+                let next = next.clone();
+                async move { client.trades(request, next).await }
+            }).await?;
+            let done = page.data.is_empty() || page.next_cursor.is_empty() || page.next_cursor == END_CURSOR;
+            trades.extend(page.data);
+            if done {
+                break;
+            }
+            cursor = Some(page.next_cursor);
+        }
+        Ok(trades)
     }
 
     /// Get server time
-    pub async fn server_time(&self) -> Result<u64, Box<dyn std::error::Error>> {
-        let response = reqwest::get("https://clob.polymarket.com/time").await?;
+    pub async fn server_time(&self) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+        let response = reqwest::get(format!("{}/time", self.clob_url)).await?;
         let time: u64 = response.json().await?;
         Ok(time)
     }
 
     /// Get markets from Gamma API
-    pub async fn get_markets(&self) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error>> {
-        let response = reqwest::get("https://gamma-api.polymarket.com/markets?limit=100").await?;
+    pub async fn get_markets(&self) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let response = reqwest::get(format!("{}/markets?limit=100", self.gamma_url)).await?;
         let markets: Vec<serde_json::Value> = response.json().await?;
         Ok(markets)
     }
@@ -394,7 +602,7 @@ impl TradeExecutor {
         size: f64,
         safe_low: f64,
         safe_high: f64,
-    ) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    ) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
         if !self.is_price_in_safe_range(price, safe_low, safe_high) {
             warn!("Price {} outside safe range [{}, {}]", price, safe_low, safe_high);
             return Ok(None);
@@ -426,4 +634,56 @@ impl TradeExecutor {
             }
         }
     }
-}
\ No newline at end of file
+}
+
+/// Check whether an SDK error means our L2 credentials were rejected
+fn is_auth_rejection(err: &polymarket_client_sdk::error::Error) -> bool {
+    err.downcast_ref::<Status>()
+        .map(|status| status.status_code.as_u16() == 401)
+        .unwrap_or(false)
+}
+
+/// Build a fill update for one order from its CLOB state and our trades
+fn order_fill_update(order: &OpenOrderResponse, trades: &[TradeResponse]) -> OrderFillUpdate {
+    let size_matched = order.size_matched.to_f64().unwrap_or(0.0);
+    // GTC orders carry a zero expiration
+    let expired = order.expiration.timestamp() > 0 && order.expiration <= chrono::Utc::now();
+    let state = match order.status {
+        OrderStatusType::Matched => OrderState::Filled,
+        OrderStatusType::Canceled if expired => OrderState::Expired,
+        OrderStatusType::Canceled | OrderStatusType::Unmatched => OrderState::Cancelled,
+        _ if size_matched > 0.0 => OrderState::PartiallyFilled,
+        _ => OrderState::Live,
+    };
+
+    OrderFillUpdate {
+        order_id: order.id.clone(),
+        state,
+        original_size: order.original_size.to_f64().unwrap_or(0.0),
+        size_matched,
+        trades: trades
+            .iter()
+            .filter_map(|trade| trade_fill_for_order(trade, &order.id))
+            .collect(),
+    }
+}
+
+/// Extract the part of a trade that executed against our order
+fn trade_fill_for_order(trade: &TradeResponse, order_id: &str) -> Option<TradeFill> {
+    if trade.status == TradeStatusType::Failed {
+        return None;
+    }
+
+    let (size, price) = if trade.taker_order_id == order_id {
+        (trade.size, trade.price)
+    } else {
+        let maker = trade.maker_orders.iter().find(|m| m.order_id == order_id)?;
+        (maker.matched_amount, maker.price)
+    };
+
+    Some(TradeFill {
+        trade_id: trade.id.clone(),
+        size: size.to_f64()?,
+        price: price.to_f64()?,
+    })
+}
@@ -1,9 +1,91 @@
 //! Position exit manager for take profit and stop loss
 //! Matches Python: should_exit_position()
 
+use serde::{Deserialize, Serialize};
 use std::time::{Duration, Instant};
 use tracing::info;
 
+use crate::config::TradingConfig;
+use crate::trading::order_tracker::Fill;
+use crate::trading::strategy::round_to_tick;
+
+/// Price tick
+const TICK: f64 = 0.01;
+
+/// Why a position is exited
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExitReason {
+    TimeStop,
+    Expiry,
+    TakeProfit,
+    StopLoss,
+}
+
+impl ExitReason {
+    /// Stats key
+    pub fn label(&self) -> &'static str {
+        match self {
+            ExitReason::TimeStop => "time_stop",
+            ExitReason::Expiry => "expiry",
+            ExitReason::TakeProfit => "take_profit",
+            ExitReason::StopLoss => "stop_loss",
+        }
+    }
+}
+
+/// How an exit SELL is priced
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitStyle {
+    /// Rest one tick inside the ask, never crossing the bid
+    Aggressive,
+    /// Cross the spread and hit the bid
+    Marketable,
+}
+
+impl ExitStyle {
+    /// SELL limit for this style at the current touch
+    pub fn sell_price(&self, bid: f64, ask: f64) -> f64 {
+        match self {
+            ExitStyle::Aggressive => round_to_tick((ask - TICK).max(bid + TICK)),
+            ExitStyle::Marketable => round_to_tick(bid),
+        }
+    }
+}
+
+/// Exit order style per exit reason
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExitStyles {
+    pub take_profit: ExitStyle,
+    pub stop_loss: ExitStyle,
+    pub time_stop: ExitStyle,
+    pub expiry: ExitStyle,
+}
+
+impl Default for ExitStyles {
+    fn default() -> Self {
+        Self {
+            take_profit: ExitStyle::Aggressive,
+            stop_loss: ExitStyle::Marketable,
+            time_stop: ExitStyle::Aggressive,
+            expiry: ExitStyle::Marketable,
+        }
+    }
+}
+
+impl ExitStyles {
+    /// Style configured for a reason
+    pub fn for_reason(&self, reason: ExitReason) -> ExitStyle {
+        match reason {
+            ExitReason::TakeProfit => self.take_profit,
+            ExitReason::StopLoss => self.stop_loss,
+            ExitReason::TimeStop => self.time_stop,
+            ExitReason::Expiry => self.expiry,
+        }
+    }
+}
+
 /// Position with tracking info
 #[derive(Debug, Clone)]
 pub struct TrackedPosition {
@@ -19,6 +101,8 @@ pub struct TrackedPosition {
 pub struct ExitCheck {
     pub should_exit: bool,
     pub reason: String,
+    /// Set when `should_exit`
+    pub exit_reason: Option<ExitReason>,
     pub pnl: f64,
 }
 
@@ -70,16 +154,18 @@ impl ExitManager {
                 should_exit: true,
                 reason: format!("Time stop ({:.0}s > {:.0}s)", 
                     hold_time.as_secs(), self.max_hold_time.as_secs()),
+                exit_reason: Some(ExitReason::TimeStop),
                 pnl,
             };
         }
         
         // 2. Expiry approaching
         if let Some(expiry) = time_to_expiry {
-            if expiry < self.exit_before_expiry {
+            if expiry <= self.exit_before_expiry {
                 return ExitCheck {
                     should_exit: true,
                     reason: format!("Expiry approaching ({:.0}s left)", expiry.as_secs()),
+                    exit_reason: Some(ExitReason::Expiry),
                     pnl,
                 };
             }
@@ -90,6 +176,7 @@ impl ExitManager {
             return ExitCheck {
                 should_exit: true,
                 reason: format!("Take profit (+{:.1}%)", pnl * 100.0),
+                exit_reason: Some(ExitReason::TakeProfit),
                 pnl,
             };
         }
@@ -99,6 +186,7 @@ impl ExitManager {
             return ExitCheck {
                 should_exit: true,
                 reason: format!("Stop loss ({:.1}%)", pnl * 100.0),
+                exit_reason: Some(ExitReason::StopLoss),
                 pnl,
             };
         }
@@ -107,6 +195,7 @@ impl ExitManager {
         ExitCheck {
             should_exit: false,
             reason: "Hold".to_string(),
+            exit_reason: None,
             pnl,
         }
     }
@@ -126,6 +215,12 @@ impl ExitManager {
     }
 }
 
+impl From<&TradingConfig> for ExitManager {
+    fn from(config: &TradingConfig) -> Self {
+        Self::new(config.max_hold_time, config.exit_before_expiry, config.take_profit, config.stop_loss)
+    }
+}
+
 /// Position tracker with exit management
 pub struct PositionExitTracker {
     positions: std::collections::HashMap<String, TrackedPosition>,
@@ -141,7 +236,7 @@ impl PositionExitTracker {
         }
     }
     
-    /// Add or update position
+    /// Add or update position; the hold clock keeps running from the first entry
     pub fn update_position(
         &mut self,
         token_id: String,
@@ -149,25 +244,39 @@ impl PositionExitTracker {
         size: f64,
         avg_price: f64,
     ) {
+        let entry_time = self
+            .positions
+            .get(&token_id)
+            .map(|p| p.entry_time)
+            .unwrap_or_else(Instant::now);
         self.positions.insert(token_id.clone(), TrackedPosition {
             token_id,
             side,
             size,
             avg_price,
-            entry_time: Instant::now(),
+            entry_time,
         });
     }
     
+    /// Get tracked position
+    pub fn get_position(
+        &self,
+        token_id: &str,
+    ) -> Option<&TrackedPosition> {
+        self.positions.get(token_id)
+    }
+    
     /// Check all positions for exit
     pub fn check_all_positions(
         &self,
         prices: &std::collections::HashMap<String, f64>,
+        time_to_expiry: Option<Duration>,
     ) -> Vec<(String, ExitCheck)> {
         let mut exits = Vec::new();
         
         for (token_id, position) in &self.positions {
             if let Some(&price) = prices.get(token_id) {
-                let check = self.exit_manager.check_exit(position, price, None);
+                let check = self.exit_manager.check_exit(position, price, time_to_expiry);
                 if check.should_exit {
                     exits.push((token_id.clone(), check));
                 }
@@ -185,9 +294,120 @@ impl PositionExitTracker {
         self.positions.remove(token_id);
     }
     
+    /// Exit rules
+    pub fn exit_manager(&self) -> &ExitManager {
+        &self.exit_manager
+    }
+    
     /// Get position count
     pub fn count(&self,
     ) -> usize {
         self.positions.len()
     }
-}
\ No newline at end of file
+}
+
+/// Exit SELL orders still working, so their fills can be booked as exit PnL
+#[derive(Debug, Default)]
+pub struct ExitOrders {
+    /// order_id -> (reason, average entry price when the exit was placed)
+    orders: std::collections::HashMap<String, (ExitReason, f64)>,
+}
+
+impl ExitOrders {
+    /// Remember a placed exit order
+    pub fn insert(
+        &mut self,
+        order_id: String,
+        reason: ExitReason,
+        avg_price: f64,
+    ) {
+        self.orders.insert(order_id, (reason, avg_price));
+    }
+    
+    /// PnL of a fill against its exit's entry price; None if the fill is not an exit
+    pub fn pnl_for(
+        &self,
+        fill: &Fill,
+    ) -> Option<(ExitReason, f64)> {
+        let (reason, avg_price) = self.orders.get(&fill.order_id)?;
+        Some((*reason, (fill.price - avg_price) * fill.size))
+    }
+    
+    /// Drop exits whose orders are no longer tracked
+    pub fn retain(
+        &mut self,
+        mut keep: impl FnMut(&str) -> bool,
+    ) {
+        self.orders.retain(|order_id, _| keep(order_id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_exit_reasons() {
+        let mut tracker = PositionExitTracker::new(ExitManager::new(180, 120, 0.03, 0.05));
+        tracker.update_position("up".into(), "UP".into(), 5.0, 0.50);
+        tracker.update_position("down".into(), "DOWN".into(), 5.0, 0.50);
+        let entry = tracker.get_position("up").unwrap().entry_time;
+
+        // Adding to a position keeps its hold clock
+        tracker.update_position("up".into(), "UP".into(), 8.0, 0.48);
+        assert_eq!(tracker.get_position("up").unwrap().entry_time, entry);
+
+        let prices = HashMap::from([("up".to_string(), 0.60), ("down".to_string(), 0.40)]);
+        let mut exits = tracker.check_all_positions(&prices, Some(Duration::from_secs(200)));
+        exits.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(exits.len(), 2);
+        assert_eq!(exits[0].1.exit_reason, Some(ExitReason::StopLoss));
+        assert_eq!(exits[1].1.exit_reason, Some(ExitReason::TakeProfit));
+
+        // Inside the expiry window everything goes, whatever the PnL
+        let flat = HashMap::from([("up".to_string(), 0.48), ("down".to_string(), 0.50)]);
+        assert!(tracker.check_all_positions(&flat, Some(Duration::from_secs(200))).is_empty());
+        let exits = tracker.check_all_positions(&flat, Some(Duration::from_secs(60)));
+        assert!(exits.iter().all(|(_, c)| c.exit_reason == Some(ExitReason::Expiry)));
+    }
+
+    #[test]
+    fn test_exit_pnl_from_fills() {
+        let mut exits = ExitOrders::default();
+        exits.insert("exit_1".into(), ExitReason::StopLoss, 0.50);
+        let fill = |order_id: &str, size, price| Fill {
+            order_id: order_id.to_string(),
+            token: "up".to_string(),
+            side: "SELL".to_string(),
+            size,
+            price,
+            trade_id: Some("trade_1".to_string()),
+        };
+
+        // Booked at the execution price, not the limit the exit was placed at
+        let (reason, pnl) = exits.pnl_for(&fill("exit_1", 4.0, 0.42)).unwrap();
+        assert_eq!(reason, ExitReason::StopLoss);
+        assert!((pnl + 0.32).abs() < 1e-9);
+        assert!(exits.pnl_for(&fill("quote_1", 4.0, 0.42)).is_none());
+
+        exits.retain(|_| false);
+        assert!(exits.pnl_for(&fill("exit_1", 1.0, 0.42)).is_none());
+    }
+
+    #[test]
+    fn test_exit_style_prices() {
+        let styles = ExitStyles::default();
+        assert_eq!(styles.for_reason(ExitReason::StopLoss), ExitStyle::Marketable);
+        assert_eq!(styles.for_reason(ExitReason::TakeProfit), ExitStyle::Aggressive);
+
+        assert!((ExitStyle::Marketable.sell_price(0.45, 0.50) - 0.45).abs() < 1e-9);
+        assert!((ExitStyle::Aggressive.sell_price(0.45, 0.50) - 0.49).abs() < 1e-9);
+        // One-tick spread: join the ask rather than cross
+        assert!((ExitStyle::Aggressive.sell_price(0.49, 0.50) - 0.50).abs() < 1e-9);
+
+        let parsed: ExitStyles = serde_json::from_str(r#"{"take_profit":"marketable"}"#).unwrap();
+        assert_eq!(parsed.take_profit, ExitStyle::Marketable);
+        assert_eq!(parsed.stop_loss, ExitStyle::Marketable);
+    }
+}
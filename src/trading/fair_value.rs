@@ -0,0 +1,191 @@
+//! Fair value of up/down markets from an external spot price
+//! UP settles at $1 when spot ends the slot at or above its opening price, so P(UP) is a
+//! digital option on spot struck at the open
+
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::RwLock;
+use tracing::debug;
+
+use crate::api::discovery::SeriesMarket;
+use crate::config::{FairValueConfig, MarketsConfig};
+use crate::trading::price_history::{PriceHistory, PriceSeries, SECONDS_PER_YEAR};
+use crate::websocket::SpotTick;
+
+/// Opening prices older than this are forgotten
+const OPENING_RETENTION_SECS: i64 = 2 * 3600;
+/// First tick must land this close to the slot boundary to count as the opening price
+const OPEN_TOLERANCE_SECS: i64 = 5;
+/// Fewer one-second returns than this fall back to the configured volatility
+const MIN_RETURNS: usize = 30;
+/// Fair probabilities are kept off 0 and 1
+const MIN_PROBABILITY: f64 = 0.01;
+const MAX_PROBABILITY: f64 = 0.99;
+
+/// Spot at the slot boundary: the first tick at or after `slot_start`
+///
+/// None until that tick arrives, or if the feed was down at the boundary.
+pub fn opening_price(series: &PriceSeries, slot_start: i64) -> Option<f64> {
+    let start = DateTime::from_timestamp(slot_start, 0)?;
+    let first = series.first_at_or_after(start)?;
+    (first.timestamp - start <= Duration::seconds(OPEN_TOLERANCE_SECS)).then_some(first.price)
+}
+
+/// Standard normal CDF (Abramowitz-Stegun 7.1.26, error < 1.5e-7)
+pub fn norm_cdf(x: f64) -> f64 {
+    let z = x.abs() / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.327_591_1 * z);
+    let poly = t * (0.254_829_592 + t * (-0.284_496_736 + t * (1.421_413_741 + t * (-1.453_152_027 + t * 1.061_405_429))));
+    let erf = 1.0 - poly * (-z * z).exp();
+    if x >= 0.0 {
+        0.5 * (1.0 + erf)
+    } else {
+        0.5 * (1.0 - erf)
+    }
+}
+
+/// P(spot >= strike at expiry) under lognormal spot with zero drift: N(d2)
+pub fn fair_up_probability(spot: f64, strike: f64, volatility: f64, seconds_left: f64) -> f64 {
+    let tau = seconds_left.max(0.0) / SECONDS_PER_YEAR;
+    let sigma_t = volatility * tau.sqrt();
+    let probability = if sigma_t <= 0.0 {
+        if spot >= strike { 1.0 } else { 0.0 }
+    } else {
+        let d2 = ((spot / strike).ln() - 0.5 * sigma_t * sigma_t) / sigma_t;
+        norm_cdf(d2)
+    };
+    probability.clamp(MIN_PROBABILITY, MAX_PROBABILITY)
+}
+
+/// Inputs and result of one fair value computation
+#[derive(Debug, Clone, PartialEq)]
+pub struct FairValue {
+    pub spot: f64,
+    /// Opening price of the slot
+    pub strike: f64,
+    /// Annualized
+    pub volatility: f64,
+    pub seconds_left: f64,
+    /// Fair price of UP; DOWN is 1 - probability
+    pub probability: f64,
+}
+
+/// Fair value model shared by all market tasks
+pub struct FairValueModel {
+    /// Spot prices keyed by symbol
+    history: Arc<RwLock<PriceHistory>>,
+    /// (symbol, slot_start) -> opening price, kept once seen
+    openings: Mutex<HashMap<(String, i64), f64>>,
+    /// series slug -> spot symbol
+    symbols: HashMap<String, String>,
+    config: FairValueConfig,
+}
+
+impl FairValueModel {
+    /// Create model for the configured series, reading spot from the shared price history
+    pub fn new(config: FairValueConfig, markets: &MarketsConfig, history: Arc<RwLock<PriceHistory>>) -> Self {
+        let symbols = markets
+            .series
+            .iter()
+            .filter_map(|s| Some((s.slug.clone(), s.spot_symbol()?)))
+            .collect();
+        Self {
+            history,
+            openings: Mutex::new(HashMap::new()),
+            symbols,
+            config,
+        }
+    }
+
+    /// Spot symbols to subscribe, sorted and deduplicated
+    pub fn symbols(&self) -> Vec<String> {
+        let mut symbols: Vec<String> = self.symbols.values().cloned().collect();
+        symbols.sort_unstable();
+        symbols.dedup();
+        symbols
+    }
+
+    /// Record a spot tick
+    pub async fn record(&self, tick: SpotTick) {
+        self.history.write().await.record(&tick.symbol, tick.timestamp, tick.price);
+    }
+
+    /// Opening price of a slot, cached once seen
+    fn strike(&self, symbol: &str, series: &PriceSeries, slot_start: i64) -> Option<f64> {
+        let mut openings = self.openings.lock().unwrap_or_else(|e| e.into_inner());
+        let key = (symbol.to_string(), slot_start);
+        if let Some(price) = openings.get(&key) {
+            return Some(*price);
+        }
+        let price = opening_price(series, slot_start)?;
+        debug!("Opening price {} @ {}: {}", symbol, slot_start, price);
+        openings.retain(|(_, start), _| *start >= slot_start - OPENING_RETENTION_SECS);
+        openings.insert(key, price);
+        Some(price)
+    }
+
+    /// Fair value of a market's UP token; None without a fresh spot price or opening price
+    pub async fn fair_up(&self, market: &SeriesMarket, now: DateTime<Utc>) -> Option<FairValue> {
+        let symbol = self.symbols.get(&market.series)?;
+        let history = self.history.read().await;
+        let series = history.series(symbol)?;
+        let latest = *series.latest()?;
+        if now - latest.timestamp > Duration::seconds(self.config.max_staleness_secs as i64) {
+            debug!("Spot price for {} is stale ({})", symbol, latest.timestamp);
+            return None;
+        }
+        let strike = self.strike(symbol, series, market.slot_start)?;
+        let volatility = series
+            .close_to_close_volatility(Duration::seconds(1), Duration::seconds(self.config.vol_window_secs as i64))
+            .filter(|vol| vol.returns >= MIN_RETURNS)
+            .map(|vol| vol.annualized())
+            .unwrap_or(self.config.fallback_volatility);
+        let seconds_left = (market.end_time - now).num_milliseconds() as f64 / 1000.0;
+        Some(FairValue {
+            spot: latest.price,
+            strike,
+            volatility,
+            seconds_left,
+            probability: fair_up_probability(latest.price, strike, volatility, seconds_left),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(secs, 0).unwrap()
+    }
+
+    #[test]
+    fn test_fair_up_probability() {
+        assert!((norm_cdf(0.0) - 0.5).abs() < 1e-7);
+        assert!((norm_cdf(1.96) - 0.975).abs() < 1e-3);
+
+        // At the money: just under a coin flip (the -sigma^2/2 drift)
+        let atm = fair_up_probability(100_000.0, 100_000.0, 0.5, 300.0);
+        assert!(atm < 0.5 && atm > 0.49);
+        // Spot above the open is worth more the less time is left
+        let early = fair_up_probability(100_100.0, 100_000.0, 0.5, 300.0);
+        let late = fair_up_probability(100_100.0, 100_000.0, 0.5, 30.0);
+        assert!(early > 0.5 && late > early);
+        // Expired: the outcome is known, clamped off the edges
+        assert_eq!(fair_up_probability(99_000.0, 100_000.0, 0.5, 0.0), 0.01);
+    }
+
+    #[test]
+    fn test_opening_price() {
+        let mut series = PriceSeries::new(100);
+        series.push(at(998), 100.0);
+        series.push(at(1001), 101.0);
+        series.push(at(1002), 102.0);
+        assert_eq!(opening_price(&series, 1000), Some(101.0));
+        // Feed gap at the boundary: no strike
+        assert_eq!(opening_price(&series, 900), None);
+        // Boundary not reached yet
+        assert_eq!(opening_price(&series, 1100), None);
+    }
+}
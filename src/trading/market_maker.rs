@@ -3,6 +3,7 @@
 
 use crate::api::{ClobClient, Order, OrderResponse};
 use crate::api::Side;
+use crate::config::TradingConfig;
 use crate::trading::TradingError;
 
 /// Market maker configuration
@@ -32,6 +33,18 @@ impl Default for MarketMakerConfig {
     }
 }
 
+impl From<&TradingConfig> for MarketMakerConfig {
+    fn from(config: &TradingConfig) -> Self {
+        Self {
+            order_size: config.order_size,
+            max_position: config.max_position,
+            safe_range_low: config.safe_range_low,
+            safe_range_high: config.safe_range_high,
+            ..Self::default()
+        }
+    }
+}
+
 /// Market maker for a specific market
 pub struct MarketMaker {
     market_id: String,
@@ -100,8 +113,9 @@ impl MarketMaker {
     ) -> Result<Vec<Order>, TradingError> {
         self.validate_price(current_price)?;
         
-        let bid_price = (current_price - spread / 2.0).max(self.config.safe_range_low);
-        let ask_price = (current_price + spread / 2.0).min(self.config.safe_range_high);
+        // Prices on the 0.01 tick
+        let bid_price = (((current_price - spread / 2.0) * 100.0).round() / 100.0).max(self.config.safe_range_low);
+        let ask_price = (((current_price + spread / 2.0) * 100.0).round() / 100.0).min(self.config.safe_range_high);
         
         let orders = vec![
             Order {
@@ -0,0 +1,316 @@
+//! Fill markouts: how the mid moved after each of our fills
+//! Positive markouts mean the fill was good for us; persistently negative ones mean toxic flow
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use crate::trading::order_tracker::Fill;
+use crate::trading::price_history::PriceHistory;
+
+/// Timed horizons after a fill, in seconds, with their stats keys
+pub const MARKOUT_HORIZONS: [(i64, &str); 3] = [(1, "1s"), (5, "5s"), (30, "30s")];
+/// Stats key of the settlement markout
+pub const SETTLEMENT_HORIZON: &str = "settle";
+/// Horizon and sample count of the recent markout strategies can react to
+const RECENT_HORIZON: &str = "5s";
+const RECENT_MARKOUTS: usize = 20;
+
+/// Per-share markout of a fill against a later mid
+pub fn markout(side: &str, price: f64, mid: f64) -> f64 {
+    if side == "BUY" {
+        mid - price
+    } else {
+        price - mid
+    }
+}
+
+/// 0.1-wide price bucket label, e.g. "0.4-0.5"
+pub fn price_bucket(price: f64) -> String {
+    let low = (price * 10.0).floor().clamp(0.0, 9.0) / 10.0;
+    format!("{:.1}-{:.1}", low, low + 0.1)
+}
+
+/// One fill's markout at one horizon
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarkoutSample {
+    /// Series slug of the market
+    pub market: String,
+    pub side: String,
+    pub price: f64,
+    pub size: f64,
+    pub horizon: &'static str,
+    pub markout: f64,
+}
+
+/// Size-weighted markout accumulator
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MarkoutBucket {
+    pub fills: u64,
+    pub volume: f64,
+    /// Sum of markout times size
+    pub total: f64,
+}
+
+impl MarkoutBucket {
+    fn add(&mut self, markout: f64, size: f64) {
+        self.fills += 1;
+        self.volume += size;
+        self.total += markout * size;
+    }
+
+    /// Average markout per share
+    pub fn average(&self) -> Option<f64> {
+        (self.volume > 0.0).then(|| self.total / self.volume)
+    }
+}
+
+/// Horizon -> bucket
+pub type HorizonBuckets = BTreeMap<String, MarkoutBucket>;
+
+/// Markout statistics by market, side and price bucket
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MarkoutStats {
+    pub by_market: BTreeMap<String, HorizonBuckets>,
+    pub by_side: BTreeMap<String, HorizonBuckets>,
+    pub by_price: BTreeMap<String, HorizonBuckets>,
+    /// Latest short-horizon markouts per market
+    #[serde(skip)]
+    recent: HashMap<String, VecDeque<f64>>,
+}
+
+impl MarkoutStats {
+    /// Record one sample
+    pub fn record(&mut self, sample: &MarkoutSample) {
+        for (table, key) in [
+            (&mut self.by_market, sample.market.clone()),
+            (&mut self.by_side, sample.side.clone()),
+            (&mut self.by_price, price_bucket(sample.price)),
+        ] {
+            table
+                .entry(key)
+                .or_default()
+                .entry(sample.horizon.to_string())
+                .or_default()
+                .add(sample.markout, sample.size);
+        }
+        if sample.horizon == RECENT_HORIZON {
+            let recent = self.recent.entry(sample.market.clone()).or_default();
+            if recent.len() == RECENT_MARKOUTS {
+                recent.pop_front();
+            }
+            recent.push_back(sample.markout);
+        }
+    }
+
+    /// Average markout per share across all fills at a horizon
+    pub fn overall(&self, horizon: &str) -> Option<f64> {
+        let mut total = MarkoutBucket::default();
+        for bucket in self.by_side.values().filter_map(|h| h.get(horizon)) {
+            total.fills += bucket.fills;
+            total.volume += bucket.volume;
+            total.total += bucket.total;
+        }
+        total.average()
+    }
+
+    /// Mean 5s markout of a market's latest fills; negative means recent flow was toxic
+    pub fn recent(&self, market: &str) -> Option<f64> {
+        let recent = self.recent.get(market).filter(|r| !r.is_empty())?;
+        Some(recent.iter().sum::<f64>() / recent.len() as f64)
+    }
+
+    /// Overall markouts per horizon, then one line per market, side and price bucket
+    pub fn summary(&self) -> String {
+        let horizons = || {
+            MARKOUT_HORIZONS
+                .iter()
+                .map(|(_, label)| *label)
+                .chain(std::iter::once(SETTLEMENT_HORIZON))
+        };
+        let format = |average: Option<f64>| average.map_or("-".to_string(), |m| format!("{:+.4}", m));
+        let mut lines = vec![format!(
+            "📐 Markouts: {}",
+            horizons()
+                .map(|h| format!("{}={}", h, format(self.overall(h))))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )];
+        for (name, table) in [("market", &self.by_market), ("side", &self.by_side), ("price", &self.by_price)] {
+            for (key, buckets) in table {
+                let fills = buckets.values().map(|b| b.fills).max().unwrap_or(0);
+                lines.push(format!(
+                    "   {} {} ({} fills): {}",
+                    name,
+                    key,
+                    fills,
+                    horizons()
+                        .map(|h| format!("{}={}", h, format(buckets.get(h).and_then(MarkoutBucket::average))))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+/// A fill still waiting for some of its markouts
+#[derive(Debug, Clone)]
+struct PendingFill {
+    market: String,
+    token: String,
+    side: String,
+    price: f64,
+    size: f64,
+    filled_at: DateTime<Utc>,
+    /// Timed horizons already sampled
+    sampled: usize,
+}
+
+/// Fills awaiting their timed and settlement markouts
+#[derive(Debug, Default)]
+pub struct MarkoutTracker {
+    pending: Vec<PendingFill>,
+}
+
+impl MarkoutTracker {
+    /// Create empty tracker
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start tracking a fill of a market in `market` (series slug)
+    pub fn track(&mut self, market: &str, fill: &Fill, filled_at: DateTime<Utc>) {
+        self.pending.push(PendingFill {
+            market: market.to_string(),
+            token: fill.token.clone(),
+            side: fill.side.clone(),
+            price: fill.price,
+            size: fill.size,
+            filled_at,
+            sampled: 0,
+        });
+    }
+
+    /// Fills still pending
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Whether any fill of a token is still pending
+    pub fn is_tracking(&self, token: &str) -> bool {
+        self.pending.iter().any(|fill| fill.token == token)
+    }
+
+    /// Timed markouts that came due by `now`, read from the mid history
+    ///
+    /// The mid at a horizon is the last one recorded at or before it; a horizon with no
+    /// mid recorded at all is skipped.
+    pub fn sample(&mut self, history: &PriceHistory, now: DateTime<Utc>) -> Vec<MarkoutSample> {
+        let mut samples = Vec::new();
+        for fill in &mut self.pending {
+            while let Some((secs, label)) = MARKOUT_HORIZONS.get(fill.sampled) {
+                let at = fill.filled_at + Duration::seconds(*secs);
+                if at > now {
+                    break;
+                }
+                fill.sampled += 1;
+                if let Some(point) = history.series(&fill.token).and_then(|series| series.price_at(at)) {
+                    samples.push(fill.sample(label, point.price));
+                }
+            }
+        }
+        samples
+    }
+
+    /// Settlement markouts of a token's fills, which stop being tracked
+    pub fn settle(&mut self, token: &str, settlement_price: f64) -> Vec<MarkoutSample> {
+        let (settled, pending) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition::<Vec<_>, _>(|fill| fill.token == token);
+        self.pending = pending;
+        settled
+            .iter()
+            .map(|fill| fill.sample(SETTLEMENT_HORIZON, settlement_price))
+            .collect()
+    }
+
+    /// Stop tracking a token whose settlement will not be observed
+    pub fn forget(&mut self, token: &str) {
+        self.pending.retain(|fill| fill.token != token);
+    }
+}
+
+impl PendingFill {
+    fn sample(&self, horizon: &'static str, mid: f64) -> MarkoutSample {
+        MarkoutSample {
+            market: self.market.clone(),
+            side: self.side.clone(),
+            price: self.price,
+            size: self.size,
+            horizon,
+            markout: markout(&self.side, self.price, mid),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(millis: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp_millis(millis).unwrap()
+    }
+
+    fn fill(side: &str, price: f64, size: f64) -> Fill {
+        Fill {
+            order_id: "o1".to_string(),
+            token: "up".to_string(),
+            side: side.to_string(),
+            size,
+            price,
+            trade_id: None,
+        }
+    }
+
+    #[test]
+    fn test_markouts_sampled_from_history() {
+        let mut history = PriceHistory::default();
+        let mut tracker = MarkoutTracker::new();
+        history.record("up", at(0), 0.50);
+        tracker.track("btc-updown-5m", &fill("BUY", 0.49, 10.0), at(100));
+        tracker.track("btc-updown-5m", &fill("SELL", 0.51, 5.0), at(100));
+        history.record("up", at(800), 0.48);
+        history.record("up", at(4_000), 0.45);
+
+        // +1s: mid 0.48 -> BUY -0.01, SELL +0.03
+        let samples = tracker.sample(&history, at(1_500));
+        assert_eq!(samples.len(), 2);
+        assert!((samples[0].markout + 0.01).abs() < 1e-12);
+        assert!((samples[1].markout - 0.03).abs() < 1e-12);
+        assert_eq!(samples[0].horizon, "1s");
+        // Nothing new until +5s
+        assert!(tracker.sample(&history, at(2_000)).is_empty());
+        assert_eq!(tracker.sample(&history, at(40_000)).len(), 4);
+
+        // Settlement at 0: BUY lost its price, SELL kept it
+        let settled = tracker.settle("up", 0.0);
+        assert_eq!(settled.len(), 2);
+        assert!((settled[0].markout + 0.49).abs() < 1e-12);
+        assert_eq!(tracker.pending(), 0);
+        assert!(!tracker.is_tracking("up"));
+
+        let mut stats = MarkoutStats::default();
+        for sample in samples.iter().chain(&settled) {
+            stats.record(sample);
+        }
+        // Size-weighted: (10 * -0.01 + 5 * 0.03) / 15
+        assert!((stats.overall("1s").unwrap() - 0.05 / 15.0).abs() < 1e-12);
+        assert_eq!(stats.by_price["0.4-0.5"]["settle"].fills, 1);
+        assert_eq!(stats.by_side["SELL"]["1s"].fills, 1);
+        assert!(stats.recent("btc-updown-5m").is_none());
+        assert!(stats.summary().contains("market btc-updown-5m (2 fills)"));
+    }
+}
@@ -14,18 +14,37 @@ pub mod price_warning;
 pub mod errors;
 pub mod exit_manager;
 pub mod callbacks;
+pub mod engine;
+pub mod rollover;
+pub mod strategy;
+pub mod arbitrage;
+pub mod fair_value;
+pub mod price_history;
+pub mod avellaneda;
+pub mod markout;
 
 pub use market_maker::{MarketMaker, MarketMakerConfig};
 pub use position::{PositionTracker, Position, PositionEntry, InventoryStatus, Action, BalanceAdjustment};
-pub use executor::TradeExecutor;
-pub use orderbook::{OrderBookDepth, OrderBookLevel, analyze_order_book_depth_safe, calculate_mm_prices};
-pub use order_tracker::{OrderTracker, ActiveOrder, FillStatus, wait_for_fill};
+pub use executor::{TradeExecutor, ImmediateFill};
+pub use orderbook::{OrderBookDepth, OrderBookLevel, L2OrderBook, BookSide, analyze_order_book_depth_safe, calculate_mm_prices};
+pub use order_tracker::{
+    OrderTracker, ActiveOrder, OrderState, OrderEvent, FillStatus, Fill, OrderFillUpdate, TradeFill,
+    wait_for_fill, reconcile_order_fills, apply_user_event, sync_order_stats,
+};
 pub use trade_history::{TradeHistory, TradeRecord};
 pub use stats::{TradingStats, PriceFreshness};
 pub use balance::{AccountBalance, PositionInfo, get_usdc_balance, get_positions, get_total_position_size};
 pub use simulation::{TradingMode, SimulationRecorder, SimulatedTrade};
 pub use price_warning::PriceWarningTracker;
 pub use errors::{TradingError, classify_error};
-pub use exit_manager::{ExitManager, PositionExitTracker, TrackedPosition, ExitCheck};
+pub use exit_manager::{ExitManager, ExitOrders, PositionExitTracker, TrackedPosition, ExitCheck, ExitReason, ExitStyle, ExitStyles};
 pub use callbacks::{CallbackManager, OrderInfo};
+pub use engine::{MarketEngine, MarketContext, PositionBudget};
+pub use rollover::RolloverScheduler;
+pub use strategy::{Strategy, StrategyRegistry, MarketSnapshot, TokenSnapshot, DesiredQuote, Outcome};
+pub use arbitrage::{ArbContext, ArbKind, ArbOpportunity, ArbOutcome, LegSender, detect_arbitrage, execute_arbitrage};
+pub use fair_value::{FairValue, FairValueModel, fair_up_probability};
+pub use avellaneda::{QuoteModel, AvellanedaStoikovConfig, StoikovQuote};
+pub use markout::{MarkoutStats, MarkoutTracker, MarkoutSample, MarkoutBucket};
+pub use price_history::{PriceHistory, PriceSeries, PricePoint, Candle, RealizedVol};
 
@@ -1,11 +1,53 @@
 //! Active order tracking
 //! Matches Python: _active_orders, _track_order, _wait_for_fill
 
-use std::collections::HashMap;
+use super::errors::TradingError;
+use super::executor::TradeExecutor;
+use super::position::PositionTracker;
+use super::stats::TradingStats;
+use crate::api::Side;
+use crate::websocket::user::{UserEvent, UserOrderEventType};
+use std::collections::{HashMap, VecDeque};
 use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
 use tokio::time::sleep;
 use tracing::{info, warn, debug};
 
+/// Order lifecycle state
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderState {
+    /// Sent to the CLOB, no order ID yet
+    PendingSubmit,
+    Live,
+    PartiallyFilled,
+    Filled,
+    Cancelled,
+    Rejected,
+    Expired,
+}
+
+impl OrderState {
+    /// Terminal states never change again
+    pub fn is_terminal(&self) -> bool {
+        matches!(self,
+            OrderState::Filled |
+            OrderState::Cancelled |
+            OrderState::Rejected |
+            OrderState::Expired
+        )
+    }
+
+    /// Check whether moving to `next` is allowed
+    pub fn can_transition_to(&self, next: OrderState) -> bool {
+        use OrderState::*;
+        matches!((self, next),
+            (PendingSubmit, Live | PartiallyFilled | Filled | Rejected) |
+            (Live, PartiallyFilled | Filled | Cancelled | Expired) |
+            (PartiallyFilled, PartiallyFilled | Filled | Cancelled | Expired)
+        )
+    }
+}
+
 /// Active order information
 #[derive(Debug, Clone)]
 pub struct ActiveOrder {
@@ -15,11 +57,92 @@ pub struct ActiveOrder {
     pub price: f64,
     pub size: f64,
     pub timestamp: Instant,
+    pub state: OrderState,
+    /// Cumulative size booked into positions
+    pub filled_size: f64,
+    /// Trade IDs already booked into positions
+    pub trade_ids: Vec<String>,
+    /// Part of `filled_size` booked before its trade was seen (at the limit or reported average price)
+    pub unattributed_filled: f64,
+    /// Size times booked price of `unattributed_filled`
+    pub unattributed_notional: f64,
+    /// Size each seen trade accounts for in `filled_size`, with the average price it was booked at
+    pub trade_fills: HashMap<String, TradeFill>,
+    /// A cancel was sent; waiting for the CLOB to confirm
+    pub cancel_requested: bool,
+}
+
+/// State change of a tracked order
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderEvent {
+    pub order_id: String,
+    pub token: String,
+    /// None when the order was first tracked
+    pub from: Option<OrderState>,
+    pub to: OrderState,
+    pub size: f64,
+    pub filled_size: f64,
+}
+
+/// One execution of our order, taken from the CLOB trades endpoint
+#[derive(Debug, Clone, PartialEq)]
+pub struct TradeFill {
+    pub trade_id: String,
+    pub size: f64,
+    pub price: f64,
 }
 
-/// Order tracker for managing active orders
+/// Order state as reported by the CLOB
+#[derive(Debug, Clone)]
+pub struct OrderFillUpdate {
+    pub order_id: String,
+    pub state: OrderState,
+    pub original_size: f64,
+    /// Cumulative matched size (authoritative quantity)
+    pub size_matched: f64,
+    /// Executions of this order found so far
+    pub trades: Vec<TradeFill>,
+}
+
+/// Newly matched quantity that has not been booked yet
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fill {
+    pub order_id: String,
+    pub token: String,
+    pub side: String,
+    pub size: f64,
+    pub price: f64,
+    /// None when the trade has not shown up on the trades endpoint yet
+    pub trade_id: Option<String>,
+}
+
+/// Sizes below this are treated as rounding noise
+const FILL_EPSILON: f64 = 1e-9;
+
+/// Terminal orders kept for inspection
+const MAX_HISTORY: usize = 500;
+
+/// User channel trades for unknown orders are held this long in case the
+/// order is still waiting for its submit response
+const UNMATCHED_TRADE_TTL: Duration = Duration::from_secs(60);
+
+/// Trade seen before the order it belongs to was confirmed
+#[derive(Debug, Clone)]
+struct UnmatchedTrade {
+    order_id: String,
+    trade: TradeFill,
+    received: Instant,
+}
+
+/// Order tracker for managing the order lifecycle
 pub struct OrderTracker {
-    orders: HashMap<String, ActiveOrder>, // token -> order
+    orders: HashMap<String, ActiveOrder>, // order_id -> non-terminal order
+    history: VecDeque<ActiveOrder>,
+    events: Vec<OrderEvent>,
+    /// Trades for orders still in PendingSubmit under their local ID
+    unmatched_trades: Vec<UnmatchedTrade>,
+    /// Fills from buffered trades, booked by the next reconcile or user event
+    replayed_fills: Vec<Fill>,
 }
 
 impl OrderTracker {
@@ -27,10 +150,14 @@ impl OrderTracker {
     pub fn new() -> Self {
         Self {
             orders: HashMap::new(),
+            history: VecDeque::new(),
+            events: Vec::new(),
+            unmatched_trades: Vec::new(),
+            replayed_fills: Vec::new(),
         }
     }
 
-    /// Track a new order
+    /// Track a new order that is already live on the CLOB
     /// Matches Python: _track_order()
     pub fn track_order(
         &mut self,
@@ -40,42 +167,155 @@ impl OrderTracker {
         price: f64,
         size: f64,
     ) {
-        let order = ActiveOrder {
-            order_id: order_id.clone(),
-            token: token.clone(),
-            side,
-            price,
-            size,
-            timestamp: Instant::now(),
-        };
-
         info!("📋 Tracking order: {} for token {}", order_id, token);
-        self.orders.insert(token, order);
+        self.insert(order_id, token, side, price, size, OrderState::Live);
+    }
+
+    /// Track an order before submission; returns a local ID
+    pub fn begin_submit(
+        &mut self,
+        token: String,
+        side: String,
+        price: f64,
+        size: f64,
+    ) -> String {
+        let local_id = format!("pending_{}", uuid::Uuid::new_v4());
+        self.insert(local_id.clone(), token, side, price, size, OrderState::PendingSubmit);
+        local_id
+    }
+
+    /// The CLOB accepted a pending order
+    /// Trades that arrived for it in the meantime are applied and queued for booking.
+    pub fn confirm_submit(
+        &mut self,
+        local_id: &str,
+        order_id: String,
+    ) -> Result<(), TradingError> {
+        let mut order = self.orders.remove(local_id).ok_or_else(|| TradingError::OrderNotFound {
+            order_id: local_id.to_string(),
+        })?;
+        info!("📋 Tracking order: {} for token {}", order_id, order.token);
+        order.order_id = order_id.clone();
+        self.orders.insert(order_id.clone(), order);
+        self.transition(&order_id, OrderState::Live)?;
+
+        let (early, rest): (Vec<_>, Vec<_>) = std::mem::take(&mut self.unmatched_trades)
+            .into_iter()
+            .partition(|t| t.order_id == order_id);
+        self.unmatched_trades = rest;
+        for unmatched in early {
+            if let Some(fill) = self.apply_trade(&order_id, &unmatched.trade) {
+                self.replayed_fills.push(fill);
+            }
+        }
+        Ok(())
+    }
+
+    /// The CLOB refused a pending order
+    pub fn reject_submit(
+        &mut self,
+        local_id: &str,
+    ) -> Result<(), TradingError> {
+        self.transition(local_id, OrderState::Rejected)
+    }
+
+    /// Move an order to a new state
+    /// Terminal orders leave the active set and go to history.
+    pub fn transition(
+        &mut self,
+        order_id: &str,
+        next: OrderState,
+    ) -> Result<(), TradingError> {
+        let order = self.orders.get_mut(order_id).ok_or_else(|| TradingError::OrderNotFound {
+            order_id: order_id.to_string(),
+        })?;
+
+        let from = order.state;
+        if from == next && next != OrderState::PartiallyFilled {
+            return Ok(());
+        }
+        if !from.can_transition_to(next) {
+            return Err(TradingError::InvalidOrderTransition {
+                order_id: order_id.to_string(),
+                from: format!("{:?}", from),
+                to: format!("{:?}", next),
+            });
+        }
+
+        order.state = next;
+        self.events.push(OrderEvent {
+            order_id: order.order_id.clone(),
+            token: order.token.clone(),
+            from: Some(from),
+            to: next,
+            size: order.size,
+            filled_size: order.filled_size,
+        });
+        debug!("Order {} {:?} -> {:?}", order_id, from, next);
+
+        if next.is_terminal() {
+            if let Some(order) = self.orders.remove(order_id) {
+                self.history.push_back(order);
+                while self.history.len() > MAX_HISTORY {
+                    self.history.pop_front();
+                }
+            }
+        }
+        Ok(())
     }
 
-    /// Get active order for a token
+    /// Get the most recent active order for a token
     pub fn get_order(&self,
         token: &str,
     ) -> Option<&ActiveOrder> {
-        self.orders.get(token)
+        self.orders
+            .values()
+            .filter(|o| o.token == token)
+            .max_by_key(|o| o.timestamp)
+    }
+
+    /// Get an active order by ID
+    pub fn get_by_id(&self,
+        order_id: &str,
+    ) -> Option<&ActiveOrder> {
+        self.orders.get(order_id)
     }
 
-    /// Remove order from tracking
+    /// Stop tracking active orders for a token without recording an outcome
     pub fn remove_order(
         &mut self,
         token: &str,
     ) {
-        if self.orders.remove(token).is_some() {
+        let before = self.orders.len();
+        self.orders.retain(|_, o| o.token != token);
+        if self.orders.len() < before {
             debug!("Removed order tracking for token {}", token);
         }
     }
 
-    /// Get all active orders
+    /// Get all active orders, keyed by order ID
     pub fn get_all_orders(&self,
     ) -> &HashMap<String, ActiveOrder> {
         &self.orders
     }
 
+    /// IDs of active orders for a token that have a CLOB order ID
+    pub fn live_order_ids(&self,
+        token: &str,
+    ) -> Vec<String> {
+        self.orders
+            .values()
+            .filter(|o| o.token == token && o.state != OrderState::PendingSubmit)
+            .map(|o| o.order_id.clone())
+            .collect()
+    }
+
+    /// Terminal orders, oldest first
+    pub fn history(&self,
+    ) -> &VecDeque<ActiveOrder> {
+        &self.history
+    }
+
     /// Find old orders (> threshold seconds)
     /// Matches Python: _cancel_old_pending_orders()
     pub fn find_old_orders(
@@ -92,6 +332,239 @@ impl OrderTracker {
             .collect()
     }
 
+    /// Flag live orders for a token as cancel-requested
+    pub fn request_cancel_for_token(
+        &mut self,
+        token: &str,
+    ) -> usize {
+        let mut flagged = 0;
+        for order in self.orders.values_mut() {
+            if order.token == token && order.state != OrderState::PendingSubmit {
+                order.cancel_requested = true;
+                flagged += 1;
+            }
+        }
+        flagged
+    }
+
+    /// Confirm cancellation of every cancel-requested order for a token
+    /// Used when there is no CLOB to ask (simulation).
+    pub fn confirm_requested_cancels(
+        &mut self,
+        token: &str,
+    ) -> usize {
+        let ids: Vec<String> = self.orders
+            .values()
+            .filter(|o| o.token == token && o.cancel_requested)
+            .map(|o| o.order_id.clone())
+            .collect();
+        ids.iter()
+            .filter(|id| self.transition(id, OrderState::Cancelled).is_ok())
+            .count()
+    }
+
+    /// Book the unbooked part of a CLOB update and advance the order state
+    /// Quantity comes from `size_matched`; prices and trade IDs come from
+    /// trades not booked before. Any matched size without a visible trade
+    /// is booked at the limit price. Calling this again with the same
+    /// update returns nothing.
+    pub fn apply_fill_update(
+        &mut self,
+        update: &OrderFillUpdate,
+    ) -> Vec<Fill> {
+        let order = match self.orders.get_mut(&update.order_id) {
+            Some(order) => order,
+            None => return vec![],
+        };
+
+        let mut remaining = update.size_matched - order.filled_size;
+        let mut fills = Vec::new();
+
+        for trade in &update.trades {
+            if order.trade_ids.contains(&trade.trade_id) {
+                continue;
+            }
+            let size = order.attribute(trade, remaining);
+            if size <= FILL_EPSILON {
+                continue;
+            }
+            remaining -= size;
+            fills.push(Fill {
+                order_id: order.order_id.clone(),
+                token: order.token.clone(),
+                side: order.side.clone(),
+                size,
+                price: trade.price,
+                trade_id: Some(trade.trade_id.clone()),
+            });
+        }
+
+        if remaining > FILL_EPSILON {
+            order.unattributed_filled += remaining;
+            order.unattributed_notional += remaining * order.price;
+            fills.push(Fill {
+                order_id: order.order_id.clone(),
+                token: order.token.clone(),
+                side: order.side.clone(),
+                size: remaining,
+                price: order.price,
+                trade_id: None,
+            });
+        }
+
+        if update.size_matched > order.filled_size {
+            order.filled_size = update.size_matched;
+        }
+
+        if !fills.is_empty() {
+            info!("🎯 Order {} matched {:.2}/{:.2} ({} new fill(s))",
+                order.order_id, update.size_matched, update.original_size, fills.len());
+        }
+
+        let next = if update.state == OrderState::Live && order.filled_size > FILL_EPSILON {
+            OrderState::PartiallyFilled
+        } else {
+            update.state
+        };
+        // Only report a repeated partial fill when something new matched
+        let changed = next != order.state || (next == OrderState::PartiallyFilled && !fills.is_empty());
+        if changed {
+            if let Err(e) = self.transition(&update.order_id, next) {
+                warn!("Ignoring CLOB state for order {}: {}", update.order_id, e);
+            }
+        }
+        fills
+    }
+
+    /// Book what an immediate (FOK/FAK) order matched on arrival
+    /// The submit response has the size and average price but no trade IDs,
+    /// so the size counts as unattributed and the order's trades arriving
+    /// later book nothing new. The unmatched rest was killed, so the order
+    /// ends here.
+    pub fn apply_immediate_fill(
+        &mut self,
+        order_id: &str,
+        filled_size: f64,
+        avg_price: f64,
+    ) -> Option<Fill> {
+        let order = self.orders.get_mut(order_id)?;
+        let size = filled_size - order.filled_size;
+        let fill = (size > FILL_EPSILON).then(|| {
+            order.unattributed_filled += size;
+            order.unattributed_notional += size * avg_price;
+            order.filled_size = filled_size;
+            Fill {
+                order_id: order.order_id.clone(),
+                token: order.token.clone(),
+                side: order.side.clone(),
+                size,
+                price: avg_price,
+                trade_id: None,
+            }
+        });
+        let next = if order.filled_size >= order.size - FILL_EPSILON {
+            OrderState::Filled
+        } else {
+            OrderState::Cancelled
+        };
+        if let Err(e) = self.transition(order_id, next) {
+            warn!("Ignoring immediate fill state for order {}: {}", order_id, e);
+        }
+        fill
+    }
+
+    /// Book a single execution pushed by the user channel
+    /// Late executions of orders already in history are booked too. Trades
+    /// for unknown orders are held while an order is awaiting its ID.
+    pub fn apply_trade(
+        &mut self,
+        order_id: &str,
+        trade: &TradeFill,
+    ) -> Option<Fill> {
+        let active = self.orders.contains_key(order_id);
+        let order = if active {
+            self.orders.get_mut(order_id)
+        } else {
+            self.history.iter_mut().rev().find(|o| o.order_id == order_id)
+        };
+        let Some(order) = order else {
+            self.hold_unmatched_trade(order_id, trade);
+            return None;
+        };
+
+        if order.trade_ids.contains(&trade.trade_id) {
+            return None;
+        }
+        let size = order.attribute(trade, order.size - order.filled_size);
+        if size <= FILL_EPSILON {
+            return None;
+        }
+        order.filled_size += size;
+
+        let fill = Fill {
+            order_id: order.order_id.clone(),
+            token: order.token.clone(),
+            side: order.side.clone(),
+            size,
+            price: trade.price,
+            trade_id: Some(trade.trade_id.clone()),
+        };
+
+        if active {
+            let next = if order.filled_size >= order.size - FILL_EPSILON {
+                OrderState::Filled
+            } else {
+                OrderState::PartiallyFilled
+            };
+            if let Err(e) = self.transition(order_id, next) {
+                warn!("Ignoring user channel state for order {}: {}", order_id, e);
+            }
+        }
+        Some(fill)
+    }
+
+    /// Undo a trade the CLOB reported as FAILED after it matched
+    /// Returns the booked size to reverse, if the trade had been booked.
+    pub fn fail_trade(
+        &mut self,
+        order_id: &str,
+        trade_id: &str,
+    ) -> Option<Fill> {
+        self.unmatched_trades.retain(|t| !(t.order_id == order_id && t.trade.trade_id == trade_id));
+        let order = match self.orders.get_mut(order_id) {
+            Some(order) => order,
+            None => self.history.iter_mut().rev().find(|o| o.order_id == order_id)?,
+        };
+        let booked = order.trade_fills.remove(trade_id)?;
+        order.trade_ids.retain(|id| id != trade_id);
+        order.filled_size = (order.filled_size - booked.size).max(0.0);
+        if booked.size <= FILL_EPSILON {
+            return None;
+        }
+        warn!("↩️ Trade {} of order {} failed, reversing {:.2} @ {:.4}",
+            trade_id, order.order_id, booked.size, booked.price);
+        Some(Fill {
+            order_id: order.order_id.clone(),
+            token: order.token.clone(),
+            side: order.side.clone(),
+            size: booked.size,
+            price: booked.price,
+            trade_id: Some(booked.trade_id),
+        })
+    }
+
+    /// Take fills of trades that arrived before their order was confirmed
+    pub fn take_replayed_fills(&mut self,
+    ) -> Vec<Fill> {
+        std::mem::take(&mut self.replayed_fills)
+    }
+
+    /// Take the state changes recorded since the last call
+    pub fn take_events(&mut self,
+    ) -> Vec<OrderEvent> {
+        std::mem::take(&mut self.events)
+    }
+
     /// Clear all tracked orders for a token
     /// Matches Python: _cancel_all_tracked_for_token()
     pub fn clear_orders_for_token(
@@ -113,10 +586,102 @@ impl OrderTracker {
         self.orders.clear();
     }
 
-    /// Get order count
+    /// Get active order count
     pub fn count(&self) -> usize {
         self.orders.len()
     }
+
+    fn insert(
+        &mut self,
+        order_id: String,
+        token: String,
+        side: String,
+        price: f64,
+        size: f64,
+        state: OrderState,
+    ) {
+        let order = ActiveOrder {
+            order_id: order_id.clone(),
+            token: token.clone(),
+            side,
+            price,
+            size,
+            timestamp: Instant::now(),
+            state,
+            filled_size: 0.0,
+            trade_ids: Vec::new(),
+            unattributed_filled: 0.0,
+            unattributed_notional: 0.0,
+            trade_fills: HashMap::new(),
+            cancel_requested: false,
+        };
+        self.events.push(OrderEvent {
+            order_id: order_id.clone(),
+            token,
+            from: None,
+            to: state,
+            size,
+            filled_size: 0.0,
+        });
+        self.orders.insert(order_id, order);
+    }
+
+    /// Hold a trade for an unknown order while any submit is unconfirmed
+    fn hold_unmatched_trade(
+        &mut self,
+        order_id: &str,
+        trade: &TradeFill,
+    ) {
+        self.unmatched_trades.retain(|t| t.received.elapsed() < UNMATCHED_TRADE_TTL);
+        let pending = self.orders.values().any(|o| o.state == OrderState::PendingSubmit);
+        let held = self.unmatched_trades.iter()
+            .any(|t| t.order_id == order_id && t.trade.trade_id == trade.trade_id);
+        if pending && !held {
+            debug!("Holding trade {} for unconfirmed order {}", trade.trade_id, order_id);
+            self.unmatched_trades.push(UnmatchedTrade {
+                order_id: order_id.to_string(),
+                trade: trade.clone(),
+                received: Instant::now(),
+            });
+        }
+    }
+}
+
+impl ActiveOrder {
+    /// Record a newly seen trade and return the size still to book
+    /// Quantity already booked at the limit price without a trade ID is
+    /// used up first, so REST and user channel bookings of the same
+    /// execution count once. At most `unbooked` is returned. The trade is
+    /// remembered at the price its size was booked at, so a reversal undoes
+    /// exactly what was booked.
+    fn attribute(
+        &mut self,
+        trade: &TradeFill,
+        unbooked: f64,
+    ) -> f64 {
+        self.trade_ids.push(trade.trade_id.clone());
+        let covered = trade.size.min(self.unattributed_filled);
+        let covered_notional = if self.unattributed_filled > FILL_EPSILON {
+            self.unattributed_notional * covered / self.unattributed_filled
+        } else {
+            0.0
+        };
+        self.unattributed_filled -= covered;
+        self.unattributed_notional -= covered_notional;
+        let size = (trade.size - covered).min(unbooked).max(0.0);
+        let booked = covered + size;
+        let price = if booked > FILL_EPSILON {
+            (covered_notional + size * trade.price) / booked
+        } else {
+            trade.price
+        };
+        self.trade_fills.insert(trade.trade_id.clone(), TradeFill {
+            size: booked,
+            price,
+            ..trade.clone()
+        });
+        size
+    }
 }
 
 impl Default for OrderTracker {
@@ -176,6 +741,160 @@ where
     0.0
 }
 
+/// Reconcile tracked orders for a token with the CLOB
+/// New fills go into positions and stats; state changes go into stats.
+pub async fn reconcile_order_fills(
+    executor: &TradeExecutor,
+    order_tracker: &RwLock<OrderTracker>,
+    position_tracker: &RwLock<PositionTracker>,
+    stats: &RwLock<TradingStats>,
+    token_id: &str,
+) -> Vec<Fill> {
+    if executor.is_simulation_mode() {
+        // Simulated orders never fill; a cancel is final
+        order_tracker.write().await.confirm_requested_cancels(token_id);
+        sync_order_stats(order_tracker, stats).await;
+        return vec![];
+    }
+
+    let mut booked = order_tracker.write().await.take_replayed_fills();
+    for fill in &booked {
+        book_fill(position_tracker, stats, fill).await;
+    }
+
+    let tracked = order_tracker.read().await.live_order_ids(token_id);
+    if tracked.is_empty() {
+        return booked;
+    }
+
+    let updates = match executor.get_order_fills(token_id, &tracked).await {
+        Ok(updates) => updates,
+        Err(e) => {
+            warn!("⚠️ Failed to check fills for {}: {}", token_id, e);
+            return booked;
+        }
+    };
+
+    for update in &updates {
+        let fills = order_tracker.write().await.apply_fill_update(update);
+        for fill in fills {
+            book_fill(position_tracker, stats, &fill).await;
+            booked.push(fill);
+        }
+    }
+
+    sync_order_stats(order_tracker, stats).await;
+    booked
+}
+
+/// Apply an order or trade event from the user channel
+pub async fn apply_user_event(
+    event: &UserEvent,
+    order_tracker: &RwLock<OrderTracker>,
+    position_tracker: &RwLock<PositionTracker>,
+    stats: &RwLock<TradingStats>,
+) -> Vec<Fill> {
+    let mut fills = Vec::new();
+    let mut reversals = Vec::new();
+    {
+        let mut tracker = order_tracker.write().await;
+        fills.extend(tracker.take_replayed_fills());
+        match event {
+            UserEvent::Trade(trade) if trade.is_failed() => {
+                let order_ids = std::iter::once(&trade.taker_order_id)
+                    .chain(trade.maker_orders.iter().map(|m| &m.order_id));
+                for order_id in order_ids {
+                    reversals.extend(tracker.fail_trade(order_id, &trade.trade_id));
+                }
+            }
+            UserEvent::Trade(trade) => {
+                // One trade can match several of our orders
+                let order_ids = std::iter::once(&trade.taker_order_id)
+                    .chain(trade.maker_orders.iter().map(|m| &m.order_id));
+                for order_id in order_ids {
+                    if let Some(fill) = trade.fill_for_order(order_id)
+                        .and_then(|t| tracker.apply_trade(order_id, &t))
+                    {
+                        fills.push(fill);
+                    }
+                }
+            }
+            UserEvent::Order(order) if order.event_type == UserOrderEventType::Cancellation => {
+                if tracker.get_by_id(&order.order_id).is_some() {
+                    if let Err(e) = tracker.transition(&order.order_id, OrderState::Cancelled) {
+                        warn!("Ignoring cancellation for order {}: {}", order.order_id, e);
+                    }
+                }
+            }
+            UserEvent::Order(order) => {
+                debug!("User order event {:?} for {}", order.event_type, order.order_id);
+            }
+        }
+    }
+
+    for fill in &fills {
+        book_fill(position_tracker, stats, fill).await;
+    }
+    for fill in &reversals {
+        unbook_fill(position_tracker, stats, fill).await;
+    }
+    sync_order_stats(order_tracker, stats).await;
+    fills
+}
+
+/// Book one fill into positions and stats
+pub(crate) async fn book_fill(
+    position_tracker: &RwLock<PositionTracker>,
+    stats: &RwLock<TradingStats>,
+    fill: &Fill,
+) {
+    let side = if fill.side == "BUY" { Side::Buy } else { Side::Sell };
+    info!("📈 Updating position for order {}: {:?} {:.2} @ {:.4} (trade {})",
+        fill.order_id, side, fill.size, fill.price,
+        fill.trade_id.as_deref().unwrap_or("pending"));
+
+    position_tracker.write().await.update_position_with_trade(
+        &fill.token,
+        side,
+        fill.size,
+        fill.price,
+        fill.trade_id.as_deref(),
+    ).await;
+    stats.write().await.record_fill(fill.size, fill.price);
+}
+
+/// Reverse a booked fill whose trade failed on chain
+async fn unbook_fill(
+    position_tracker: &RwLock<PositionTracker>,
+    stats: &RwLock<TradingStats>,
+    fill: &Fill,
+) {
+    let side = if fill.side == "BUY" { Side::Sell } else { Side::Buy };
+    position_tracker.write().await.update_position_with_trade(
+        &fill.token,
+        side,
+        fill.size,
+        fill.price,
+        fill.trade_id.as_deref(),
+    ).await;
+    stats.write().await.reverse_fill(fill.size, fill.price);
+}
+
+/// Feed order state changes into stats
+pub async fn sync_order_stats(
+    order_tracker: &RwLock<OrderTracker>,
+    stats: &RwLock<TradingStats>,
+) {
+    let events = order_tracker.write().await.take_events();
+    if events.is_empty() {
+        return;
+    }
+    let mut stats = stats.write().await;
+    for event in &events {
+        stats.record_order_event(event);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -219,6 +938,331 @@ mod tests {
         // Note: Can't easily test time-based filtering in unit tests
     }
 
+    #[test]
+    fn test_apply_fill_update_partial_then_full() {
+        let mut tracker = OrderTracker::new();
+        tracker.track_order(
+            "token_1".to_string(),
+            "order_123".to_string(),
+            "BUY".to_string(),
+            0.5,
+            10.0,
+        );
+
+        let partial = OrderFillUpdate {
+            order_id: "order_123".to_string(),
+            state: OrderState::Live,
+            original_size: 10.0,
+            size_matched: 4.0,
+            trades: vec![TradeFill { trade_id: "t1".to_string(), size: 4.0, price: 0.48 }],
+        };
+        let fills = tracker.apply_fill_update(&partial);
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].size, 4.0);
+        assert_eq!(fills[0].price, 0.48);
+        assert_eq!(fills[0].trade_id.as_deref(), Some("t1"));
+        assert_eq!(tracker.get_by_id("order_123").unwrap().state, OrderState::PartiallyFilled);
+
+        // Same update again books nothing
+        assert!(tracker.apply_fill_update(&partial).is_empty());
+
+        // Rest matched, trade not visible yet: booked at limit price
+        let more = OrderFillUpdate {
+            size_matched: 10.0,
+            ..partial.clone()
+        };
+        let fills = tracker.apply_fill_update(&more);
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].size, 6.0);
+        assert_eq!(fills[0].price, 0.5);
+        assert_eq!(fills[0].trade_id, None);
+
+        // Late trade is recorded but not booked twice
+        let full = OrderFillUpdate {
+            state: OrderState::Filled,
+            trades: vec![
+                TradeFill { trade_id: "t1".to_string(), size: 4.0, price: 0.48 },
+                TradeFill { trade_id: "t2".to_string(), size: 6.0, price: 0.5 },
+            ],
+            ..more
+        };
+        assert!(tracker.apply_fill_update(&full).is_empty());
+        assert_eq!(tracker.count(), 0);
+
+        let done = tracker.history().back().unwrap();
+        assert_eq!(done.state, OrderState::Filled);
+        assert_eq!(done.filled_size, 10.0);
+        assert_eq!(done.trade_ids.len(), 2);
+    }
+
+    #[test]
+    fn test_apply_fill_update_unfilled_cancel() {
+        let mut tracker = OrderTracker::new();
+        tracker.track_order(
+            "token_1".to_string(),
+            "order_123".to_string(),
+            "BUY".to_string(),
+            0.5,
+            10.0,
+        );
+
+        let cancelled = OrderFillUpdate {
+            order_id: "order_123".to_string(),
+            state: OrderState::Cancelled,
+            original_size: 10.0,
+            size_matched: 0.0,
+            trades: vec![],
+        };
+        assert!(tracker.apply_fill_update(&cancelled).is_empty());
+        assert_eq!(tracker.history().back().unwrap().state, OrderState::Cancelled);
+    }
+
+    #[test]
+    fn test_submit_lifecycle_and_events() {
+        let mut tracker = OrderTracker::new();
+
+        let local = tracker.begin_submit("token_1".to_string(), "BUY".to_string(), 0.5, 5.0);
+        assert!(tracker.live_order_ids("token_1").is_empty());
+        tracker.confirm_submit(&local, "order_1".to_string()).unwrap();
+        assert_eq!(tracker.live_order_ids("token_1"), vec!["order_1".to_string()]);
+
+        let rejected = tracker.begin_submit("token_1".to_string(), "BUY".to_string(), 0.5, 5.0);
+        tracker.reject_submit(&rejected).unwrap();
+
+        let states: Vec<(Option<OrderState>, OrderState)> = tracker
+            .take_events()
+            .into_iter()
+            .map(|e| (e.from, e.to))
+            .collect();
+        assert_eq!(states, vec![
+            (None, OrderState::PendingSubmit),
+            (Some(OrderState::PendingSubmit), OrderState::Live),
+            (None, OrderState::PendingSubmit),
+            (Some(OrderState::PendingSubmit), OrderState::Rejected),
+        ]);
+        assert!(tracker.take_events().is_empty());
+        assert_eq!(tracker.history().len(), 1);
+    }
+
+    #[test]
+    fn test_invalid_transitions() {
+        let mut tracker = OrderTracker::new();
+        tracker.track_order("token_1".to_string(), "order_1".to_string(), "BUY".to_string(), 0.5, 5.0);
+
+        // Live orders cannot go back to pending or be rejected
+        assert!(tracker.transition("order_1", OrderState::PendingSubmit).is_err());
+        assert!(tracker.transition("order_1", OrderState::Rejected).is_err());
+
+        // Terminal orders leave the active set
+        tracker.transition("order_1", OrderState::Cancelled).unwrap();
+        assert!(matches!(
+            tracker.transition("order_1", OrderState::Filled),
+            Err(TradingError::OrderNotFound { .. })
+        ));
+        assert!(!OrderState::Filled.can_transition_to(OrderState::Cancelled));
+    }
+
+    #[test]
+    fn test_apply_trade_from_user_channel() {
+        let mut tracker = OrderTracker::new();
+        tracker.track_order("token_1".to_string(), "order_1".to_string(), "BUY".to_string(), 0.5, 10.0);
+
+        let t1 = TradeFill { trade_id: "t1".to_string(), size: 4.0, price: 0.49 };
+        let fill = tracker.apply_trade("order_1", &t1).unwrap();
+        assert_eq!(fill.size, 4.0);
+        assert_eq!(tracker.get_by_id("order_1").unwrap().state, OrderState::PartiallyFilled);
+
+        // MATCHED/MINED/CONFIRMED repeats of the same trade book once
+        assert!(tracker.apply_trade("order_1", &t1).is_none());
+
+        // REST reconciliation sees the same quantity and books nothing more
+        let update = OrderFillUpdate {
+            order_id: "order_1".to_string(),
+            state: OrderState::Live,
+            original_size: 10.0,
+            size_matched: 4.0,
+            trades: vec![t1.clone()],
+        };
+        assert!(tracker.apply_fill_update(&update).is_empty());
+
+        // A fill arriving after cancellation is still booked
+        tracker.transition("order_1", OrderState::Cancelled).unwrap();
+        let t2 = TradeFill { trade_id: "t2".to_string(), size: 1.0, price: 0.5 };
+        assert_eq!(tracker.apply_trade("order_1", &t2).unwrap().size, 1.0);
+        assert_eq!(tracker.history().back().unwrap().filled_size, 5.0);
+        assert!(tracker.apply_trade("unknown", &t2).is_none());
+    }
+
+    #[test]
+    fn test_rest_then_user_channel_books_once() {
+        let mut tracker = OrderTracker::new();
+        tracker.track_order("token_1".to_string(), "order_1".to_string(), "BUY".to_string(), 0.5, 10.0);
+
+        // REST sees 4 matched before the trade shows up: booked at the limit price
+        let update = OrderFillUpdate {
+            order_id: "order_1".to_string(),
+            state: OrderState::Live,
+            original_size: 10.0,
+            size_matched: 4.0,
+            trades: vec![],
+        };
+        assert_eq!(tracker.apply_fill_update(&update)[0].size, 4.0);
+
+        // The user channel trade for the same execution is already covered
+        let t1 = TradeFill { trade_id: "t1".to_string(), size: 4.0, price: 0.49 };
+        assert!(tracker.apply_trade("order_1", &t1).is_none());
+        let order = tracker.get_by_id("order_1").unwrap();
+        assert_eq!(order.filled_size, 4.0);
+        assert_eq!(order.unattributed_filled, 0.0);
+
+        // REST listing the trade later books nothing either; a new trade books in full
+        let update = OrderFillUpdate { trades: vec![t1], ..update };
+        assert!(tracker.apply_fill_update(&update).is_empty());
+        let t2 = TradeFill { trade_id: "t2".to_string(), size: 3.0, price: 0.5 };
+        assert_eq!(tracker.apply_trade("order_1", &t2).unwrap().size, 3.0);
+        assert_eq!(tracker.get_by_id("order_1").unwrap().filled_size, 7.0);
+    }
+
+    #[test]
+    fn test_user_channel_then_rest_books_once() {
+        let mut tracker = OrderTracker::new();
+        tracker.track_order("token_1".to_string(), "order_1".to_string(), "BUY".to_string(), 0.5, 10.0);
+
+        let t1 = TradeFill { trade_id: "t1".to_string(), size: 4.0, price: 0.49 };
+        assert_eq!(tracker.apply_trade("order_1", &t1).unwrap().price, 0.49);
+
+        // REST size_matched includes the trade, with or without it on the trades endpoint
+        let update = OrderFillUpdate {
+            order_id: "order_1".to_string(),
+            state: OrderState::Live,
+            original_size: 10.0,
+            size_matched: 4.0,
+            trades: vec![],
+        };
+        assert!(tracker.apply_fill_update(&update).is_empty());
+        let update = OrderFillUpdate { trades: vec![t1], ..update };
+        assert!(tracker.apply_fill_update(&update).is_empty());
+        assert_eq!(tracker.get_by_id("order_1").unwrap().filled_size, 4.0);
+    }
+
+    #[test]
+    fn test_trade_before_submit_confirmed_is_replayed() {
+        let mut tracker = OrderTracker::new();
+        let local = tracker.begin_submit("token_1".to_string(), "BUY".to_string(), 0.5, 10.0);
+
+        let t1 = TradeFill { trade_id: "t1".to_string(), size: 10.0, price: 0.5 };
+        assert!(tracker.apply_trade("order_1", &t1).is_none());
+        assert!(tracker.take_replayed_fills().is_empty());
+
+        tracker.confirm_submit(&local, "order_1".to_string()).unwrap();
+        let fills = tracker.take_replayed_fills();
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].order_id, "order_1");
+        assert_eq!(tracker.history().back().unwrap().state, OrderState::Filled);
+
+        // Without a pending submit, unknown trades are not held
+        assert!(tracker.apply_trade("foreign", &t1).is_none());
+        assert!(tracker.unmatched_trades.is_empty());
+    }
+
+    #[test]
+    fn test_failed_trade_reverses_fill() {
+        let mut tracker = OrderTracker::new();
+        tracker.track_order("token_1".to_string(), "order_1".to_string(), "BUY".to_string(), 0.5, 10.0);
+
+        let t1 = TradeFill { trade_id: "t1".to_string(), size: 4.0, price: 0.49 };
+        tracker.apply_trade("order_1", &t1).unwrap();
+        let reversal = tracker.fail_trade("order_1", "t1").unwrap();
+        assert_eq!((reversal.size, reversal.price), (4.0, 0.49));
+        assert_eq!(tracker.get_by_id("order_1").unwrap().filled_size, 0.0);
+        // Reported twice, reversed once
+        assert!(tracker.fail_trade("order_1", "t1").is_none());
+    }
+
+    #[test]
+    fn test_immediate_fill_then_user_channel() {
+        let mut tracker = OrderTracker::new();
+        let local = tracker.begin_submit("token_1".to_string(), "BUY".to_string(), 0.47, 10.0);
+        tracker.confirm_submit(&local, "fak_1".to_string()).unwrap();
+
+        // FAK matched 6 of 10 at a better average than the limit; the rest was killed
+        let fill = tracker.apply_immediate_fill("fak_1", 6.0, 0.455).unwrap();
+        assert_eq!((fill.size, fill.price), (6.0, 0.455));
+        let order = tracker.history().back().unwrap();
+        assert_eq!((order.state, order.filled_size), (OrderState::Cancelled, 6.0));
+
+        // Its trades arriving on the user channel are already booked
+        let t1 = TradeFill { trade_id: "t1".to_string(), size: 4.0, price: 0.45 };
+        let t2 = TradeFill { trade_id: "t2".to_string(), size: 2.0, price: 0.465 };
+        assert!(tracker.apply_trade("fak_1", &t1).is_none());
+        assert!(tracker.apply_trade("fak_1", &t2).is_none());
+    }
+
+    #[test]
+    fn test_failed_trade_reverses_at_booked_price() {
+        let mut tracker = OrderTracker::new();
+        tracker.track_order("token_1".to_string(), "order_1".to_string(), "BUY".to_string(), 0.5, 10.0);
+
+        // REST saw 4 matched before the trade: booked at the 0.50 limit
+        let update = OrderFillUpdate {
+            order_id: "order_1".to_string(),
+            state: OrderState::Live,
+            original_size: 10.0,
+            size_matched: 4.0,
+            trades: vec![],
+        };
+        tracker.apply_fill_update(&update);
+        // The trade executed at 0.48 and books nothing new
+        let t1 = TradeFill { trade_id: "t1".to_string(), size: 4.0, price: 0.48 };
+        assert!(tracker.apply_trade("order_1", &t1).is_none());
+
+        let reversal = tracker.fail_trade("order_1", "t1").unwrap();
+        assert_eq!(reversal.size, 4.0);
+        assert!((reversal.price - 0.5).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_apply_user_event_failed_trade() {
+        use crate::websocket::user::UserTradeEvent;
+
+        let order_tracker = RwLock::new(OrderTracker::new());
+        let position_tracker = RwLock::new(PositionTracker::new());
+        let stats = RwLock::new(TradingStats::new());
+        order_tracker.write().await.track_order(
+            "token_1".to_string(), "order_1".to_string(), "BUY".to_string(), 0.5, 10.0);
+
+        let mut trade = UserTradeEvent {
+            trade_id: "t1".to_string(),
+            asset_id: "token_1".to_string(),
+            status: "MATCHED".to_string(),
+            size: 4.0,
+            price: 0.5,
+            taker_order_id: "order_1".to_string(),
+            maker_orders: vec![],
+        };
+        let event = UserEvent::Trade(trade.clone());
+        assert_eq!(apply_user_event(&event, &order_tracker, &position_tracker, &stats).await.len(), 1);
+        assert_eq!(position_tracker.read().await.held_shares("token_1"), 4.0);
+
+        trade.status = "FAILED".to_string();
+        let event = UserEvent::Trade(trade);
+        assert!(apply_user_event(&event, &order_tracker, &position_tracker, &stats).await.is_empty());
+        assert_eq!(position_tracker.read().await.held_shares("token_1"), 0.0);
+        assert_eq!(stats.read().await.fills, 0);
+    }
+
+    #[test]
+    fn test_simulated_cancel_confirmation() {
+        let mut tracker = OrderTracker::new();
+        tracker.track_order("token_1".to_string(), "order_1".to_string(), "BUY".to_string(), 0.5, 5.0);
+        tracker.track_order("token_2".to_string(), "order_2".to_string(), "BUY".to_string(), 0.5, 5.0);
+
+        assert_eq!(tracker.request_cancel_for_token("token_1"), 1);
+        assert_eq!(tracker.confirm_requested_cancels("token_1"), 1);
+        assert_eq!(tracker.count(), 1);
+        assert!(tracker.get_order("token_2").is_some());
+    }
+
     #[tokio::test]
     async fn test_wait_for_fill_success() {
         // Simplified test - just verify the function exists and works
@@ -1,6 +1,7 @@
 //! Order book depth analysis
 //! Matches Python polymaker_5m.py logic
 
+use std::collections::BTreeMap;
 
 /// Order book level
 #[derive(Debug, Clone)]
@@ -9,6 +10,115 @@ pub struct OrderBookLevel {
     pub size: f64,
 }
 
+/// Side of an L2 book level
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookSide {
+    Bid,
+    Ask,
+}
+
+/// Price keys are stored as integer ticks of 1e-6
+const PRICE_SCALE: f64 = 1_000_000.0;
+
+fn price_key(price: f64) -> u64 {
+    (price * PRICE_SCALE).round() as u64
+}
+
+/// Full L2 order book for one asset
+/// Built from a `book` snapshot and kept current with `price_change` deltas.
+#[derive(Debug, Clone, Default)]
+pub struct L2OrderBook {
+    pub asset_id: String,
+    bids: BTreeMap<u64, f64>,
+    asks: BTreeMap<u64, f64>,
+    pub last_update: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl L2OrderBook {
+    /// Create an empty book
+    pub fn new(asset_id: &str) -> Self {
+        Self {
+            asset_id: asset_id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Replace both sides with a snapshot
+    pub fn apply_snapshot(&mut self, bids: &[OrderBookLevel], asks: &[OrderBookLevel]) {
+        self.bids.clear();
+        self.asks.clear();
+        for level in bids {
+            self.apply_delta(BookSide::Bid, level.price, level.size);
+        }
+        for level in asks {
+            self.apply_delta(BookSide::Ask, level.price, level.size);
+        }
+        self.last_update = Some(chrono::Utc::now());
+    }
+
+    /// Set the size at one price level (size 0 removes it)
+    pub fn apply_delta(&mut self, side: BookSide, price: f64, size: f64) {
+        if !price.is_finite() || price <= 0.0 {
+            return;
+        }
+        let levels = match side {
+            BookSide::Bid => &mut self.bids,
+            BookSide::Ask => &mut self.asks,
+        };
+        if size > 0.0 {
+            levels.insert(price_key(price), size);
+        } else {
+            levels.remove(&price_key(price));
+        }
+        self.last_update = Some(chrono::Utc::now());
+    }
+
+    /// Top bid levels, best (highest) first
+    pub fn bids(&self, depth: usize) -> Vec<OrderBookLevel> {
+        self.bids
+            .iter()
+            .rev()
+            .take(depth)
+            .map(|(key, size)| OrderBookLevel { price: *key as f64 / PRICE_SCALE, size: *size })
+            .collect()
+    }
+
+    /// Top ask levels, best (lowest) first
+    pub fn asks(&self, depth: usize) -> Vec<OrderBookLevel> {
+        self.asks
+            .iter()
+            .take(depth)
+            .map(|(key, size)| OrderBookLevel { price: *key as f64 / PRICE_SCALE, size: *size })
+            .collect()
+    }
+
+    /// Best bid level
+    pub fn best_bid(&self) -> Option<OrderBookLevel> {
+        self.bids(1).pop()
+    }
+
+    /// Best ask level
+    pub fn best_ask(&self) -> Option<OrderBookLevel> {
+        self.asks(1).pop()
+    }
+
+    /// Depth analysis over the top `depth_lookback` levels
+    pub fn depth(&self, min_size: f64, depth_lookback: usize) -> Option<OrderBookDepth> {
+        let filter = |levels: Vec<OrderBookLevel>| -> Vec<OrderBookLevel> {
+            levels.into_iter().filter(|l| l.size >= min_size).collect()
+        };
+        analyze_levels(
+            filter(self.bids(depth_lookback)),
+            filter(self.asks(depth_lookback)),
+        )
+    }
+
+    /// Check if both sides are empty
+    pub fn is_empty(&self) -> bool {
+        self.bids.is_empty() && self.asks.is_empty()
+    }
+}
+
 /// Order book depth analysis
 #[derive(Debug, Clone)]
 pub struct OrderBookDepth {
@@ -52,7 +162,7 @@ pub fn analyze_order_book_depth_safe(
     depth_lookback: usize,
 ) -> Option<OrderBookDepth> {
     // Parse and filter bids
-    let mut parsed_bids: Vec<OrderBookLevel> = bids
+    let parsed_bids: Vec<OrderBookLevel> = bids
         .iter()
         .take(depth_lookback)
         .filter_map(|b| {
@@ -67,7 +177,7 @@ pub fn analyze_order_book_depth_safe(
         .collect();
 
     // Parse and filter asks
-    let mut parsed_asks: Vec<OrderBookLevel> = asks
+    let parsed_asks: Vec<OrderBookLevel> = asks
         .iter()
         .take(depth_lookback)
         .filter_map(|a| {
@@ -81,6 +191,15 @@ pub fn analyze_order_book_depth_safe(
         })
         .collect();
 
+    analyze_levels(parsed_bids, parsed_asks)
+}
+
+/// Build depth analysis from parsed levels
+/// Returns None unless each side has at least two levels
+fn analyze_levels(
+    mut parsed_bids: Vec<OrderBookLevel>,
+    mut parsed_asks: Vec<OrderBookLevel>,
+) -> Option<OrderBookDepth> {
     // Check if we have enough data
     if parsed_bids.len() < 2 || parsed_asks.len() < 2 {
         // Return None to indicate insufficient data
@@ -195,6 +314,31 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn test_l2_book_snapshot_and_deltas() {
+        let level = |price, size| OrderBookLevel { price, size };
+        let mut book = L2OrderBook::new("token_1");
+        // Snapshot order does not matter (WS sends bids ascending)
+        book.apply_snapshot(
+            &[level(0.50, 30.0), level(0.52, 100.0), level(0.51, 200.0)],
+            &[level(0.56, 80.0), level(0.54, 150.0), level(0.55, 100.0)],
+        );
+        assert_eq!(book.best_bid().unwrap().price, 0.52);
+        assert_eq!(book.best_ask().unwrap().price, 0.54);
+
+        // Best bid pulled, new ask level inside the spread
+        book.apply_delta(BookSide::Bid, 0.52, 0.0);
+        book.apply_delta(BookSide::Ask, 0.53, 20.0);
+        assert_eq!(book.best_bid().unwrap().price, 0.51);
+        assert_eq!(book.best_ask().unwrap().price, 0.53);
+        assert_eq!(book.bids(10).len(), 2);
+
+        let depth = book.depth(10.0, 2).unwrap();
+        assert_eq!(depth.second_ask.price, 0.54);
+        assert_eq!(depth.bid_depth, 230.0);
+        assert_eq!(depth.ask_depth, 170.0);
+    }
+
     #[test]
     fn test_calculate_mm_prices() {
         let depth = OrderBookDepth {
@@ -210,7 +354,7 @@ mod tests {
         let (bid, ask) = calculate_mm_prices(&depth, 0.0, 0.005, 0.02);
 
         assert!(bid < ask);
-        assert!(bid >= 0.01 && bid <= 0.99);
-        assert!(ask >= 0.01 && ask <= 0.99);
+        assert!((0.01..=0.99).contains(&bid));
+        assert!((0.01..=0.99).contains(&ask));
     }
 }
\ No newline at end of file
@@ -31,6 +31,8 @@ pub struct PositionEntry {
     pub size: f64,
     pub price: f64,
     pub timestamp: chrono::DateTime<chrono::Utc>,
+    /// CLOB trade that produced this entry, if known
+    pub trade_id: Option<String>,
 }
 
 /// Inventory status summary
@@ -44,6 +46,42 @@ pub struct InventoryStatus {
     pub recommendation: String,
 }
 
+impl InventoryStatus {
+    /// Status from UP and DOWN dollar values
+    pub fn from_values(up_value: f64, down_value: f64) -> Self {
+        let total = up_value + down_value;
+        let skew = if total == 0.0 {
+            0.0
+        } else {
+            (up_value - down_value) / total
+        };
+
+        // Consider balanced if skew < 30%
+        let is_balanced = skew.abs() < 0.3;
+
+        let recommendation = if skew > 0.5 {
+            format!("UP position too large ({:.1}%), reduce UP or add DOWN", skew * 100.0)
+        } else if skew < -0.5 {
+            format!("DOWN position too large ({:.1}%), reduce DOWN or add UP", skew.abs() * 100.0)
+        } else if skew > 0.3 {
+            format!("UP slightly overweight ({:.1}%), consider balancing", skew * 100.0)
+        } else if skew < -0.3 {
+            format!("DOWN slightly overweight ({:.1}%), consider balancing", skew.abs() * 100.0)
+        } else {
+            "Portfolio balanced".to_string()
+        };
+
+        Self {
+            up_value,
+            down_value,
+            total_value: total,
+            skew,
+            is_balanced,
+            recommendation,
+        }
+    }
+}
+
 /// Balance adjustment action
 #[derive(Debug, Clone)]
 pub enum Action {
@@ -88,11 +126,24 @@ impl PositionTracker {
         side: Side,
         size: f64,
         price: f64,
+    ) {
+        self.update_position_with_trade(market_id, side, size, price, None).await;
+    }
+
+    /// Update position with an execution, keeping its CLOB trade ID
+    pub async fn update_position_with_trade(
+        &mut self,
+        market_id: &str,
+        side: Side,
+        size: f64,
+        price: f64,
+        trade_id: Option<&str>,
     ) {
         let entry = PositionEntry {
             size,
             price,
             timestamp: chrono::Utc::now(),
+            trade_id: trade_id.map(str::to_string),
         };
         
         self.positions
@@ -191,64 +242,71 @@ impl PositionTracker {
             }
         }
 
-        let total = up_value + down_value;
-        let skew = if total == 0.0 {
-            0.0
-        } else {
-            (up_value - down_value) / total
-        };
-
-        // Consider balanced if skew < 30%
-        let is_balanced = skew.abs() < 0.3;
+        InventoryStatus::from_values(up_value, down_value)
+    }
 
-        let recommendation = if skew > 0.5 {
-            format!("UP position too large ({:.1}%), reduce UP or add DOWN", skew * 100.0)
-        } else if skew < -0.5 {
-            format!("DOWN position too large ({:.1}%), reduce DOWN or add UP", skew.abs() * 100.0)
-        } else if skew > 0.3 {
-            format!("UP slightly overweight ({:.1}%), consider balancing", skew * 100.0)
-        } else if skew < -0.3 {
-            format!("DOWN slightly overweight ({:.1}%), consider balancing", skew.abs() * 100.0)
-        } else {
-            "Portfolio balanced".to_string()
-        };
+    /// Inventory of one market: UP and DOWN shares held, valued at their mids
+    /// Split pairs sit on both tokens, so a fresh split reads as balanced.
+    pub fn market_inventory(
+        &self,
+        up_token_id: &str,
+        down_token_id: &str,
+        up_mid: f64,
+        down_mid: f64,
+    ) -> InventoryStatus {
+        InventoryStatus::from_values(
+            self.held_shares(up_token_id) * up_mid,
+            self.held_shares(down_token_id) * down_mid,
+        )
+    }
 
-        InventoryStatus {
-            up_value,
-            down_value,
-            total_value: total,
-            skew,
-            is_balanced,
-            recommendation,
+    /// Shares held long in a token
+    pub fn held_shares(&self, token_id: &str) -> f64 {
+        match self.positions.get(token_id) {
+            Some(position) if position.side == Side::Buy => position.total_size,
+            _ => 0.0,
         }
     }
 
-    /// Check merge opportunity
+    /// Check merge opportunity: complete UP+DOWN pairs that can be merged back into USDC
     /// Matches Python: check_merge_opportunity()
     pub fn check_merge_opportunity(
         &self,
-        market_id: &str,
+        up_token_id: &str,
+        down_token_id: &str,
         merge_threshold: f64,
     ) -> Option<f64> {
-        // Get positions for this market
-        let up_pos = self.positions.values().find(|p| {
-            p.market_id == market_id && matches!(p.side, Side::Buy)
-        });
-        
-        let down_pos = self.positions.values().find(|p| {
-            p.market_id == market_id && matches!(p.side, Side::Sell)
-        });
-
-        if let (Some(up), Some(down)) = (up_pos, down_pos) {
-            if up.total_size > 0.0 && down.total_size > 0.0 {
-                let merge_amount = up.total_size.min(down.total_size);
-                if merge_amount >= merge_threshold {
-                    return Some(merge_amount);
-                }
-            }
+        let merge_amount = self.held_shares(up_token_id).min(self.held_shares(down_token_id));
+        if merge_amount > 0.0 && merge_amount >= merge_threshold {
+            Some(merge_amount)
+        } else {
+            None
+        }
+    }
+
+    /// Record pairs minted by a split; each leg costs half a dollar
+    pub async fn apply_split(
+        &mut self,
+        up_token_id: &str,
+        down_token_id: &str,
+        shares: f64,
+    ) {
+        for token_id in [up_token_id, down_token_id] {
+            self.update_position(token_id, Side::Buy, shares, 0.5).await;
         }
+    }
 
-        None
+    /// Remove merged pairs from both legs at their cost basis
+    pub async fn apply_merge(
+        &mut self,
+        up_token_id: &str,
+        down_token_id: &str,
+        shares: f64,
+    ) {
+        for token_id in [up_token_id, down_token_id] {
+            let price = self.positions.get(token_id).map(|p| p.avg_price).unwrap_or(0.0);
+            self.update_position(token_id, Side::Sell, shares, price).await;
+        }
     }
 
     /// Check if should skip trading on one side
@@ -402,6 +460,51 @@ mod tests {
         assert!((pos.avg_price - 0.55).abs() < 0.001);
     }
 
+    #[tokio::test]
+    async fn test_merge_opportunity_and_apply() {
+        let mut tracker = PositionTracker::new();
+        tracker.update_position("up", Side::Buy, 8.0, 0.45).await;
+        tracker.update_position("down", Side::Buy, 5.0, 0.50).await;
+
+        assert_eq!(tracker.check_merge_opportunity("up", "down", 10.0), None);
+        assert_eq!(tracker.check_merge_opportunity("up", "down", 5.0), Some(5.0));
+
+        tracker.apply_merge("up", "down", 5.0).await;
+        assert!((tracker.held_shares("up") - 3.0).abs() < 1e-9);
+        assert_eq!(tracker.held_shares("down"), 0.0);
+        assert!((tracker.get_position("up").await.unwrap().avg_price - 0.45).abs() < 1e-9);
+        assert_eq!(tracker.check_merge_opportunity("up", "down", 0.0), None);
+    }
+
+    #[tokio::test]
+    async fn test_split_cost_basis() {
+        let mut tracker = PositionTracker::new();
+        tracker.update_position("up", Side::Buy, 2.0, 0.40).await;
+        tracker.apply_split("up", "down", 4.0).await;
+
+        assert!((tracker.held_shares("up") - 6.0).abs() < 1e-9);
+        assert!((tracker.held_shares("down") - 4.0).abs() < 1e-9);
+        // (2*0.40 + 4*0.50) / 6
+        assert!((tracker.get_position("up").await.unwrap().avg_price - 2.8 / 6.0).abs() < 1e-9);
+        assert!((tracker.get_position("down").await.unwrap().avg_price - 0.5).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_fresh_split_has_no_skew() {
+        let mut tracker = PositionTracker::new();
+        tracker.apply_split("up", "down", 10.0).await;
+
+        let status = tracker.market_inventory("up", "down", 0.5, 0.5);
+        assert_eq!(status.skew, 0.0);
+        assert!(status.is_balanced);
+        assert!((status.total_value - 10.0).abs() < 1e-9);
+
+        // Skew weighs full UP against full DOWN holdings at mid: 20 UP @ 0.6 vs 10 DOWN @ 0.4
+        tracker.update_position("up", Side::Buy, 10.0, 0.5).await;
+        let status = tracker.market_inventory("up", "down", 0.6, 0.4);
+        assert!((status.skew - (12.0 - 4.0) / 16.0).abs() < 1e-9);
+    }
+
     #[tokio::test]
     async fn test_reduce_position() {
         let mut tracker = PositionTracker::new();
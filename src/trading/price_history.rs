@@ -0,0 +1,315 @@
+//! Price history per token and per reference asset
+//! Ring buffers of recent prices with OHLC candles and realized volatility estimators
+
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{HashMap, VecDeque};
+
+/// Points kept per series unless configured otherwise
+pub const DEFAULT_HISTORY_CAPACITY: usize = 20_000;
+
+/// Seconds in a year; crypto spot trades around the clock
+pub const SECONDS_PER_YEAR: f64 = 365.0 * 24.0 * 3600.0;
+
+/// One observed price
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PricePoint {
+    pub timestamp: DateTime<Utc>,
+    pub price: f64,
+}
+
+/// OHLC bar of one interval
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Candle {
+    /// Interval start, aligned to a multiple of the interval
+    pub start: DateTime<Utc>,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    /// Points aggregated
+    pub count: usize,
+}
+
+/// Realized volatility estimate
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RealizedVol {
+    /// Standard deviation of log returns per interval
+    pub per_interval: f64,
+    pub interval: Duration,
+    /// Returns the estimate is based on
+    pub returns: usize,
+}
+
+impl RealizedVol {
+    /// Per square root of a second
+    pub fn per_second(&self) -> f64 {
+        self.per_interval / (self.interval.num_milliseconds() as f64 / 1000.0).sqrt()
+    }
+
+    /// Annualized
+    pub fn annualized(&self) -> f64 {
+        self.per_second() * SECONDS_PER_YEAR.sqrt()
+    }
+}
+
+/// Ring buffer of prices for one token or asset
+#[derive(Debug, Clone)]
+pub struct PriceSeries {
+    points: VecDeque<PricePoint>,
+    capacity: usize,
+}
+
+impl PriceSeries {
+    /// Create empty series keeping at most `capacity` points
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            points: VecDeque::with_capacity(capacity.min(1024)),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Append a price; out-of-order and non-positive prices are ignored
+    pub fn push(&mut self, timestamp: DateTime<Utc>, price: f64) {
+        if price <= 0.0 || self.points.back().is_some_and(|last| last.timestamp > timestamp) {
+            return;
+        }
+        if self.points.len() == self.capacity {
+            self.points.pop_front();
+        }
+        self.points.push_back(PricePoint { timestamp, price });
+    }
+
+    /// Most recent point
+    pub fn latest(&self) -> Option<&PricePoint> {
+        self.points.back()
+    }
+
+    /// Points, oldest first
+    pub fn points(&self) -> impl DoubleEndedIterator<Item = &PricePoint> {
+        self.points.iter()
+    }
+
+    /// Last point at or before `at`: the price in force at that time
+    pub fn price_at(&self, at: DateTime<Utc>) -> Option<&PricePoint> {
+        self.points.iter().rev().find(|p| p.timestamp <= at)
+    }
+
+    /// First point at or after `at`
+    pub fn first_at_or_after(&self, at: DateTime<Utc>) -> Option<&PricePoint> {
+        self.points.iter().find(|p| p.timestamp >= at)
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// OHLC candles since `since`, oldest first; intervals without points are skipped
+    pub fn candles(&self, interval: Duration, since: Option<DateTime<Utc>>) -> Vec<Candle> {
+        let step = interval.num_milliseconds();
+        if step <= 0 {
+            return Vec::new();
+        }
+        let mut candles: Vec<Candle> = Vec::new();
+        for point in self.points.iter().filter(|p| since.is_none_or(|since| p.timestamp >= since)) {
+            let millis = point.timestamp.timestamp_millis();
+            let start = millis - millis.rem_euclid(step);
+            match candles.last_mut() {
+                Some(candle) if candle.start.timestamp_millis() == start => {
+                    candle.high = candle.high.max(point.price);
+                    candle.low = candle.low.min(point.price);
+                    candle.close = point.price;
+                    candle.count += 1;
+                }
+                _ => candles.push(Candle {
+                    start: DateTime::from_timestamp_millis(start).unwrap_or(point.timestamp),
+                    open: point.price,
+                    high: point.price,
+                    low: point.price,
+                    close: point.price,
+                    count: 1,
+                }),
+            }
+        }
+        candles
+    }
+
+    /// Close-to-close log returns over the trailing window, with the intervals each one spans
+    fn interval_returns(&self, interval: Duration, window: Duration) -> Vec<(f64, f64)> {
+        let Some(latest) = self.latest() else {
+            return Vec::new();
+        };
+        let step = interval.num_milliseconds() as f64;
+        let candles = self.candles(interval, Some(latest.timestamp - window));
+        candles
+            .windows(2)
+            .map(|w| {
+                let spans = ((w[1].start - w[0].start).num_milliseconds() as f64 / step).max(1.0);
+                ((w[1].close / w[0].close).ln(), spans)
+            })
+            .collect()
+    }
+
+    /// Close-to-close volatility over the trailing window
+    ///
+    /// Returns across gaps in the data count for as many intervals as they span.
+    pub fn close_to_close_volatility(&self, interval: Duration, window: Duration) -> Option<RealizedVol> {
+        let returns = self.interval_returns(interval, window);
+        if returns.is_empty() {
+            return None;
+        }
+        let squared: f64 = returns.iter().map(|(r, _)| r * r).sum();
+        let spans: f64 = returns.iter().map(|(_, n)| n).sum();
+        Some(RealizedVol {
+            per_interval: (squared / spans).sqrt(),
+            interval,
+            returns: returns.len(),
+        })
+    }
+
+    /// EWMA (RiskMetrics) volatility over the trailing window, `lambda` the per-interval decay
+    pub fn ewma_volatility(&self, interval: Duration, window: Duration, lambda: f64) -> Option<RealizedVol> {
+        let returns = self.interval_returns(interval, window);
+        let (first, rest) = returns.split_first()?;
+        let mut variance = first.0 * first.0 / first.1;
+        for (r, spans) in rest {
+            let decay = lambda.powf(*spans);
+            variance = decay * variance + (1.0 - decay) * r * r / spans;
+        }
+        Some(RealizedVol {
+            per_interval: variance.sqrt(),
+            interval,
+            returns: returns.len(),
+        })
+    }
+}
+
+/// Price series keyed by token id or reference symbol
+#[derive(Debug)]
+pub struct PriceHistory {
+    series: HashMap<String, PriceSeries>,
+    capacity: usize,
+}
+
+impl Default for PriceHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTORY_CAPACITY)
+    }
+}
+
+impl PriceHistory {
+    /// Create history keeping at most `capacity` points per series
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            series: HashMap::new(),
+            capacity,
+        }
+    }
+
+    /// Record a price
+    pub fn record(&mut self, key: &str, timestamp: DateTime<Utc>, price: f64) {
+        if let Some(series) = self.series.get_mut(key) {
+            series.push(timestamp, price);
+            return;
+        }
+        let mut series = PriceSeries::new(self.capacity);
+        series.push(timestamp, price);
+        self.series.insert(key.to_string(), series);
+    }
+
+    /// Series of a token or symbol
+    pub fn series(&self, key: &str) -> Option<&PriceSeries> {
+        self.series.get(key)
+    }
+
+    /// Drop a series, e.g. once its market has expired
+    pub fn remove(&mut self, key: &str) {
+        self.series.remove(key);
+    }
+
+    /// Most recent price
+    pub fn latest(&self, key: &str) -> Option<&PricePoint> {
+        self.series.get(key)?.latest()
+    }
+
+    /// OHLC candles since `since`
+    pub fn candles(&self, key: &str, interval: Duration, since: Option<DateTime<Utc>>) -> Vec<Candle> {
+        self.series
+            .get(key)
+            .map(|s| s.candles(interval, since))
+            .unwrap_or_default()
+    }
+
+    /// Close-to-close volatility over the trailing window
+    pub fn close_to_close_volatility(&self, key: &str, interval: Duration, window: Duration) -> Option<RealizedVol> {
+        self.series.get(key)?.close_to_close_volatility(interval, window)
+    }
+
+    /// EWMA volatility over the trailing window
+    pub fn ewma_volatility(&self, key: &str, interval: Duration, window: Duration, lambda: f64) -> Option<RealizedVol> {
+        self.series.get(key)?.ewma_volatility(interval, window, lambda)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(millis: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp_millis(millis).unwrap()
+    }
+
+    #[test]
+    fn test_ring_buffer_and_candles() {
+        let mut series = PriceSeries::new(5);
+        for (millis, price) in [(0, 1.0), (400, 3.0), (900, 2.0), (1_200, 2.5), (3_100, 4.0)] {
+            series.push(at(millis), price);
+        }
+        // Out of order: ignored
+        series.push(at(1_000), 9.0);
+
+        let candles = series.candles(Duration::seconds(1), None);
+        assert_eq!(candles.len(), 3);
+        assert_eq!((candles[0].open, candles[0].high, candles[0].low, candles[0].close), (1.0, 3.0, 1.0, 2.0));
+        assert_eq!(candles[0].count, 3);
+        assert_eq!(candles[1].start, at(1_000));
+        assert_eq!(candles[2].start, at(3_000));
+
+        // Full buffer drops the oldest point
+        series.push(at(3_500), 5.0);
+        assert_eq!(series.len(), 5);
+        assert_eq!(series.points().next().unwrap().price, 3.0);
+        assert_eq!(series.first_at_or_after(at(1_000)).unwrap().price, 2.5);
+        assert_eq!(series.price_at(at(3_000)).unwrap().price, 2.5);
+    }
+
+    #[test]
+    fn test_realized_volatility() {
+        let mut history = PriceHistory::default();
+        // Alternating 1% moves every second
+        let r = 0.01f64.ln_1p();
+        for i in 0..61 {
+            history.record("BTCUSDT", at(i * 1_000), if i % 2 == 0 { 100.0 } else { 101.0 });
+        }
+        let second = Duration::seconds(1);
+        let vol = history.close_to_close_volatility("BTCUSDT", second, Duration::seconds(60)).unwrap();
+        assert_eq!(vol.returns, 60);
+        assert!((vol.per_interval - r).abs() < 1e-12);
+        assert!((vol.annualized() - r * SECONDS_PER_YEAR.sqrt()).abs() < 1e-6);
+
+        // Constant squared returns: EWMA matches close-to-close
+        let ewma = history.ewma_volatility("BTCUSDT", second, Duration::seconds(60), 0.94).unwrap();
+        assert!((ewma.per_interval - r).abs() < 1e-12);
+
+        // A 4s gap counts as four intervals
+        history.record("BTCUSDT", at(64_000), 101.0);
+        let gapped = history.close_to_close_volatility("BTCUSDT", second, Duration::seconds(4)).unwrap();
+        assert_eq!(gapped.returns, 1);
+        assert!((gapped.per_interval - r / 2.0).abs() < 1e-12);
+
+        assert!(history.close_to_close_volatility("ETHUSDT", second, Duration::seconds(60)).is_none());
+    }
+}
@@ -0,0 +1,100 @@
+//! Slot rollover scheduling
+//! Holds each series' next-slot market so it can be subscribed early and switched in at the boundary
+
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+use crate::api::discovery::SeriesMarket;
+
+/// Next-slot markets staged per series, waiting for their slot to begin
+#[derive(Debug, Default)]
+pub struct RolloverScheduler {
+    /// Series slug -> staged market
+    staged: HashMap<String, SeriesMarket>,
+}
+
+impl RolloverScheduler {
+    /// Create empty scheduler
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stage a series' next market; false if that market is already staged
+    pub fn stage(&mut self, market: SeriesMarket) -> bool {
+        if self
+            .staged
+            .get(&market.series)
+            .is_some_and(|m| m.condition_id == market.condition_id)
+        {
+            return false;
+        }
+        self.staged.insert(market.series.clone(), market);
+        true
+    }
+
+    /// Staged market of a series
+    pub fn staged(&self, series: &str) -> Option<&SeriesMarket> {
+        self.staged.get(series)
+    }
+
+    /// All staged markets
+    pub fn markets(&self) -> impl Iterator<Item = &SeriesMarket> {
+        self.staged.values()
+    }
+
+    /// Earliest pending slot boundary
+    pub fn next_switch(&self) -> Option<DateTime<Utc>> {
+        self.staged.values().map(SeriesMarket::starts_at).min()
+    }
+
+    /// Take every staged market whose slot has begun
+    pub fn take_due(&mut self, now: DateTime<Utc>) -> Vec<SeriesMarket> {
+        let due: Vec<String> = self
+            .staged
+            .iter()
+            .filter(|(_, m)| m.starts_at() <= now)
+            .map(|(series, _)| series.clone())
+            .collect();
+        due.into_iter().filter_map(|series| self.staged.remove(&series)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::gamma::Market;
+
+    fn market(series: &str, slot_start: i64) -> SeriesMarket {
+        SeriesMarket {
+            series: series.to_string(),
+            slot_start,
+            slot_seconds: 300,
+            slug: format!("{}-{}", series, slot_start),
+            condition_id: format!("0x{}", slot_start),
+            up_token: format!("{}1", slot_start),
+            down_token: format!("{}2", slot_start),
+            end_time: DateTime::from_timestamp(slot_start + 300, 0).unwrap(),
+            market: Market::default(),
+        }
+    }
+
+    #[test]
+    fn test_switches_at_slot_boundary() {
+        let mut scheduler = RolloverScheduler::new();
+        assert!(scheduler.stage(market("btc-updown-5m", 1_760_000_100)));
+        assert!(!scheduler.stage(market("btc-updown-5m", 1_760_000_100)));
+        assert!(scheduler.stage(market("eth-updown-15m", 1_760_000_400)));
+
+        let boundary = DateTime::from_timestamp(1_760_000_100, 0).unwrap();
+        assert_eq!(scheduler.next_switch(), Some(boundary));
+
+        // One second early nothing is due
+        assert!(scheduler.take_due(boundary - chrono::Duration::seconds(1)).is_empty());
+
+        let due = scheduler.take_due(boundary);
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].series, "btc-updown-5m");
+        assert!(scheduler.staged("btc-updown-5m").is_none());
+        assert_eq!(scheduler.next_switch(), DateTime::from_timestamp(1_760_000_400, 0));
+    }
+}
@@ -1,7 +1,10 @@
 //! Trading statistics
 //! Matches Python: self.stats
 
+use super::markout::MarkoutStats;
+use super::order_tracker::{OrderEvent, OrderState};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Trading statistics
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -16,6 +19,37 @@ pub struct TradingStats {
     pub total_pnl: f64,
     pub merge_count: u64,
     pub last_update: String,
+    /// Executions booked from CLOB trades (partial fills count individually)
+    #[serde(default)]
+    pub fills: u64,
+    /// Matched size across all executions
+    #[serde(default)]
+    pub filled_volume: f64,
+    /// Matched size times execution price
+    #[serde(default)]
+    pub filled_notional: f64,
+    #[serde(default)]
+    pub orders_rejected: u64,
+    /// USDC splits into UP+DOWN pairs
+    #[serde(default)]
+    pub split_count: u64,
+    /// Positions exited by the exit manager, by reason
+    #[serde(default)]
+    pub exits: BTreeMap<String, u64>,
+    /// PnL of exit fills against the average entry price
+    #[serde(default)]
+    pub exit_pnl: f64,
+    /// Complementary-pair arbitrage attempts that traded
+    #[serde(default)]
+    pub arbitrages: u64,
+    /// Of those, attempts whose second leg came up short
+    #[serde(default)]
+    pub arb_one_legged: u64,
+    #[serde(default)]
+    pub arb_pnl: f64,
+    /// Mid moves after our fills, by market, side and price bucket
+    #[serde(default)]
+    pub markouts: MarkoutStats,
 }
 
 impl TradingStats {
@@ -33,6 +67,17 @@ impl TradingStats {
             total_pnl: 0.0,
             merge_count: 0,
             last_update: now,
+            fills: 0,
+            filled_volume: 0.0,
+            filled_notional: 0.0,
+            orders_rejected: 0,
+            split_count: 0,
+            exits: BTreeMap::new(),
+            exit_pnl: 0.0,
+            arbitrages: 0,
+            arb_one_legged: 0,
+            arb_pnl: 0.0,
+            markouts: MarkoutStats::default(),
         }
     }
     
@@ -53,6 +98,50 @@ impl TradingStats {
         self.update_time();
     }
     
+    /// Record one execution (full or partial) at its actual price
+    pub fn record_fill(&mut self,
+        size: f64,
+        price: f64,
+    ) {
+        self.fills += 1;
+        self.filled_volume += size;
+        self.filled_notional += size * price;
+        self.update_time();
+    }
+
+    /// Take back an execution whose trade failed after matching
+    pub fn reverse_fill(&mut self,
+        size: f64,
+        price: f64,
+    ) {
+        self.fills = self.fills.saturating_sub(1);
+        self.filled_volume -= size;
+        self.filled_notional -= size * price;
+        self.update_time();
+    }
+    
+    /// Record an order lifecycle change from OrderTracker
+    pub fn record_order_event(&mut self,
+        event: &OrderEvent,
+    ) {
+        let newly_placed = matches!(event.from, None | Some(OrderState::PendingSubmit))
+            && !matches!(event.to, OrderState::PendingSubmit | OrderState::Rejected);
+        if newly_placed {
+            self.record_order_placed(event.size);
+        }
+
+        match event.to {
+            OrderState::Filled => self.record_order_filled(event.filled_size),
+            OrderState::Cancelled => self.record_order_cancelled(),
+            OrderState::Expired => self.record_order_expired(),
+            OrderState::Rejected => {
+                self.orders_rejected += 1;
+                self.update_time();
+            }
+            OrderState::PendingSubmit | OrderState::Live | OrderState::PartiallyFilled => {}
+        }
+    }
+    
     /// Record order cancelled
     pub fn record_order_cancelled(&mut self,
     ) {
@@ -81,6 +170,34 @@ impl TradingStats {
         self.update_time();
     }
     
+    /// Record split
+    pub fn record_split(&mut self) {
+        self.split_count += 1;
+        self.update_time();
+    }
+    
+    /// Record a position exit
+    pub fn record_exit(&mut self, reason: &str) {
+        *self.exits.entry(reason.to_string()).or_insert(0) += 1;
+        self.update_time();
+    }
+    
+    /// Record an execution of an exit order
+    pub fn record_exit_fill(&mut self, pnl: f64) {
+        self.exit_pnl += pnl;
+        self.update_time();
+    }
+    
+    /// Record a traded arbitrage
+    pub fn record_arbitrage(&mut self, pnl: f64, one_legged: bool) {
+        self.arbitrages += 1;
+        if one_legged {
+            self.arb_one_legged += 1;
+        }
+        self.arb_pnl += pnl;
+        self.update_time();
+    }
+    
     /// Update PnL
     pub fn update_pnl(&mut self,
         pnl: f64,
@@ -127,7 +244,7 @@ impl TradingStats {
     /// Get secure data path
     fn get_data_path(filename: &str) -> std::path::PathBuf {
         dirs::data_dir()
-            .unwrap_or_else(|| std::env::temp_dir())
+            .unwrap_or_else(std::env::temp_dir)
             .join("polymarket-pro")
             .join(filename)
     }
@@ -148,17 +265,30 @@ impl TradingStats {
     
     /// Get summary
     pub fn summary(&self) -> String {
-        format!(
-            "📊 Stats: Orders placed={}, filled={}, cancelled={}, expired={}, errors={}, volume={:.2}, PnL={:.2}, merges={}",
+        let summary = format!(
+            "📊 Stats: Orders placed={}, filled={}, fills={}, filled_volume={:.2}, cancelled={}, expired={}, rejected={}, errors={}, volume={:.2}, PnL={:.2}, merges={}, splits={}, exits={}, exit_pnl={:.2}, arbs={} (one-legged {}), arb_pnl={:.2}",
             self.orders_placed,
             self.orders_filled,
+            self.fills,
+            self.filled_volume,
             self.orders_cancelled,
             self.orders_expired,
+            self.orders_rejected,
             self.errors,
             self.total_volume,
             self.total_pnl,
-            self.merge_count
-        )
+            self.merge_count,
+            self.split_count,
+            self.exits.values().sum::<u64>(),
+            self.exit_pnl,
+            self.arbitrages,
+            self.arb_one_legged,
+            self.arb_pnl
+        );
+        if self.markouts.by_side.is_empty() {
+            return summary;
+        }
+        format!("{}\n{}", summary, self.markouts.summary())
     }
     
     fn update_time(&mut self,
@@ -229,6 +359,46 @@ mod tests {
         assert_eq!(stats.total_volume, 10.0);
     }
     
+    #[test]
+    fn test_record_fill_and_old_stats_file() {
+        let mut stats = TradingStats::new();
+        stats.record_fill(4.0, 0.5);
+        stats.record_fill(6.0, 0.4);
+        
+        assert_eq!(stats.fills, 2);
+        assert_eq!(stats.filled_volume, 10.0);
+        assert!((stats.filled_notional - 4.4).abs() < 1e-9);
+        
+        // Stats files written before fill tracking still load
+        let old = r#"{"start_time":"","orders_placed":1,"orders_filled":1,"orders_cancelled":0,
+            "orders_expired":0,"errors":0,"total_volume":1.0,"total_pnl":0.0,"merge_count":0,"last_update":""}"#;
+        let loaded: TradingStats = serde_json::from_str(old).unwrap();
+        assert_eq!(loaded.fills, 0);
+    }
+    
+    #[test]
+    fn test_record_order_events() {
+        let mut stats = TradingStats::new();
+        let event = |from, to| OrderEvent {
+            order_id: "order_1".to_string(),
+            token: "token_1".to_string(),
+            from,
+            to,
+            size: 5.0,
+            filled_size: 5.0,
+        };
+        
+        stats.record_order_event(&event(None, OrderState::PendingSubmit));
+        stats.record_order_event(&event(Some(OrderState::PendingSubmit), OrderState::Live));
+        stats.record_order_event(&event(Some(OrderState::Live), OrderState::Filled));
+        stats.record_order_event(&event(Some(OrderState::PendingSubmit), OrderState::Rejected));
+        
+        assert_eq!(stats.orders_placed, 1);
+        assert_eq!(stats.total_volume, 5.0);
+        assert_eq!(stats.orders_filled, 1);
+        assert_eq!(stats.orders_rejected, 1);
+    }
+    
     #[test]
     fn test_price_freshness() {
         let mut freshness = PriceFreshness::new(5);
@@ -0,0 +1,648 @@
+//! Pluggable quoting strategies
+//! A strategy turns a market snapshot into the quotes it wants resting; the trading cycle places them
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+use crate::api::discovery::SeriesMarket;
+use crate::api::Side;
+use crate::config::TradingConfig;
+use crate::trading::avellaneda::{QuoteModel, StoikovQuote};
+use crate::trading::market_maker::{MarketMaker, MarketMakerConfig};
+use crate::trading::orderbook::{calculate_mm_prices, L2OrderBook};
+use crate::trading::position::InventoryStatus;
+use crate::trading::price_history::RealizedVol;
+
+/// Balance buffer applied on top of the notional of all buys (matches Python)
+pub const BALANCE_BUFFER_RATIO: f64 = 1.15;
+
+/// Which side of the binary market a token settles on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Up,
+    Down,
+}
+
+impl Outcome {
+    /// The other side of the market
+    pub fn opposite(&self) -> Self {
+        match self {
+            Outcome::Up => Outcome::Down,
+            Outcome::Down => Outcome::Up,
+        }
+    }
+
+    /// Log label
+    pub fn label(&self) -> &'static str {
+        match self {
+            Outcome::Up => "UP",
+            Outcome::Down => "DOWN",
+        }
+    }
+}
+
+/// Top of book and depth of one token
+#[derive(Debug, Clone)]
+pub struct TokenSnapshot {
+    pub token_id: String,
+    pub bid: f64,
+    pub ask: f64,
+    pub book: Option<L2OrderBook>,
+    /// Shares held
+    pub shares: f64,
+    /// Spot-implied fair price, when the fair value model has one
+    pub fair: Option<f64>,
+    /// Realized volatility of the mid over the last few minutes
+    pub volatility: Option<RealizedVol>,
+}
+
+impl TokenSnapshot {
+    /// Mid price
+    pub fn mid(&self) -> f64 {
+        (self.bid + self.ask) / 2.0
+    }
+}
+
+/// Everything a strategy may look at for one market
+#[derive(Debug, Clone)]
+pub struct MarketSnapshot {
+    pub market: SeriesMarket,
+    pub up: TokenSnapshot,
+    pub down: TokenSnapshot,
+    pub inventory: InventoryStatus,
+    /// -1 (all DOWN) to 1 (all UP)
+    pub inventory_skew: f64,
+    pub seconds_to_expiry: i64,
+    /// Free USDC
+    pub balance: f64,
+    /// Shared position budget still available
+    pub budget_remaining: f64,
+    /// Mean short-horizon markout of this series' latest fills; negative when flow turned toxic
+    pub recent_markout: Option<f64>,
+}
+
+impl MarketSnapshot {
+    /// Token of an outcome
+    pub fn token(&self, outcome: Outcome) -> &TokenSnapshot {
+        match outcome {
+            Outcome::Up => &self.up,
+            Outcome::Down => &self.down,
+        }
+    }
+}
+
+/// An order a strategy wants resting
+#[derive(Debug, Clone, PartialEq)]
+pub struct DesiredQuote {
+    pub outcome: Outcome,
+    pub token_id: String,
+    pub side: Side,
+    pub price: f64,
+    pub size: f64,
+}
+
+impl DesiredQuote {
+    /// BUY quote on an outcome's token
+    pub fn buy(snapshot: &MarketSnapshot, outcome: Outcome, price: f64, size: f64) -> Self {
+        Self {
+            outcome,
+            token_id: snapshot.token(outcome).token_id.clone(),
+            side: Side::Buy,
+            price,
+            size,
+        }
+    }
+
+    /// SELL quote on an outcome's token
+    pub fn sell(snapshot: &MarketSnapshot, outcome: Outcome, price: f64, size: f64) -> Self {
+        Self {
+            side: Side::Sell,
+            ..Self::buy(snapshot, outcome, price, size)
+        }
+    }
+}
+
+/// Quoting strategy, shared by all market tasks
+pub trait Strategy: Send + Sync {
+    /// Registry name
+    fn name(&self) -> &str;
+
+    /// Quotes to rest for this cycle; empty to stay out
+    fn quote(&self, snapshot: &MarketSnapshot, config: &TradingConfig) -> Vec<DesiredQuote>;
+}
+
+/// Round to the 0.01 tick
+pub fn round_to_tick(price: f64) -> f64 {
+    (price * 100.0).round() / 100.0
+}
+
+/// USDC needed for the BUY quotes, including the balance buffer
+pub fn buy_notional(quotes: &[DesiredQuote]) -> f64 {
+    quotes
+        .iter()
+        .filter(|q| q.side == Side::Buy)
+        .map(|q| q.price * q.size)
+        .sum::<f64>()
+        * BALANCE_BUFFER_RATIO
+}
+
+/// Sell size allowed by held shares, on the 0.01 share step
+pub fn sellable_size(shares: f64, order_size: f64) -> f64 {
+    (shares.min(order_size) * 100.0).floor() / 100.0
+}
+
+/// Four-order market making: bid and ask on both outcomes, skewed by inventory
+///
+/// Prices center on the spot-implied fair value when available, else on the live book depth
+/// (spread clamped to min/max_spread, imbalance above `imbalance_threshold` leans the quotes).
+/// Bids are gated by inventory skew and the shared budget; asks are sized from held shares only.
+/// With the `avellaneda_stoikov` quote model, inventory moves the reservation price and the
+/// spread instead, and bids stop once a token's net position reaches `max_position`.
+/// When recent fills marked out against us, both sides back off by that much (up to `max_spread`).
+#[derive(Debug, Default)]
+pub struct MarketMakerStrategy;
+
+impl Strategy for MarketMakerStrategy {
+    fn name(&self) -> &str {
+        "market_maker"
+    }
+
+    fn quote(&self, snapshot: &MarketSnapshot, config: &TradingConfig) -> Vec<DesiredQuote> {
+        let skew = snapshot.inventory_skew;
+        let base_size = config.order_size;
+        let remaining = snapshot.budget_remaining;
+        let (up_size, down_size) = match config.quote_model {
+            QuoteModel::Linear => Self::linear_sizes(skew, base_size, remaining),
+            QuoteModel::AvellanedaStoikov => {
+                let size = |outcome| {
+                    if Self::net_shares(snapshot, outcome) >= config.max_position {
+                        warn!("⚠️ {} position at max_position, not bidding", outcome.label());
+                        0.0
+                    } else {
+                        base_size.min(remaining / 2.0)
+                    }
+                };
+                (size(Outcome::Up), size(Outcome::Down))
+            }
+        };
+
+        // Balance is checked at the mid; need both sides for the hedge
+        let need = (snapshot.up.mid() * up_size + snapshot.down.mid() * down_size) * BALANCE_BUFFER_RATIO;
+        let can_buy = snapshot.balance >= need;
+        if !can_buy {
+            warn!("⚠️ Insufficient balance (with buffer): {:.2} < {:.2}, quoting asks only", snapshot.balance, need);
+        }
+
+        let widen = Self::markout_widening(snapshot.recent_markout, config);
+        if widen > 0.0 {
+            warn!("⚠️ Recent markout {:.4} is adverse, widening quotes by {:.2}",
+                snapshot.recent_markout.unwrap_or(0.0), widen);
+        }
+
+        let mut quotes = Vec::new();
+        for (outcome, buy_size) in [(Outcome::Up, up_size), (Outcome::Down, down_size)] {
+            let token = snapshot.token(outcome);
+            let prices = match config.quote_model {
+                QuoteModel::Linear => self.prices(token, skew, config),
+                QuoteModel::AvellanedaStoikov => self.stoikov_prices(snapshot, outcome, config),
+            };
+            let Some((bid, ask)) = prices else {
+                warn!("⚠️ Not quoting {}: no valid bid/ask", outcome.label());
+                continue;
+            };
+            let bid = round_to_tick(bid - widen);
+            let ask = round_to_tick(ask + widen).min(config.safe_range_high).max(ask);
+            if can_buy && buy_size > 0.0 && bid >= config.safe_range_low {
+                quotes.push(DesiredQuote::buy(snapshot, outcome, bid, buy_size));
+            }
+            // Asks only against shares we hold
+            let size = sellable_size(token.shares, base_size);
+            if size > 0.0 {
+                quotes.push(DesiredQuote::sell(snapshot, outcome, ask, size));
+            }
+        }
+        quotes
+    }
+}
+
+impl MarketMakerStrategy {
+    /// BUY sizes under the linear model: the heavier side stops buying past the skew threshold
+    fn linear_sizes(skew: f64, base_size: f64, remaining: f64) -> (f64, f64) {
+        // Python: max_skew = order_size * 0.4 (normalized to ~0.4)
+        let max_skew = 0.4;
+        if skew > max_skew {
+            // UP too much, only buy DOWN
+            warn!("⚠️ UP skew too high ({:.1}), buying only DOWN to balance", skew);
+            (0.0, base_size.min(remaining))
+        } else if skew < -max_skew {
+            // DOWN too much, only buy UP
+            warn!("⚠️ DOWN skew too high ({:.1}), buying only UP to balance", skew.abs());
+            (base_size.min(remaining), 0.0)
+        } else {
+            // Balanced, buy both
+            (base_size.min(remaining / 2.0), base_size.min(remaining / 2.0))
+        }
+    }
+
+    /// How far to back both sides off after adverse recent markouts, whole ticks up to max_spread
+    fn markout_widening(recent_markout: Option<f64>, config: &TradingConfig) -> f64 {
+        match recent_markout {
+            Some(markout) if markout < 0.0 => {
+                let ticks = ((-markout).min(config.max_spread) * 100.0 - 1e-9).ceil();
+                ticks / 100.0
+            }
+            _ => 0.0,
+        }
+    }
+
+    /// Shares of an outcome not hedged by the opposite outcome (negative when short of a pair)
+    fn net_shares(snapshot: &MarketSnapshot, outcome: Outcome) -> f64 {
+        snapshot.token(outcome).shares - snapshot.token(outcome.opposite()).shares
+    }
+
+    /// Bid/ask for one token from the Avellaneda-Stoikov reservation price and optimal spread
+    fn stoikov_prices(&self, snapshot: &MarketSnapshot, outcome: Outcome, config: &TradingConfig) -> Option<(f64, f64)> {
+        let token = snapshot.token(outcome);
+        let params = &config.avellaneda_stoikov;
+        let mid = token.fair.unwrap_or_else(|| token.mid());
+        // Log-return volatility to price units at the current mid
+        let volatility = token
+            .volatility
+            .map(|vol| vol.per_second() * mid)
+            .unwrap_or(params.fallback_volatility);
+        let inventory = Self::net_shares(snapshot, outcome) / config.order_size;
+        let raw = params.quote(mid, inventory, volatility, snapshot.seconds_to_expiry as f64);
+        debug!("A-S {}: reservation {:.4}, spread {:.4} (q={:.2}, sigma={:.5})",
+            outcome.label(), raw.reservation, raw.spread, inventory, volatility);
+        let quote = StoikovQuote { spread: raw.spread.clamp(config.min_spread, config.max_spread), ..raw };
+        // Stay passive: never cross the touch
+        let bid = round_to_tick(quote.bid()).min(round_to_tick(token.ask - 0.01));
+        let ask = round_to_tick(quote.ask()).max(round_to_tick(token.bid + 0.01));
+        let bid = bid.max(config.safe_range_low);
+        let ask = ask.min(config.safe_range_high);
+        (bid < ask).then_some((bid, ask))
+    }
+
+    /// Bid/ask for one token: around the fair value, else from book depth, else mid -/+ spread/2
+    fn prices(&self, token: &TokenSnapshot, skew: f64, config: &TradingConfig) -> Option<(f64, f64)> {
+        let depth = token
+            .book
+            .as_ref()
+            .and_then(|book| book.depth(config.order_size, config.depth_lookback as usize));
+        let spread = config.spread.clamp(config.min_spread, config.max_spread);
+        let (bid, ask) = match (token.fair, depth) {
+            (Some(fair), _) => {
+                let (bid, ask) = Self::around(token, fair + skew * 0.01, spread, config)?;
+                // Stay passive: never cross the touch
+                (bid.min(round_to_tick(token.ask - 0.01)), ask.max(round_to_tick(token.bid + 0.01)))
+            }
+            (None, Some(mut depth)) => {
+                // Small imbalances are noise
+                if depth.imbalance.abs() < config.imbalance_threshold {
+                    depth.imbalance = 0.0;
+                }
+                let (bid, ask) = calculate_mm_prices(&depth, skew, config.min_spread, config.max_spread);
+                // Stay passive: never cross the touch
+                let bid = round_to_tick(bid).min(round_to_tick(depth.best_ask.price - 0.01));
+                let ask = round_to_tick(ask).max(round_to_tick(depth.best_bid.price + 0.01));
+                (bid, ask)
+            }
+            (None, None) => {
+                debug!("Thin book for {}, quoting around the mid", token.token_id);
+                // Python: bid/ask = mid -/+ half_spread + inventory_skew * 0.01
+                Self::around(token, token.mid() + skew * 0.01, spread, config)?
+            }
+        };
+        let bid = bid.max(config.safe_range_low);
+        let ask = ask.min(config.safe_range_high);
+        (bid < ask).then_some((bid, ask))
+    }
+
+    /// Bid/ask at center -/+ spread/2 on the tick
+    fn around(token: &TokenSnapshot, center: f64, spread: f64, config: &TradingConfig) -> Option<(f64, f64)> {
+        let maker = MarketMaker::new(token.token_id.clone(), MarketMakerConfig::from(config));
+        let orders = maker.generate_orders(center, spread).ok()?;
+        let price = |side: Side| orders.iter().find(|o| o.side == side).map(|o| o.price);
+        Some((price(Side::Buy)?, price(Side::Sell)?))
+    }
+}
+
+/// Buys both outcomes at the UP book (UP at its bid, DOWN at 1 - UP ask) and holds to settlement
+#[derive(Debug, Default)]
+pub struct BuyHoldStrategy;
+
+impl BuyHoldStrategy {
+    /// No orders outside this band (no orders in 0-0.1 or 0.9-1.0)
+    const EXTREME_MIN: f64 = 0.10;
+    const EXTREME_MAX: f64 = 0.90;
+}
+
+impl Strategy for BuyHoldStrategy {
+    fn name(&self) -> &str {
+        "buy_hold"
+    }
+
+    fn quote(&self, snapshot: &MarketSnapshot, config: &TradingConfig) -> Vec<DesiredQuote> {
+        // Python logic: UP price = bid, DOWN price = 1.0 - UP_ask (not DOWN_ask)
+        let up_price = round_to_tick(snapshot.up.bid);
+        let down_price = round_to_tick(1.0 - snapshot.up.ask);
+        let in_band = |p: f64| (Self::EXTREME_MIN..=Self::EXTREME_MAX).contains(&p);
+        if !in_band(up_price) || !in_band(down_price) {
+            warn!("⚠️ Extreme prices detected: UP={}, DOWN={}, skipping all orders", up_price, down_price);
+            return Vec::new();
+        }
+
+        // Skew in dollars; Python uses 0.6 (not 0.4)
+        let skew = snapshot.inventory.up_value - snapshot.inventory.down_value;
+        let max_skew = config.order_size * 0.6;
+        let size = config.order_size.min(snapshot.budget_remaining);
+        let (up_size, down_size) = if skew > max_skew {
+            warn!("⚠️ UP skew too high ({}), buying only DOWN", skew);
+            (0.0, size)
+        } else if skew < -max_skew {
+            warn!("⚠️ DOWN skew too high ({}), buying only UP", skew.abs());
+            (size, 0.0)
+        } else {
+            // Python: both sides buy base_size, not split
+            (size, size)
+        };
+
+        let quotes: Vec<DesiredQuote> = [(Outcome::Up, up_price, up_size), (Outcome::Down, down_price, down_size)]
+            .into_iter()
+            .filter(|(_, _, size)| *size > 0.0)
+            .map(|(outcome, price, size)| DesiredQuote::buy(snapshot, outcome, price, size))
+            .collect();
+
+        let need = buy_notional(&quotes);
+        if snapshot.balance < need {
+            warn!("⚠️ Insufficient balance: {} < {}", snapshot.balance, need);
+            return Vec::new();
+        }
+        quotes
+    }
+}
+
+/// Strategies by name; `strategy_mode` selects one
+pub struct StrategyRegistry {
+    strategies: HashMap<String, Arc<dyn Strategy>>,
+}
+
+impl Default for StrategyRegistry {
+    /// Registry with the built-in strategies
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry.register(Arc::new(MarketMakerStrategy));
+        registry.register(Arc::new(BuyHoldStrategy));
+        registry
+    }
+}
+
+impl StrategyRegistry {
+    /// Registry without any strategy
+    pub fn empty() -> Self {
+        Self {
+            strategies: HashMap::new(),
+        }
+    }
+
+    /// Register a strategy under its name, replacing any previous one
+    pub fn register(&mut self, strategy: Arc<dyn Strategy>) {
+        self.strategies.insert(strategy.name().to_string(), strategy);
+    }
+
+    /// Strategy by name
+    pub fn get(&self, name: &str) -> Option<Arc<dyn Strategy>> {
+        self.strategies.get(name).cloned()
+    }
+
+    /// Registered names, sorted
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.strategies.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::gamma::Market;
+    use crate::config::Config;
+
+    fn snapshot(up: (f64, f64), down: (f64, f64), skew: f64) -> MarketSnapshot {
+        let token = |id: &str, (bid, ask): (f64, f64)| TokenSnapshot {
+            token_id: id.to_string(),
+            bid,
+            ask,
+            book: None,
+            shares: 0.0,
+            fair: None,
+            volatility: None,
+        };
+        MarketSnapshot {
+            market: SeriesMarket {
+                series: "btc-updown-5m".to_string(),
+                slot_start: 0,
+                slot_seconds: 300,
+                slug: "btc-updown-5m-0".to_string(),
+                condition_id: "0xc".to_string(),
+                up_token: "1".to_string(),
+                down_token: "2".to_string(),
+                end_time: chrono::Utc::now(),
+                market: Market::default(),
+            },
+            up: token("1", up),
+            down: token("2", down),
+            inventory: InventoryStatus {
+                up_value: 0.0,
+                down_value: 0.0,
+                total_value: 0.0,
+                skew,
+                is_balanced: true,
+                recommendation: String::new(),
+            },
+            inventory_skew: skew,
+            seconds_to_expiry: 200,
+            balance: 100.0,
+            budget_remaining: 30.0,
+            recent_markout: None,
+        }
+    }
+
+    #[test]
+    fn test_market_maker_quotes() {
+        let config = Config::default().trading;
+        let strategy = MarketMakerStrategy;
+
+        // Flat inventory: bids only, no shares to offer
+        let quotes = strategy.quote(&snapshot((0.54, 0.56), (0.44, 0.46), 0.0), &config);
+        assert_eq!(quotes.len(), 2);
+        assert!(quotes.iter().all(|q| q.side == Side::Buy));
+        assert_eq!(quotes[0].outcome, Outcome::Up);
+        assert!((quotes[0].price - 0.54).abs() < 1e-9);
+        assert!((quotes[1].price - 0.44).abs() < 1e-9);
+
+        // Heavy UP inventory: only DOWN is bought
+        let quotes = strategy.quote(&snapshot((0.54, 0.56), (0.44, 0.46), 0.6), &config);
+        assert_eq!(quotes.iter().filter(|q| q.side == Side::Buy).count(), 1);
+        assert_eq!(quotes[0].outcome, Outcome::Down);
+
+        // Not enough balance for the hedge: no bids
+        let mut broke = snapshot((0.54, 0.56), (0.44, 0.46), 0.0);
+        broke.balance = 0.5;
+        assert!(strategy.quote(&broke, &config).is_empty());
+    }
+
+    #[test]
+    fn test_market_maker_asks_sized_from_inventory() {
+        let config = Config::default().trading;
+        let mut held = snapshot((0.54, 0.56), (0.44, 0.46), 0.0);
+        held.up.shares = 0.4;
+        held.down.shares = 3.0;
+
+        let quotes = MarketMakerStrategy.quote(&held, &config);
+        assert_eq!(quotes.len(), 4);
+        let asks: Vec<&DesiredQuote> = quotes.iter().filter(|q| q.side == Side::Sell).collect();
+        assert_eq!(asks[0].outcome, Outcome::Up);
+        assert!((asks[0].price - 0.56).abs() < 1e-9);
+        // Never more than held, never more than order_size
+        assert!((asks[0].size - 0.4).abs() < 1e-9);
+        assert!((asks[1].size - config.order_size).abs() < 1e-9);
+        assert!((asks[1].price - 0.46).abs() < 1e-9);
+
+        // Asks stay up even when bids are unaffordable
+        held.balance = 0.0;
+        let quotes = MarketMakerStrategy.quote(&held, &config);
+        assert!(quotes.len() == 2 && quotes.iter().all(|q| q.side == Side::Sell));
+    }
+
+    #[test]
+    fn test_market_maker_prices_from_book_depth() {
+        use crate::trading::orderbook::OrderBookLevel;
+        let config = Config::default().trading;
+        let level = |price, size| OrderBookLevel { price, size };
+        let mut book = L2OrderBook::new("1");
+        book.apply_snapshot(
+            &[level(0.52, 100.0), level(0.51, 200.0)],
+            &[level(0.58, 150.0), level(0.59, 100.0)],
+        );
+
+        // Top of book says 0.40/0.42 but the book is authoritative; its 0.06 spread is clamped to max_spread
+        let mut snap = snapshot((0.40, 0.42), (0.44, 0.46), 0.0);
+        snap.up.book = Some(book.clone());
+        let quotes = MarketMakerStrategy.quote(&snap, &config);
+        assert!((quotes[0].price - 0.54).abs() < 1e-9);
+
+        // A single level per side is too thin: fall back to the mid
+        let mut thin = L2OrderBook::new("1");
+        thin.apply_snapshot(&[level(0.52, 100.0)], &[level(0.58, 150.0)]);
+        snap.up.book = Some(thin);
+        let quotes = MarketMakerStrategy.quote(&snap, &config);
+        assert!((quotes[0].price - 0.40).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_market_maker_anchors_on_fair_value() {
+        let config = Config::default().trading;
+        // Book mid 0.55 but spot says UP is worth 0.60; DOWN 0.40
+        let mut snap = snapshot((0.50, 0.60), (0.40, 0.50), 0.0);
+        snap.up.fair = Some(0.60);
+        snap.down.fair = Some(0.40);
+        snap.down.shares = 5.0;
+        let quotes = MarketMakerStrategy.quote(&snap, &config);
+        assert!((quotes[0].price - 0.59).abs() < 1e-9);
+        // DOWN quoted around its own fair value, not its 0.45 mid
+        assert!((quotes[1].price - 0.39).abs() < 1e-9);
+        assert!((quotes[2].price - 0.41).abs() < 1e-9);
+
+        // A fair value through the touch is capped one tick inside it
+        snap.up.fair = Some(0.75);
+        let quotes = MarketMakerStrategy.quote(&snap, &config);
+        assert!((quotes[0].price - 0.59).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_market_maker_widens_on_adverse_markout() {
+        let config = Config::default().trading;
+        let mut snap = snapshot((0.54, 0.56), (0.44, 0.46), 0.0);
+        snap.up.shares = 5.0;
+        let price = |quotes: &[DesiredQuote], side| {
+            quotes.iter().find(|q| q.outcome == Outcome::Up && q.side == side).unwrap().price
+        };
+
+        // Good recent flow leaves the quotes alone
+        snap.recent_markout = Some(0.01);
+        let quotes = MarketMakerStrategy.quote(&snap, &config);
+        assert!((price(&quotes, Side::Buy) - 0.54).abs() < 1e-9);
+        assert!((price(&quotes, Side::Sell) - 0.56).abs() < 1e-9);
+
+        // Fills lost 1.5c on average: both sides back off by two ticks
+        snap.recent_markout = Some(-0.015);
+        let quotes = MarketMakerStrategy.quote(&snap, &config);
+        assert!((price(&quotes, Side::Buy) - 0.52).abs() < 1e-9);
+        assert!((price(&quotes, Side::Sell) - 0.58).abs() < 1e-9);
+
+        // Never more than max_spread
+        snap.recent_markout = Some(-0.5);
+        let quotes = MarketMakerStrategy.quote(&snap, &config);
+        let widen = (config.max_spread * 100.0).round() / 100.0;
+        assert!((price(&quotes, Side::Buy) - (0.54 - widen)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_market_maker_avellaneda_stoikov() {
+        let mut config = Config::default().trading;
+        config.quote_model = QuoteModel::AvellanedaStoikov;
+        config.max_spread = 0.04;
+
+        // Flat: symmetric around the mid
+        let flat = MarketMakerStrategy.quote(&snapshot((0.45, 0.55), (0.45, 0.55), 0.0), &config);
+        assert_eq!(flat.len(), 2);
+        let flat_bid = flat[0].price;
+
+        // Long UP: UP quotes lean down (cheaper ask to shed, lower bid), DOWN quotes lean up
+        let mut long = snapshot((0.45, 0.55), (0.45, 0.55), 0.0);
+        long.up.shares = 4.0;
+        config.avellaneda_stoikov.fallback_volatility = 0.01;
+        let quotes = MarketMakerStrategy.quote(&long, &config);
+        let up_bid = quotes.iter().find(|q| q.outcome == Outcome::Up && q.side == Side::Buy).unwrap();
+        let down_bid = quotes.iter().find(|q| q.outcome == Outcome::Down && q.side == Side::Buy).unwrap();
+        assert!(up_bid.price < flat_bid && down_bid.price > flat_bid);
+
+        // At max_position the long side stops bidding
+        long.up.shares = config.max_position;
+        let quotes = MarketMakerStrategy.quote(&long, &config);
+        assert!(!quotes.iter().any(|q| q.outcome == Outcome::Up && q.side == Side::Buy));
+    }
+
+    #[test]
+    fn test_buy_hold_prices_from_up_book() {
+        let config = Config::default().trading;
+        let quotes = BuyHoldStrategy.quote(&snapshot((0.54, 0.56), (0.40, 0.50), 0.0), &config);
+        assert!((quotes[0].price - 0.54).abs() < 1e-9);
+        assert!((quotes[1].price - 0.44).abs() < 1e-9);
+
+        // Outside [0.10, 0.90] nothing is quoted
+        assert!(BuyHoldStrategy.quote(&snapshot((0.93, 0.95), (0.05, 0.07), 0.0), &config).is_empty());
+    }
+
+    #[test]
+    fn test_registry_lookup() {
+        struct Idle;
+        impl Strategy for Idle {
+            fn name(&self) -> &str {
+                "idle"
+            }
+            fn quote(&self, _: &MarketSnapshot, _: &TradingConfig) -> Vec<DesiredQuote> {
+                Vec::new()
+            }
+        }
+
+        let mut registry = StrategyRegistry::default();
+        registry.register(Arc::new(Idle));
+        assert_eq!(registry.names(), vec!["buy_hold", "idle", "market_maker"]);
+        assert_eq!(registry.get("market_maker").unwrap().name(), "market_maker");
+        assert!(registry.get("grid").is_none());
+    }
+}
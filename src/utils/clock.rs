@@ -0,0 +1,73 @@
+//! Server-time-corrected clock
+//! Slot boundaries are defined by the exchange, so local drift must not move them
+
+use chrono::{DateTime, Utc};
+use std::sync::atomic::{AtomicI64, Ordering};
+use tokio::time::{Duration, Instant};
+
+/// Local clock corrected by the offset to the CLOB server time
+#[derive(Debug, Default)]
+pub struct ServerClock {
+    /// server - local, in milliseconds
+    offset_ms: AtomicI64,
+}
+
+impl ServerClock {
+    /// Create clock with no correction
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Update the offset from a server timestamp (seconds) fetched between `sent` and `received`
+    ///
+    /// The server is assumed to have stamped the response halfway through the round trip.
+    /// Returns the new offset in milliseconds.
+    pub fn sync(&self, server_secs: u64, sent: DateTime<Utc>, received: DateTime<Utc>) -> i64 {
+        let local_mid = sent.timestamp_millis() + (received - sent).num_milliseconds() / 2;
+        // `/time` has 1s resolution; assume the middle of that second
+        let server_ms = server_secs as i64 * 1000 + 500;
+        let offset = server_ms - local_mid;
+        self.offset_ms.store(offset, Ordering::Relaxed);
+        offset
+    }
+
+    /// Current offset (server - local) in milliseconds
+    pub fn offset_ms(&self) -> i64 {
+        self.offset_ms.load(Ordering::Relaxed)
+    }
+
+    /// Current server time
+    pub fn now(&self) -> DateTime<Utc> {
+        Utc::now() + chrono::Duration::milliseconds(self.offset_ms())
+    }
+
+    /// Local tokio instant at which the server clock reaches `at` (now if already past)
+    pub fn instant_at(&self, at: DateTime<Utc>) -> Instant {
+        let wait = (at - self.now()).to_std().unwrap_or(Duration::ZERO);
+        Instant::now() + wait
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sync_offset() {
+        let clock = ServerClock::new();
+        let sent = DateTime::from_timestamp_millis(1_760_000_000_000).unwrap();
+        let received = sent + chrono::Duration::milliseconds(200);
+
+        // Server runs 2s ahead of the local clock
+        let offset = clock.sync(1_760_000_002, sent, received);
+        assert_eq!(offset, 2_400);
+        assert_eq!(clock.offset_ms(), 2_400);
+
+        let drift = (clock.now() - Utc::now()).num_milliseconds();
+        assert!((2_390..=2_410).contains(&drift));
+
+        // A boundary already passed on the server clock fires immediately
+        let past = clock.now() - chrono::Duration::seconds(1);
+        assert!(clock.instant_at(past) <= Instant::now());
+    }
+}
@@ -2,6 +2,8 @@
 
 pub mod retry;
 pub mod rate_limiter;
+pub mod clock;
 
 pub use retry::{retry, retry_with_backoff, RetryConfig};
-pub use rate_limiter::RateLimiter;
\ No newline at end of file
+pub use rate_limiter::RateLimiter;
+pub use clock::ServerClock;
\ No newline at end of file
@@ -46,6 +46,11 @@ impl SafeWallet {
         self.nonce
     }
     
+    /// Sync nonce with the on-chain value
+    pub fn set_nonce(&mut self, nonce: u64) {
+        self.nonce = nonce;
+    }
+    
     /// Increment nonce after transaction
     pub fn increment_nonce(&mut self) {
         self.nonce += 1;
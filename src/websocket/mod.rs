@@ -2,23 +2,37 @@
 
 use futures::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
-use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, mpsc, Mutex, RwLock};
+use tokio::task::JoinHandle;
 use tokio::time::{interval, Duration};
 use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, warn};
 
+use crate::trading::orderbook::{BookSide, L2OrderBook, OrderBookDepth, OrderBookLevel};
+use polymarket_client_sdk::types::U256;
+
+pub mod spot;
+pub mod user;
+
+pub use spot::{spot_adapter, BinanceAdapter, SpotFeedAdapter, SpotReplay, SpotTick, SpotWebSocket};
+pub use user::{UserChannelAuth, UserEvent, UserOrderEvent, UserOrderEventType, UserTradeEvent, UserWebSocket};
+
 const WS_TIMEOUT_SECONDS: u64 = 30;
-const WS_RECONNECT_DELAY: u64 = 5;
+const WS_RECONNECT_BASE_DELAY_MS: u64 = 1_000;
+const WS_RECONNECT_MAX_DELAY_MS: u64 = 60_000;
 const WS_PING_INTERVAL: u64 = 5;
+const WS_SHUTDOWN_TIMEOUT: u64 = 5;
 const DISPLAY_INTERVAL: f64 = 1.0; // 每1秒打印一次价格（实时更新）
 #[allow(dead_code)]
 const MAX_CACHE_SIZE: usize = 1000;
+/// 行情事件广播缓冲（慢消费者超出后会收到 Lagged）
+const MARKET_EVENT_CAPACITY: usize = 1024;
 
 /// WebSocket URL
-const MARKET_WS_URL: &str = "wss://ws-subscriptions-clob.polymarket.com/ws/market";
+pub(crate) const MARKET_WS_URL: &str = "wss://ws-subscriptions-clob.polymarket.com/ws/market";
 
 /// 订阅消息
 #[derive(Debug, Clone, Serialize)]
@@ -27,14 +41,101 @@ struct SubscribeMessage {
     asset_ids: Vec<String>,
 }
 
-/// 订单簿条目
-#[derive(Debug, Clone, Deserialize)]
-#[allow(dead_code)]
-struct OrderBookEntry {
-    #[allow(dead_code)]
-    price: String,
-    #[allow(dead_code)]
-    size: String,
+/// 动态订阅/退订消息（在已建立的连接上发送）
+#[derive(Debug, Clone, Serialize)]
+struct SubscriptionUpdate<'a> {
+    #[serde(rename = "assets_ids")]
+    asset_ids: &'a [String],
+    operation: &'a str,
+}
+
+/// 发给连接任务的订阅指令
+#[derive(Debug, Clone)]
+enum WsCommand {
+    Subscribe(Vec<String>),
+    Unsubscribe(Vec<String>),
+}
+
+/// 正在运行的连接任务
+struct ConnectionTask {
+    handle: JoinHandle<()>,
+    commands: mpsc::UnboundedSender<WsCommand>,
+    cancel: CancellationToken,
+}
+
+/// 行情连接状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    /// 未启动或已停止
+    Disconnected,
+    Connecting,
+    Connected,
+    /// 连接断开，第 attempt 次重连将在 delay_ms 后开始
+    Reconnecting { attempt: u32, delay_ms: u64 },
+    /// 重连次数用尽（或未开启自动重连），连接任务已退出
+    GaveUp { attempts: u32 },
+}
+
+impl ConnectionState {
+    pub fn is_connected(&self) -> bool {
+        matches!(self, ConnectionState::Connected)
+    }
+}
+
+/// 重连策略 - 带抖动的指数退避
+#[derive(Debug, Clone)]
+pub struct ReconnectPolicy {
+    /// 断线后是否自动重连
+    pub auto_reconnect: bool,
+    /// 连续失败的最大重连次数（0 = 不限）
+    pub max_reconnect: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            auto_reconnect: true,
+            max_reconnect: 0,
+            base_delay: Duration::from_millis(WS_RECONNECT_BASE_DELAY_MS),
+            max_delay: Duration::from_millis(WS_RECONNECT_MAX_DELAY_MS),
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// 按 WebSocketConfig 的 auto_reconnect / max_reconnect 创建
+    pub fn new(auto_reconnect: bool, max_reconnect: u32) -> Self {
+        Self { auto_reconnect, max_reconnect, ..Self::default() }
+    }
+
+    /// 是否允许第 attempt 次重连（从 1 开始）
+    pub fn allows(&self, attempt: u32) -> bool {
+        self.auto_reconnect && (self.max_reconnect == 0 || attempt <= self.max_reconnect)
+    }
+
+    /// 第 attempt 次重连的等待时间：base * 2^(attempt-1)，封顶 max_delay，再取 [1/2, 1] 区间的随机抖动
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exp = attempt.saturating_sub(1).min(16);
+        let capped = self.base_delay.saturating_mul(1 << exp).min(self.max_delay);
+        let half = capped / 2;
+        let jitter = rand::random::<f64>() * half.as_secs_f64();
+        half + Duration::from_secs_f64(jitter)
+    }
+}
+
+/// 连接任务共享的缓存与统计
+#[derive(Clone)]
+struct FeedState {
+    subscribed_tokens: Arc<RwLock<Vec<String>>>,
+    quotes: Arc<RwLock<HashMap<String, Quote>>>,
+    books: Arc<RwLock<HashMap<String, L2OrderBook>>>,
+    last_display_time: Arc<RwLock<f64>>,
+    messages_received: Arc<RwLock<u64>>,
+    token_labels: Arc<RwLock<HashMap<String, String>>>,
+    events: broadcast::Sender<MarketEvent>,
+    connection_state: Arc<RwLock<ConnectionState>>,
 }
 
 /// Book 事件格式
@@ -54,12 +155,31 @@ struct PriceChangeEntry {
     #[serde(rename = "asset_id")]
     asset_id: String,
     price: String,
+    /// 该价位的新总量（0 = 删除）
     size: String,
+    /// BUY = bid 侧, SELL = ask 侧
+    #[serde(default)]
+    side: Option<String>,
+}
+
+/// 最新成交消息格式
+#[derive(Debug, Clone, Deserialize)]
+struct LastTradePriceEvent {
+    #[serde(rename = "event_type")]
+    event_type: String,
+    #[serde(rename = "asset_id")]
+    asset_id: String,
+    price: String,
+    #[serde(default)]
+    size: Option<String>,
+    #[serde(default)]
+    side: Option<String>,
 }
 
 /// Price changes 消息格式
 #[derive(Debug, Clone, Deserialize)]
 struct PriceChangesEvent {
+    #[allow(dead_code)]
     market: String,
     #[serde(rename = "price_changes")]
     price_changes: Vec<PriceChangeEntry>,
@@ -74,41 +194,148 @@ pub struct PriceUpdate {
     pub timestamp: chrono::DateTime<chrono::Utc>,
 }
 
+impl PriceUpdate {
+    /// 中间价
+    pub fn mid(&self) -> f64 {
+        (self.bid + self.ask) / 2.0
+    }
+}
+
+/// 订单簿变化
+#[derive(Debug, Clone)]
+pub struct BookUpdate {
+    pub token_id: String,
+    /// true = 完整快照, false = price_change 增量
+    pub snapshot: bool,
+    pub book: L2OrderBook,
+}
+
+/// 市场成交（last_trade_price）
+#[derive(Debug, Clone)]
+pub struct TradeUpdate {
+    pub token_id: String,
+    pub price: f64,
+    pub size: f64,
+    /// 主动方 BUY/SELL
+    pub side: Option<String>,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+}
+
+/// 行情事件 - 通过 `PolymarketWebSocket::subscribe` 接收
+#[derive(Debug, Clone)]
+pub enum MarketEvent {
+    /// 最优买卖价变化
+    Price(PriceUpdate),
+    Book(BookUpdate),
+    Trade(TradeUpdate),
+    /// 连接状态变化（断线期间应撤单）
+    Connection(ConnectionState),
+}
+
+/// 单个资产的最新报价（来自 L2 订单簿最优档）
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quote {
+    pub bid: Option<f64>,
+    pub ask: Option<f64>,
+    pub bid_size: f64,
+    pub ask_size: f64,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+}
+
+impl Quote {
+    /// 双边都有报价时返回 (bid, ask)
+    pub fn bid_ask(&self) -> Option<(f64, f64)> {
+        Some((self.bid?, self.ask?))
+    }
+}
+
+/// 规范化 asset ID：十进制原样返回，0x 十六进制转为十进制，其它格式返回 None
+fn normalize_asset_id(id: &str) -> Option<String> {
+    if !id.is_empty() && id.bytes().all(|b| b.is_ascii_digit()) {
+        return Some(id.to_string());
+    }
+    let hex = id.strip_prefix("0x")?;
+    U256::from_str_radix(hex, 16).ok().map(|v| v.to_string())
+}
+
+/// 按完整 asset ID 查找，格式不符或疑似被截断时记录警告
+fn lookup_asset<'a, T>(map: &'a HashMap<String, T>, token_id: &str) -> Option<&'a T> {
+    let Some(id) = normalize_asset_id(token_id) else {
+        warn!("⚠️ Asset ID format mismatch: {:?} is not a decimal or 0x token ID", token_id);
+        return None;
+    };
+    if let Some(value) = map.get(&id) {
+        return Some(value);
+    }
+    if let Some(other) = map.keys().find(|k| k.starts_with(&id) || id.starts_with(k.as_str())) {
+        warn!("⚠️ Asset ID mismatch: requested {} ({} chars) but cache has {} ({} chars)",
+            id, id.len(), other, other.len());
+    }
+    None
+}
+
 /// Polymarket WebSocket 客户端 - 复刻 Python 版本
 pub struct PolymarketWebSocket {
+    /// 市场频道 WebSocket 地址
+    url: String,
     /// 当前订阅的 token IDs
     subscribed_tokens: Arc<RwLock<Vec<String>>>,
     /// Token 标签映射 (token_id -> "UP"/"DOWN")
     token_labels: Arc<RwLock<HashMap<String, String>>>,
-    /// 最新价格缓存 (token_id_bid/ask -> price)
-    last_prices: Arc<RwLock<HashMap<String, f64>>>,
-    /// 消息计数器（用于轮询 token）
-    msg_counter: Arc<RwLock<usize>>,
-    /// 运行状态
-    running: Arc<RwLock<bool>>,
+    /// 最新报价缓存 (完整 asset_id -> quote)
+    quotes: Arc<RwLock<HashMap<String, Quote>>>,
+    /// L2 订单簿 (完整 asset_id -> book)；同时持有时先锁 books 再锁 quotes
+    books: Arc<RwLock<HashMap<String, L2OrderBook>>>,
     /// 最后显示时间
     last_display_time: Arc<RwLock<f64>>,
     /// 统计
     messages_received: Arc<RwLock<u64>>,
-    /// 重启标志 - 用于市场切换时重新连接
-    restart_flag: Arc<RwLock<bool>>,
+    /// 唯一的连接任务（未启动或已停止时为 None）
+    connection: Mutex<Option<ConnectionTask>>,
+    /// 行情事件广播
+    events: broadcast::Sender<MarketEvent>,
+    /// 连接状态
+    connection_state: Arc<RwLock<ConnectionState>>,
+    /// 重连策略
+    reconnect_policy: ReconnectPolicy,
+}
+
+impl Default for PolymarketWebSocket {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl PolymarketWebSocket {
     /// 创建新的 WebSocket 客户端
     pub fn new() -> Self {
         Self {
+            url: MARKET_WS_URL.to_string(),
             subscribed_tokens: Arc::new(RwLock::new(Vec::new())),
             token_labels: Arc::new(RwLock::new(HashMap::new())),
-            last_prices: Arc::new(RwLock::new(HashMap::new())),
-            msg_counter: Arc::new(RwLock::new(0)),
-            running: Arc::new(RwLock::new(false)),
+            quotes: Arc::new(RwLock::new(HashMap::new())),
+            books: Arc::new(RwLock::new(HashMap::new())),
             last_display_time: Arc::new(RwLock::new(0.0)),
             messages_received: Arc::new(RwLock::new(0)),
-            restart_flag: Arc::new(RwLock::new(false)),
+            connection: Mutex::new(None),
+            events: broadcast::channel(MARKET_EVENT_CAPACITY).0,
+            connection_state: Arc::new(RwLock::new(ConnectionState::Disconnected)),
+            reconnect_policy: ReconnectPolicy::default(),
         }
     }
 
+    /// 使用自定义 WebSocket 地址（例如本地 mock 服务器）
+    pub fn with_url(mut self, url: &str) -> Self {
+        self.url = url.to_string();
+        self
+    }
+
+    /// 设置重连策略（来自 WebSocketConfig）
+    pub fn with_reconnect_policy(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect_policy = policy;
+        self
+    }
+
     /// 设置 token 标签
     pub async fn set_token_labels(&self, labels: HashMap<String, String>) {
         let mut token_labels = self.token_labels.write().await;
@@ -117,9 +344,10 @@ impl PolymarketWebSocket {
 
     /// 启动 WebSocket 连接
     pub async fn start(&self, token_ids: Vec<String>) {
-        {
-            let mut running = self.running.write().await;
-            *running = true;
+        if self.is_running().await {
+            warn!("WebSocket already running, updating subscription instead");
+            self.update_subscription(token_ids).await;
+            return;
         }
         {
             let mut tokens = self.subscribed_tokens.write().await;
@@ -127,193 +355,206 @@ impl PolymarketWebSocket {
         }
 
         info!("🚀 WebSocket client starting ({} markets)", token_ids.len());
+        self.spawn_connection().await;
+    }
 
-        // 克隆 Arc 用于任务
-        let subscribed_tokens = self.subscribed_tokens.clone();
-        let last_prices = self.last_prices.clone();
-        let msg_counter = self.msg_counter.clone();
-        let running = self.running.clone();
-        let last_display_time = self.last_display_time.clone();
-        let messages_received = self.messages_received.clone();
-        let token_labels = self.token_labels.clone();
-
-        // 启动连接任务
-        tokio::spawn(async move {
-            Self::connect_market(
-                subscribed_tokens,
-                last_prices,
-                msg_counter,
-                running,
-                last_display_time,
-                messages_received,
-                token_labels,
-            ).await;
-        });
+    /// 订阅行情事件流（价格变化、订单簿变化、成交）
+    pub fn subscribe(&self) -> broadcast::Receiver<MarketEvent> {
+        self.events.subscribe()
+    }
+
+    /// 当前连接状态
+    pub async fn connection_state(&self) -> ConnectionState {
+        *self.connection_state.read().await
+    }
+
+    /// 连接任务是否在运行
+    pub async fn is_running(&self) -> bool {
+        self.connection
+            .lock()
+            .await
+            .as_ref()
+            .is_some_and(|task| !task.handle.is_finished())
     }
 
-    /// 停止 WebSocket
+    /// 停止 WebSocket 并等待连接任务退出
     pub async fn stop(&self) {
-        let mut running = self.running.write().await;
-        *running = false;
+        let Some(task) = self.connection.lock().await.take() else {
+            return;
+        };
         info!("WebSocket client stopping...");
+        task.cancel.cancel();
+        let abort = task.handle.abort_handle();
+        if tokio::time::timeout(Duration::from_secs(WS_SHUTDOWN_TIMEOUT), task.handle).await.is_err() {
+            warn!("WebSocket task did not stop within {}s, aborting", WS_SHUTDOWN_TIMEOUT);
+            abort.abort();
+        }
+        Self::set_state(&self.feed_state(), ConnectionState::Disconnected).await;
     }
-    
-    /// 重启 WebSocket - 用于市场切换时重新订阅
+
+    /// 重启 WebSocket 连接 - 保留缓存，新连接的快照会覆盖旧数据
+    /// 重连放弃（GaveUp）后只能由此显式重启
     pub async fn restart(&self) {
-        info!("🔄 Restarting WebSocket for new subscription...");
-        // Stop current connection
-        {
-            let mut running = self.running.write().await;
-            *running = false;
-        }
-        // Wait for connection to close (like Python's time.sleep(1))
-        tokio::time::sleep(Duration::from_secs(1)).await;
-        
-        // Clear prices
-        {
-            let mut prices = self.last_prices.write().await;
-            prices.clear();
+        info!("🔄 Restarting WebSocket connection...");
+        self.stop().await;
+        self.spawn_connection().await;
+        let count = self.subscribed_tokens.read().await.len();
+        info!("✅ WebSocket restarted with {} markets", count);
+    }
+
+    /// 在当前连接上追加订阅
+    pub async fn subscribe_tokens(&self, token_ids: Vec<String>) {
+        let added: Vec<String> = {
+            let mut tokens = self.subscribed_tokens.write().await;
+            let mut added = Vec::new();
+            for id in token_ids {
+                if !tokens.contains(&id) {
+                    tokens.push(id.clone());
+                    added.push(id);
+                }
+            }
+            added
+        };
+        if added.is_empty() {
+            return;
         }
-        
-        // Get current tokens
-        let token_ids = {
-            self.subscribed_tokens.read().await.clone()
+        info!("📡 Subscribing to {} tokens", added.len());
+        self.send_command(WsCommand::Subscribe(added)).await;
+    }
+
+    /// 在当前连接上退订，并丢弃这些 token 的缓存
+    pub async fn unsubscribe_tokens(&self, token_ids: &[String]) {
+        let removed: Vec<String> = {
+            let mut tokens = self.subscribed_tokens.write().await;
+            let removed = tokens.iter().filter(|id| token_ids.contains(id)).cloned().collect();
+            tokens.retain(|id| !token_ids.contains(id));
+            removed
         };
-        
-        // Start again - restart the connection task
+        if removed.is_empty() {
+            return;
+        }
         {
-            let mut running = self.running.write().await;
-            *running = true;
-        }
-        
-        // Clone Arc for new task
-        let subscribed_tokens = self.subscribed_tokens.clone();
-        let last_prices = self.last_prices.clone();
-        let msg_counter = self.msg_counter.clone();
-        let running = self.running.clone();
-        let last_display_time = self.last_display_time.clone();
-        let messages_received = self.messages_received.clone();
-        let token_labels = self.token_labels.clone();
-        
-        // Spawn new connection task
-        tokio::spawn(async move {
-            Self::connect_market(
-                subscribed_tokens,
-                last_prices,
-                msg_counter,
-                running,
-                last_display_time,
-                messages_received,
-                token_labels,
-            ).await;
-        });
-        
-        info!("✅ WebSocket restarted with {} markets", token_ids.len());
+            // 与行情处理相同的加锁顺序：先 books 后 quotes
+            let mut books = self.books.write().await;
+            let mut quotes = self.quotes.write().await;
+            for id in removed.iter().filter_map(|id| normalize_asset_id(id)) {
+                quotes.remove(&id);
+                books.remove(&id);
+            }
+        }
+        info!("📴 Unsubscribed from {} tokens", removed.len());
+        self.send_command(WsCommand::Unsubscribe(removed)).await;
     }
 
-    /// 更新订阅的 token - 完全重启 WebSocket（像 Python 一样）
+    /// 更新订阅的 token - 在现有连接上增量订阅/退订，保留的 token 价格不中断
+    /// 连接已停止时会重新启动，但 GaveUp 是终态：需调用 restart() 才会重连
     pub async fn update_subscription(&self, token_ids: Vec<String>) {
-        info!("📡 Updating subscription to {} tokens - restarting WebSocket", token_ids.len());
-        
-        // Stop current connection
-        {
-            let mut running = self.running.write().await;
-            *running = false;
-        }
-        
-        // Wait for connection to close (like Python's time.sleep(1))
-        tokio::time::sleep(Duration::from_secs(1)).await;
-        
-        // Update tokens
-        {
-            let mut tokens = self.subscribed_tokens.write().await;
-            *tokens = token_ids.clone();
+        let removed: Vec<String> = {
+            let tokens = self.subscribed_tokens.read().await;
+            tokens.iter().filter(|id| !token_ids.contains(id)).cloned().collect()
+        };
+        info!("📡 Updating subscription to {} tokens ({} removed)", token_ids.len(), removed.len());
+
+        self.unsubscribe_tokens(&removed).await;
+        self.subscribe_tokens(token_ids).await;
+
+        if self.is_running().await {
+            return;
         }
-        
-        // Clear old prices (like Python)
-        {
-            let mut prices = self.last_prices.write().await;
-            prices.clear();
-            info!("🧹 Cleared old price cache for new market");
+        if let ConnectionState::GaveUp { attempts } = self.connection_state().await {
+            warn!("WebSocket gave up after {} reconnect attempts, not reconnecting until restarted", attempts);
+            return;
+        }
+        self.spawn_connection().await;
+    }
+
+    /// 启动唯一的连接任务（调用方保证旧任务已停止）
+    async fn spawn_connection(&self) {
+        let mut connection = self.connection.lock().await;
+        if let Some(old) = connection.take() {
+            old.cancel.cancel();
+        }
+        let (commands, rx) = mpsc::unbounded_channel();
+        let cancel = CancellationToken::new();
+        let handle = tokio::spawn(Self::connect_market(
+            self.url.clone(),
+            self.feed_state(),
+            self.reconnect_policy.clone(),
+            rx,
+            cancel.clone(),
+        ));
+        *connection = Some(ConnectionTask { handle, commands, cancel });
+    }
+
+    /// 发送订阅指令；未连接时下次连接会发送完整订阅列表
+    async fn send_command(&self, command: WsCommand) {
+        if let Some(task) = self.connection.lock().await.as_ref() {
+            if task.commands.send(command).is_err() {
+                debug!("WebSocket task gone, subscription applies on next connect");
+            }
+        }
+    }
+
+    fn feed_state(&self) -> FeedState {
+        FeedState {
+            subscribed_tokens: self.subscribed_tokens.clone(),
+            quotes: self.quotes.clone(),
+            books: self.books.clone(),
+            last_display_time: self.last_display_time.clone(),
+            messages_received: self.messages_received.clone(),
+            token_labels: self.token_labels.clone(),
+            events: self.events.clone(),
+            connection_state: self.connection_state.clone(),
         }
-        
-        // Restart connection - MUST spawn new task
-        {
-            let mut running = self.running.write().await;
-            *running = true;
-        }
-        
-        // Clone Arc for new task
-        let subscribed_tokens = self.subscribed_tokens.clone();
-        let last_prices = self.last_prices.clone();
-        let msg_counter = self.msg_counter.clone();
-        let running = self.running.clone();
-        let last_display_time = self.last_display_time.clone();
-        let messages_received = self.messages_received.clone();
-        let token_labels = self.token_labels.clone();
-        
-        // Spawn new connection task - CRITICAL FIX
-        tokio::spawn(async move {
-            Self::connect_market(
-                subscribed_tokens,
-                last_prices,
-                msg_counter,
-                running,
-                last_display_time,
-                messages_received,
-                token_labels,
-            ).await;
-        });
-        
-        info!("✅ WebSocket restarted with {} markets", token_ids.len());
     }
 
     /// 获取最新价格
     pub async fn get_price(&self, token_id: &str) -> Option<(f64, f64)> {
-        // Use FIRST 20 chars of token ID as key to match Gamma API behavior
-        // Gamma API returns short IDs (20 chars), WebSocket returns long IDs (77+ chars)
-        let short_token_id = if token_id.len() > 20 {
-            &token_id[..20]
-        } else {
-            token_id
-        };
-        
-        let prices = self.last_prices.read().await;
-        let bid_key = format!("{}_bid", short_token_id);
-        let ask_key = format!("{}_ask", short_token_id);
-        let bid = prices.get(&bid_key).copied();
-        let ask = prices.get(&ask_key).copied();
-        
-        // Debug: print available keys if not found
-        if bid.is_none() || ask.is_none() {
-            info!("DEBUG get_price: token_id={}, short={}, bid_key={}, ask_key={}, bid={:?}, ask={:?}", 
-                token_id, short_token_id, bid_key, ask_key, bid, ask);
-            info!("DEBUG available keys: {:?}", prices.keys().collect::<Vec<_>>());
-        }
-        
-        match (bid, ask) {
-            (Some(b), Some(a)) => Some((b, a)),
-            _ => None,
+        self.get_quote(token_id).await?.bid_ask()
+    }
+
+    /// 获取最新报价（含挂单量和更新时间）
+    pub async fn get_quote(&self, token_id: &str) -> Option<Quote> {
+        let quotes = self.quotes.read().await;
+        let quote = lookup_asset(&quotes, token_id).cloned();
+        if quote.is_none() {
+            debug!("No quote for {} ({} cached)", token_id, quotes.len());
         }
+        quote
     }
 
     /// 获取所有订阅 token 的价格
     pub async fn get_all_prices(&self) -> HashMap<String, (f64, f64)> {
-        let prices = self.last_prices.read().await;
+        let quotes = self.quotes.read().await;
         let tokens = self.subscribed_tokens.read().await;
-        let mut result = HashMap::new();
-        
-        for token in tokens.iter() {
-            // Use short token ID (first 20 chars) to match storage key
-            let short_token = if token.len() > 20 { &token[..20] } else { token };
-            let bid = prices.get(&format!("{}_bid", short_token)).copied();
-            let ask = prices.get(&format!("{}_ask", short_token)).copied();
-            if let (Some(b), Some(a)) = (bid, ask) {
-                result.insert(token.clone(), (b, a));
-            }
-        }
-        
-        result
+        tokens
+            .iter()
+            .filter_map(|token| {
+                let prices = lookup_asset(&quotes, token)?.bid_ask()?;
+                Some((token.clone(), prices))
+            })
+            .collect()
+    }
+
+    /// 获取当前订阅的 token
+    pub async fn get_subscribed_tokens(&self) -> Vec<String> {
+        self.subscribed_tokens.read().await.clone()
+    }
+
+    /// 获取 L2 订单簿快照
+    pub async fn get_order_book(&self, token_id: &str) -> Option<L2OrderBook> {
+        let books = self.books.read().await;
+        lookup_asset(&books, token_id).cloned()
+    }
+
+    /// 获取订单簿深度分析（可直接用于 calculate_mm_prices）
+    pub async fn get_order_book_depth(
+        &self,
+        token_id: &str,
+        min_size: f64,
+        depth_lookback: usize,
+    ) -> Option<OrderBookDepth> {
+        self.get_order_book(token_id).await?.depth(min_size, depth_lookback)
     }
 
     /// 获取消息统计
@@ -323,67 +564,89 @@ impl PolymarketWebSocket {
 
     /// 市场数据连接循环 - 复刻 Python _connect_market
     async fn connect_market(
-        subscribed_tokens: Arc<RwLock<Vec<String>>>,
-        last_prices: Arc<RwLock<HashMap<String, f64>>>,
-        msg_counter: Arc<RwLock<usize>>,
-        running: Arc<RwLock<bool>>,
-        last_display_time: Arc<RwLock<f64>>,
-        messages_received: Arc<RwLock<u64>>,
-        token_labels: Arc<RwLock<HashMap<String, String>>>,
+        url: String,
+        state: FeedState,
+        policy: ReconnectPolicy,
+        mut commands: mpsc::UnboundedReceiver<WsCommand>,
+        cancel: CancellationToken,
     ) {
-        loop {
-            // 检查是否停止
-            if !*running.read().await {
-                break;
-            }
+        // 连续失败的重连次数，连接成功后清零
+        let mut attempt: u32 = 0;
 
+        while !cancel.is_cancelled() {
             info!("Connecting to WebSocket...");
+            Self::set_state(&state, ConnectionState::Connecting).await;
 
-            match Self::try_connect(
-                subscribed_tokens.clone(),
-                last_prices.clone(),
-                msg_counter.clone(),
-                running.clone(),
-                last_display_time.clone(),
-                messages_received.clone(),
-                token_labels.clone(),
-            ).await {
+            match Self::try_connect(&url, &state, &mut commands, &cancel).await {
+                Ok(()) if cancel.is_cancelled() => break,
                 Ok(()) => {
-                    warn!("WebSocket closed, reconnecting...");
+                    warn!("WebSocket closed");
                 }
                 Err(e) => {
                     error!("WebSocket error: {}", e);
                 }
             }
 
-            // 重连延迟
-            tokio::time::sleep(Duration::from_secs(WS_RECONNECT_DELAY)).await;
+            if state.connection_state.read().await.is_connected() {
+                attempt = 0;
+            }
+            attempt += 1;
+            if !policy.allows(attempt) {
+                error!("🛑 WebSocket gave up after {} reconnect attempts", attempt - 1);
+                Self::set_state(&state, ConnectionState::GaveUp { attempts: attempt - 1 }).await;
+                return;
+            }
+
+            // 指数退避
+            let delay = policy.backoff(attempt);
+            warn!("🔁 Reconnecting in {:.1}s (attempt {})", delay.as_secs_f64(), attempt);
+            Self::set_state(&state, ConnectionState::Reconnecting {
+                attempt,
+                delay_ms: delay.as_millis() as u64,
+            }).await;
+            tokio::select! {
+                _ = cancel.cancelled() => break,
+                _ = tokio::time::sleep(delay) => {}
+            }
         }
 
+        Self::set_state(&state, ConnectionState::Disconnected).await;
         info!("WebSocket connection manager stopped");
     }
 
+    /// 更新连接状态，变化时广播
+    async fn set_state(state: &FeedState, new_state: ConnectionState) {
+        let changed = {
+            let mut current = state.connection_state.write().await;
+            std::mem::replace(&mut *current, new_state) != new_state
+        };
+        if changed {
+            Self::publish(state, MarketEvent::Connection(new_state));
+        }
+    }
+
     /// 尝试连接 - 复刻 Python 逻辑
     async fn try_connect(
-        subscribed_tokens: Arc<RwLock<Vec<String>>>,
-        last_prices: Arc<RwLock<HashMap<String, f64>>>,
-        msg_counter: Arc<RwLock<usize>>,
-        running: Arc<RwLock<bool>>,
-        last_display_time: Arc<RwLock<f64>>,
-        messages_received: Arc<RwLock<u64>>,
-        token_labels: Arc<RwLock<HashMap<String, String>>>,
+        url: &str,
+        state: &FeedState,
+        commands: &mut mpsc::UnboundedReceiver<WsCommand>,
+        cancel: &CancellationToken,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        let (mut ws, _) = tokio::time::timeout(
-            Duration::from_secs(WS_TIMEOUT_SECONDS),
-            connect_async(MARKET_WS_URL)
-        ).await
-            .map_err(|_| "Connection timeout")?
-            .map_err(|e| format!("Connection error: {}", e))?;
+        let (mut ws, _) = tokio::select! {
+            _ = cancel.cancelled() => return Ok(()),
+            result = tokio::time::timeout(Duration::from_secs(WS_TIMEOUT_SECONDS), connect_async(url)) => result
+                .map_err(|_| "Connection timeout")?
+                .map_err(|e| format!("Connection error: {}", e))?,
+        };
 
         info!("WebSocket connected");
+        Self::set_state(state, ConnectionState::Connected).await;
+
+        // 积压的指令已体现在完整订阅列表中
+        while commands.try_recv().is_ok() {}
 
         // 获取当前订阅的 token
-        let tokens = subscribed_tokens.read().await.clone();
+        let tokens = state.subscribed_tokens.read().await.clone();
         if !tokens.is_empty() {
             let msg = SubscribeMessage { asset_ids: tokens };
             let msg_text = serde_json::to_string(&msg)?;
@@ -395,6 +658,19 @@ impl PolymarketWebSocket {
 
         loop {
             tokio::select! {
+                _ = cancel.cancelled() => {
+                    let _ = ws.close(None).await;
+                    return Ok(());
+                }
+                Some(command) = commands.recv() => {
+                    let (asset_ids, operation) = match &command {
+                        WsCommand::Subscribe(ids) => (ids, "subscribe"),
+                        WsCommand::Unsubscribe(ids) => (ids, "unsubscribe"),
+                    };
+                    let msg = SubscriptionUpdate { asset_ids, operation };
+                    ws.send(Message::Text(serde_json::to_string(&msg)?)).await?;
+                    info!("📡 Sent {} for {} tokens", operation, asset_ids.len());
+                }
                 msg = tokio::time::timeout(
                     Duration::from_secs(WS_TIMEOUT_SECONDS),
                     ws.next()
@@ -403,19 +679,12 @@ impl PolymarketWebSocket {
                         Ok(Some(Ok(Message::Text(text)))) => {
                             // 更新统计
                             {
-                                let mut counter = messages_received.write().await;
+                                let mut counter = state.messages_received.write().await;
                                 *counter += 1;
                             }
 
                             // 解析消息
-                            Self::process_message(
-                                &text,
-                                &subscribed_tokens,
-                                &last_prices,
-                                &msg_counter,
-                                &last_display_time,
-                                &token_labels,
-                            ).await;
+                            Self::process_message(&text, state).await;
                         }
                         Ok(Some(Ok(Message::Ping(data)))) => {
                             ws.send(Message::Pong(data)).await?;
@@ -442,255 +711,186 @@ impl PolymarketWebSocket {
                     ws.send(Message::Ping(vec![])).await?;
                 }
             }
-
-            // 检查是否停止
-            if !*running.read().await {
-                let _ = ws.close(None).await;
-                return Ok(());
-            }
         }
     }
 
     /// 处理消息 - 复刻 Python _process_market_data
-    async fn process_message(
-        text: &str,
-        _subscribed_tokens: &Arc<RwLock<Vec<String>>>,
-        last_prices: &Arc<RwLock<HashMap<String, f64>>>,
-        _msg_counter: &Arc<RwLock<usize>>,
-        last_display_time: &Arc<RwLock<f64>>,
-        token_labels: &Arc<RwLock<HashMap<String, String>>>,
-    ) {
-        // 打印原始消息前200字符用于调试
-        info!("WebSocket raw message: {}...", &text[..text.len().min(200)]);
-        
-        // 尝试解析为对象（book 事件）- 这是主要的数据来源
-        match serde_json::from_str::<BookEvent>(text) {
-            Ok(event) => {
-                info!("Received book event for asset: {}, event_type: {}, bids: {}, asks: {}", 
-                    event.asset_id, event.event_type, event.bids.len(), event.asks.len());
-                
-                if event.event_type == "book" {
-                    Self::process_book_event(
-                        event,
-                        last_prices,
-                        last_display_time,
-                        token_labels,
-                    ).await;
-                }
-                return;
-            }
-            Err(e) => {
-                info!("Failed to parse as BookEvent: {}", e);
+    async fn process_message(text: &str, state: &FeedState) {
+        debug!("WebSocket raw message: {}...", &text[..text.len().min(200)]);
+
+        // book 快照：单个对象或初始推送的数组，每个都带 asset_id
+        let book_events = match serde_json::from_str::<BookEvent>(text) {
+            Ok(event) => Some(vec![event]),
+            Err(_) => serde_json::from_str::<Vec<BookEvent>>(text).ok(),
+        };
+        if let Some(events) = book_events {
+            for event in events.into_iter().filter(|e| e.event_type == "book") {
+                Self::process_book_event(event, state).await;
             }
+            return;
         }
 
-        // 尝试解析为数组（订单簿快照）- 复刻 Python List 格式处理
-        match serde_json::from_str::<Vec<OrderBookEntry>>(text) {
-            Ok(entries) => {
-                info!("Received orderbook snapshot with {} entries", entries.len());
-                // 处理快照 - 使用轮询 token 方式复刻 Python 逻辑
-                Self::process_orderbook_snapshot(
-                    entries,
-                    _subscribed_tokens,
-                    last_prices,
-                    _msg_counter,
-                    last_display_time,
-                    token_labels,
-                ).await;
-                return;
-            }
-            Err(e) => {
-                info!("Failed to parse as Vec<OrderBookEntry>: {}", e);
-            }
+        // price_change 增量
+        if let Ok(event) = serde_json::from_str::<PriceChangesEvent>(text) {
+            debug!("Received price_changes event with {} entries", event.price_changes.len());
+            Self::process_price_changes(event, state).await;
+            return;
         }
 
-        // 尝试解析为 price_changes 事件
-        match serde_json::from_str::<PriceChangesEvent>(text) {
-            Ok(event) => {
-                info!("Received price_changes event with {} entries", event.price_changes.len());
-                for change in event.price_changes {
-                    if let (Ok(price), Ok(_size)) = (change.price.parse::<f64>(), change.size.parse::<f64>()) {
-                        let short_token_id = if change.asset_id.len() > 20 {
-                            &change.asset_id[..20]
-                        } else {
-                            &change.asset_id
-                        };
-                        // 存储为 bid 和 ask（price_changes 通常是最新成交价）
-                        let mut prices = last_prices.write().await;
-                        prices.insert(format!("{}_bid", short_token_id), price);
-                        prices.insert(format!("{}_ask", short_token_id), price);
-                        info!("DEBUG storing price_change: key={}_bid/ask, price={}", short_token_id, price);
-                    }
-                }
+        // 最新成交
+        if let Ok(event) = serde_json::from_str::<LastTradePriceEvent>(text) {
+            if event.event_type == "last_trade_price" {
+                Self::process_last_trade(event, state);
                 return;
             }
-            Err(e) => {
-                info!("Failed to parse as PriceChangesEvent: {}", e);
-            }
         }
 
-        // 尝试解析为通用 JSON
         if let Ok(data) = serde_json::from_str::<serde_json::Value>(text) {
             debug!("Received other message: {:?}", data);
         }
     }
 
-    /// 处理订单簿快照 - 复刻 Python List 格式处理
-    async fn process_orderbook_snapshot(
-        entries: Vec<OrderBookEntry>,
-        subscribed_tokens: &Arc<RwLock<Vec<String>>>,
-        last_prices: &Arc<RwLock<HashMap<String, f64>>>,
-        msg_counter: &Arc<RwLock<usize>>,
-        last_display_time: &Arc<RwLock<f64>>,
-        token_labels: &Arc<RwLock<HashMap<String, String>>>,
-    ) {
-        let tokens = subscribed_tokens.read().await;
-        if tokens.is_empty() {
-            return;
-        }
+    /// 解析价位列表
+    fn parse_levels(levels: &[HashMap<String, String>]) -> Vec<OrderBookLevel> {
+        levels
+            .iter()
+            .filter_map(|level| {
+                let price = level.get("price")?.parse::<f64>().ok()?;
+                let size = level.get("size")?.parse::<f64>().ok()?;
+                Some(OrderBookLevel { price, size })
+            })
+            .collect()
+    }
+
+    /// 广播行情事件（没有订阅者时忽略）
+    fn publish(state: &FeedState, event: MarketEvent) {
+        let _ = state.events.send(event);
+    }
 
-        // 轮询 token: token_idx = msg_counter % len(tokens)
-        let token_idx = {
-            let mut counter = msg_counter.write().await;
-            let idx = *counter % tokens.len();
-            *counter += 1;
-            idx
+    /// 用订单簿最优档更新报价缓存，最优价变化时广播 PriceUpdate
+    async fn store_quote(asset_id: &str, book: &L2OrderBook, state: &FeedState) -> Quote {
+        let best_bid = book.best_bid();
+        let best_ask = book.best_ask();
+        let quote = Quote {
+            bid: best_bid.as_ref().map(|l| l.price),
+            ask: best_ask.as_ref().map(|l| l.price),
+            bid_size: best_bid.map(|l| l.size).unwrap_or(0.0),
+            ask_size: best_ask.map(|l| l.size).unwrap_or(0.0),
+            timestamp: chrono::Utc::now(),
         };
-        let token_id = tokens[token_idx].clone();
-
-        // 解析价格/数量
-        let mut prices_sizes: Vec<(f64, f64)> = Vec::new();
-        for entry in entries {
-            if let (Ok(price), Ok(size)) = (entry.price.parse::<f64>(), entry.size.parse::<f64>()) {
-                if price > 0.0 && size > 0.0 {
-                    prices_sizes.push((price, size));
-                }
-            }
+        let previous = state.quotes.write().await.insert(asset_id.to_string(), quote.clone());
+
+        let moved = previous.is_none_or(|p| p.bid != quote.bid || p.ask != quote.ask);
+        if let (true, Some((bid, ask))) = (moved, quote.bid_ask()) {
+            Self::publish(state, MarketEvent::Price(PriceUpdate {
+                token_id: asset_id.to_string(),
+                bid,
+                ask,
+                timestamp: quote.timestamp,
+            }));
         }
+        quote
+    }
 
-        if prices_sizes.len() >= 2 {
-            // 按价格降序排序 - 安全处理 NaN
-            prices_sizes.sort_by(|a, b| {
-                match b.0.partial_cmp(&a.0) {
-                    Some(ordering) => ordering,
-                    None => {
-                        // Handle NaN cases
-                        if a.0.is_nan() && b.0.is_nan() {
-                            Ordering::Equal
-                        } else if a.0.is_nan() {
-                            Ordering::Greater // NaN goes to the end
-                        } else {
-                            Ordering::Less
-                        }
+    /// 处理 price_change 增量
+    async fn process_price_changes(event: PriceChangesEvent, state: &FeedState) {
+        let mut touched: Vec<String> = Vec::new();
+        {
+            let mut books = state.books.write().await;
+            for change in event.price_changes {
+                let side = match change.side.as_deref().map(str::to_uppercase).as_deref() {
+                    Some("BUY") => BookSide::Bid,
+                    Some("SELL") => BookSide::Ask,
+                    _ => {
+                        debug!("price_change without side for {}", change.asset_id);
+                        continue;
                     }
-                }
-            });
-
-            // Best bid = 最高价格（买盘）
-            let best_bid = prices_sizes[0].0;
-            // Best ask = 最低价格（卖盘）
-            let best_ask = prices_sizes[prices_sizes.len() - 1].0;
-
-            // 更新缓存 - 使用短 token_id 作为 key（与其他地方一致）
-            {
-                let mut prices = last_prices.write().await;
-                let short_token_id: String = if token_id.len() > 20 {
-                    token_id[..20].to_string()
-                } else {
-                    token_id.to_string()
                 };
-                prices.insert(format!("{}_bid", short_token_id), best_bid);
-                prices.insert(format!("{}_ask", short_token_id), best_ask);
-                info!("DEBUG storing: key={}, bid={}", short_token_id, best_bid);
-                info!("DEBUG storing: key={}, ask={}", short_token_id, best_ask);
-            }
-
-            // 每 5 秒打印一次
-            let now = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .map(|d| d.as_secs_f64())
-                .unwrap_or(0.0);
-
-            let should_display = {
-                let mut last_display = last_display_time.write().await;
-                if now - *last_display >= DISPLAY_INTERVAL {
-                    *last_display = now;
-                    true
-                } else {
-                    false
+                let (Ok(price), Ok(size)) = (change.price.parse::<f64>(), change.size.parse::<f64>()) else {
+                    continue;
+                };
+                let Some(asset_id) = normalize_asset_id(&change.asset_id) else {
+                    warn!("⚠️ Asset ID format mismatch in price_change: {:?}", change.asset_id);
+                    continue;
+                };
+                // 没有快照的增量无法还原完整订单簿
+                let Some(book) = books.get_mut(&asset_id) else {
+                    debug!("price_change before book snapshot for {}", asset_id);
+                    continue;
+                };
+                book.apply_delta(side, price, size);
+                if !touched.contains(&asset_id) {
+                    touched.push(asset_id);
                 }
-            };
+            }
+        }
 
-            if should_display {
-                let labels = token_labels.read().await;
-                let label = labels.get(&token_id).cloned().unwrap_or_else(|| {
-                    token_id.chars().take(6).collect()
-                });
-                info!("{}: 买{:.4}/卖{:.4}", label, best_bid, best_ask);
+        let books = state.books.read().await;
+        for asset_id in touched {
+            if let Some(book) = books.get(&asset_id) {
+                Self::store_quote(&asset_id, book, state).await;
+                Self::publish(state, MarketEvent::Book(BookUpdate {
+                    token_id: asset_id,
+                    snapshot: false,
+                    book: book.clone(),
+                }));
             }
         }
     }
 
-    /// 处理 book 事件 - 复刻 Python Dict 格式处理
-    async fn process_book_event(
-        event: BookEvent,
-        last_prices: &Arc<RwLock<HashMap<String, f64>>>,
-        last_display_time: &Arc<RwLock<f64>>,
-        token_labels: &Arc<RwLock<HashMap<String, String>>>,
-    ) {
-        // Use FIRST 20 chars of token ID as key to match Gamma API behavior
-        // Gamma API returns short IDs (20 chars), WebSocket returns long IDs (77+ chars)
-        // The first 20 chars of WebSocket asset_id match the Gamma API token_id
-        let full_token_id = event.asset_id;
-        let short_token_id = if full_token_id.len() > 20 {
-            full_token_id[..20].to_string()
-        } else {
-            full_token_id.clone()
+    /// 处理 last_trade_price 事件
+    fn process_last_trade(event: LastTradePriceEvent, state: &FeedState) {
+        let Some(asset_id) = normalize_asset_id(&event.asset_id) else {
+            warn!("⚠️ Asset ID format mismatch in last_trade_price: {:?}", event.asset_id);
+            return;
         };
+        let Ok(price) = event.price.parse::<f64>() else {
+            return;
+        };
+        let size = event.size.and_then(|s| s.parse::<f64>().ok()).unwrap_or(0.0);
+        debug!("Trade on {}: {:.2} x {:.2}", asset_id, price, size);
+        Self::publish(state, MarketEvent::Trade(TradeUpdate {
+            token_id: asset_id,
+            price,
+            size,
+            side: event.side,
+            timestamp: chrono::Utc::now(),
+        }));
+    }
 
-        // 解析 bids - best bid = 最高价格 = 第一个元素 (matches Python: parsed_bids[0])
-        let best_bid = event.bids.first()
-            .and_then(|b| b.get("price"))
-            .and_then(|p| p.parse::<f64>().ok());
-
-        // 解析 asks - best ask = 最低价格 = 第一个元素 (matches Python: parsed_asks[0])
-        let best_ask = event.asks.first()
-            .and_then(|a| a.get("price"))
-            .and_then(|p| p.parse::<f64>().ok());
-        
-        info!("DEBUG price parsing: bids_count={}, asks_count={}, best_bid={:?}, best_ask={:?}", 
-            event.bids.len(), event.asks.len(), best_bid, best_ask);
-        if let Some(first_bid) = event.bids.first() {
-            info!("DEBUG first bid: {:?}", first_bid);
-        }
-        if let Some(first_ask) = event.asks.first() {
-            info!("DEBUG first ask: {:?}", first_ask);
-        }
+    /// 处理 book 事件 - 用快照替换整本订单簿
+    async fn process_book_event(event: BookEvent, state: &FeedState) {
+        let bids = Self::parse_levels(&event.bids);
+        let asks = Self::parse_levels(&event.asks);
+        debug!("Received book for asset {}: {} bids, {} asks", event.asset_id, bids.len(), asks.len());
+        let Some(asset_id) = normalize_asset_id(&event.asset_id) else {
+            warn!("⚠️ Asset ID format mismatch in book event: {:?}", event.asset_id);
+            return;
+        };
 
-        // 更新缓存 - use short token ID as key
-        {
-            let mut prices = last_prices.write().await;
-            if let Some(bid) = best_bid {
-                let key = format!("{}_bid", short_token_id);
-                info!("DEBUG storing: key={}, bid={}", key, bid);
-                prices.insert(key, bid);
-            }
-            if let Some(ask) = best_ask {
-                let key = format!("{}_ask", short_token_id);
-                info!("DEBUG storing: key={}, ask={}", key, ask);
-                prices.insert(key, ask);
-            }
-            info!("DEBUG total keys in cache: {}", prices.len());
-        }
+        let quote = {
+            let mut books = state.books.write().await;
+            let book = books
+                .entry(asset_id.clone())
+                .or_insert_with(|| L2OrderBook::new(&asset_id));
+            book.apply_snapshot(&bids, &asks);
+            let quote = Self::store_quote(&asset_id, book, state).await;
+            Self::publish(state, MarketEvent::Book(BookUpdate {
+                token_id: asset_id.clone(),
+                snapshot: true,
+                book: book.clone(),
+            }));
+            quote
+        };
 
-        // 每 5 秒打印一次
+        // 每 DISPLAY_INTERVAL 秒打印一次
         let now = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .map(|d| d.as_secs_f64())
             .unwrap_or(0.0);
 
         let should_display = {
-            let mut last_display = last_display_time.write().await;
+            let mut last_display = state.last_display_time.write().await;
             if now - *last_display >= DISPLAY_INTERVAL {
                 *last_display = now;
                 true
@@ -699,26 +899,17 @@ impl PolymarketWebSocket {
             }
         };
 
-        if should_display && (best_bid.is_some() || best_ask.is_some()) {
-            let labels = token_labels.read().await;
-            let _label = labels.get(&short_token_id).cloned().unwrap_or_else(|| {
-                short_token_id.chars().take(6).collect()
-            });
-            let _bid_str = best_bid.map(|b| format!("{:.4}", b)).unwrap_or_else(|| "--".to_string());
-            let _ask_str = best_ask.map(|a| format!("{:.4}", a)).unwrap_or_else(|| "--".to_string());
-            
+        if should_display && (quote.bid.is_some() || quote.ask.is_some()) {
+            let labels = state.token_labels.read().await;
             // Print all token prices, not just the current one
-            let all_prices = last_prices.read().await;
+            let all_quotes = state.quotes.read().await;
             let mut price_msgs: Vec<String> = Vec::new();
-            for (key, value) in all_prices.iter() {
-                if key.ends_with("_bid") {
-                    let token = &key[..key.len()-4];
-                    let label_short = labels.get(token).cloned().unwrap_or_else(|| {
-                        token.chars().take(4).collect()
-                    });
-                    let ask = all_prices.get(&format!("{}_ask", token)).copied().unwrap_or(0.0);
-                    price_msgs.push(format!("{}:买{:.2}/卖{:.2}", label_short, *value, ask));
-                }
+            for (token, q) in all_quotes.iter() {
+                let label_short = labels.get(token).cloned().unwrap_or_else(|| {
+                    token.chars().take(4).collect()
+                });
+                price_msgs.push(format!("{}:买{:.2}/卖{:.2}", label_short,
+                    q.bid.unwrap_or(0.0), q.ask.unwrap_or(0.0)));
             }
             if !price_msgs.is_empty() {
                 info!("{}", price_msgs.join(" | "));
@@ -736,4 +927,191 @@ mod tests {
         let ws = PolymarketWebSocket::new();
         assert_eq!(ws.get_stats().await, 0);
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_book_snapshot_and_price_change() {
+        let ws = PolymarketWebSocket::new();
+        let asset = "71321045679252212594626385532706912750332728571942532289631379312455583992563";
+        let state = ws.feed_state();
+        let process = |text: String| {
+            let state = &state;
+            async move { PolymarketWebSocket::process_message(&text, state).await }
+        };
+
+        // 初始推送为数组，bids 按价格升序
+        process(format!(r#"[{{"event_type":"book","asset_id":"{}","market":"0x1",
+            "bids":[{{"price":"0.48","size":"50"}},{{"price":"0.50","size":"20"}}],
+            "asks":[{{"price":"0.53","size":"40"}},{{"price":"0.52","size":"10"}}]}}]"#, asset)).await;
+        assert_eq!(ws.get_price(asset).await, Some((0.50, 0.52)));
+
+        process(format!(r#"{{"market":"0x1","event_type":"price_change","price_changes":[
+            {{"asset_id":"{a}","price":"0.50","size":"0","side":"BUY"}},
+            {{"asset_id":"{a}","price":"0.51","size":"15","side":"SELL"}}]}}"#, a = asset)).await;
+        assert_eq!(ws.get_price(asset).await, Some((0.48, 0.51)));
+
+        let book = ws.get_order_book(asset).await.unwrap();
+        assert_eq!(book.asks(5).len(), 3);
+        assert!(ws.get_order_book_depth(asset, 0.0, 5).await.is_none()); // 只剩一档 bid
+    }
+
+    #[tokio::test]
+    async fn test_quotes_keyed_by_full_asset_id() {
+        let ws = PolymarketWebSocket::new();
+        // 前 20 位相同的两个 token
+        let up = "71321045679252212594626385532706912750332728571942532289631379312455583992563";
+        let down = "71321045679252212594111111111111111111111111111111111111111111111111111111111";
+        for (asset, bid, ask) in [(up, "0.40", "0.42"), (down, "0.57", "0.59")] {
+            let text = format!(r#"{{"event_type":"book","asset_id":"{}","market":"0x1",
+                "bids":[{{"price":"{}","size":"5"}}],"asks":[{{"price":"{}","size":"7"}}]}}"#, asset, bid, ask);
+            PolymarketWebSocket::process_message(&text, &ws.feed_state()).await;
+        }
+
+        assert_eq!(ws.get_price(up).await, Some((0.40, 0.42)));
+        assert_eq!(ws.get_price(down).await, Some((0.57, 0.59)));
+        let quote = ws.get_quote(down).await.unwrap();
+        assert_eq!((quote.bid_size, quote.ask_size), (5.0, 7.0));
+        // 截断的 ID 不再匹配
+        assert!(ws.get_price(&up[..20]).await.is_none());
+        assert!(ws.get_price("not-a-token").await.is_none());
+    }
+
+    #[test]
+    fn test_normalize_asset_id() {
+        assert_eq!(normalize_asset_id("12345").as_deref(), Some("12345"));
+        assert_eq!(normalize_asset_id("0xff").as_deref(), Some("255"));
+        assert_eq!(normalize_asset_id("abc"), None);
+        assert_eq!(normalize_asset_id(""), None);
+    }
+
+    #[tokio::test]
+    async fn test_incremental_subscription_on_live_connection() {
+        use tokio::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (seen_tx, mut seen) = mpsc::unbounded_channel::<serde_json::Value>();
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            while let Some(Ok(msg)) = ws.next().await {
+                if let Message::Text(text) = msg {
+                    seen_tx.send(serde_json::from_str(&text).unwrap()).unwrap();
+                }
+            }
+        });
+
+        let ws = PolymarketWebSocket::new().with_url(&format!("ws://{}", addr));
+        ws.start(vec!["1".to_string()]).await;
+        async fn recv(seen: &mut mpsc::UnboundedReceiver<serde_json::Value>) -> serde_json::Value {
+            tokio::time::timeout(Duration::from_secs(5), seen.recv()).await.unwrap().unwrap()
+        }
+        assert_eq!(recv(&mut seen).await, serde_json::json!({"assets_ids": ["1"]}));
+
+        // 切换市场：只发送差量，不重连
+        ws.update_subscription(vec!["1".to_string(), "2".to_string()]).await;
+        assert_eq!(recv(&mut seen).await, serde_json::json!({"assets_ids": ["2"], "operation": "subscribe"}));
+        ws.update_subscription(vec!["2".to_string()]).await;
+        assert_eq!(recv(&mut seen).await, serde_json::json!({"assets_ids": ["1"], "operation": "unsubscribe"}));
+        assert_eq!(ws.get_subscribed_tokens().await, vec!["2".to_string()]);
+
+        assert!(ws.is_running().await);
+        ws.stop().await;
+        assert!(!ws.is_running().await);
+        tokio::time::timeout(Duration::from_secs(5), server).await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_market_event_stream() {
+        let ws = PolymarketWebSocket::new();
+        let mut events = ws.subscribe();
+        let state = ws.feed_state();
+        let asset = "71321045679252212594626385532706912750332728571942532289631379312455583992563";
+
+        let book = format!(r#"{{"event_type":"book","asset_id":"{}","market":"0x1",
+            "bids":[{{"price":"0.50","size":"20"}}],"asks":[{{"price":"0.52","size":"10"}}]}}"#, asset);
+        PolymarketWebSocket::process_message(&book, &state).await;
+        match events.try_recv().unwrap() {
+            MarketEvent::Price(update) => assert!((update.mid() - 0.51).abs() < 1e-9),
+            other => panic!("expected price update, got {:?}", other),
+        }
+        assert!(matches!(events.try_recv().unwrap(), MarketEvent::Book(b) if b.snapshot));
+
+        // 非最优档变化只推送 Book，不推送 Price
+        let change = format!(r#"{{"market":"0x1","event_type":"price_change","price_changes":[
+            {{"asset_id":"{}","price":"0.45","size":"30","side":"BUY"}}]}}"#, asset);
+        PolymarketWebSocket::process_message(&change, &state).await;
+        assert!(matches!(events.try_recv().unwrap(), MarketEvent::Book(b) if !b.snapshot));
+        assert!(events.try_recv().is_err());
+
+        let trade = format!(r#"{{"event_type":"last_trade_price","asset_id":"{}","market":"0x1",
+            "price":"0.52","size":"12.5","side":"BUY","timestamp":"1750428146322"}}"#, asset);
+        PolymarketWebSocket::process_message(&trade, &state).await;
+        match events.try_recv().unwrap() {
+            MarketEvent::Trade(t) => assert_eq!((t.price, t.size, t.side.as_deref()), (0.52, 12.5, Some("BUY"))),
+            other => panic!("expected trade, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_reconnect_backoff() {
+        let policy = ReconnectPolicy::new(true, 3);
+        assert!(policy.allows(3));
+        assert!(!policy.allows(4));
+        assert!(!ReconnectPolicy::new(false, 3).allows(1));
+        assert!(ReconnectPolicy::new(true, 0).allows(1000));
+
+        for attempt in 1..=10 {
+            let delay = policy.backoff(attempt);
+            let cap = (policy.base_delay * 2u32.pow(attempt - 1)).min(policy.max_delay);
+            assert!(delay >= cap / 2 && delay <= cap, "attempt {}: {:?}", attempt, delay);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_reconnect() {
+        // 拿一个空闲端口后立即释放，保证连接被拒绝
+        let addr = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+        let policy = ReconnectPolicy {
+            base_delay: Duration::from_millis(5),
+            max_delay: Duration::from_millis(20),
+            ..ReconnectPolicy::new(true, 2)
+        };
+        let ws = PolymarketWebSocket::new()
+            .with_url(&format!("ws://{}", addr))
+            .with_reconnect_policy(policy);
+        let mut events = ws.subscribe();
+        ws.start(vec![]).await;
+
+        let mut states = Vec::new();
+        while let Ok(Ok(MarketEvent::Connection(state))) =
+            tokio::time::timeout(Duration::from_secs(5), events.recv()).await
+        {
+            states.push(state);
+            if matches!(state, ConnectionState::GaveUp { .. }) {
+                break;
+            }
+        }
+        let attempts: Vec<u32> = states
+            .iter()
+            .filter_map(|s| match s {
+                ConnectionState::Reconnecting { attempt, .. } => Some(*attempt),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(attempts, vec![1, 2]);
+        assert_eq!(states.last(), Some(&ConnectionState::GaveUp { attempts: 2 }));
+        assert_eq!(ws.connection_state().await, ConnectionState::GaveUp { attempts: 2 });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!ws.is_running().await);
+
+        // 定时的订阅同步不会让放弃的连接复活
+        ws.update_subscription(vec!["token_1".to_string()]).await;
+        assert!(!ws.is_running().await);
+        assert_eq!(ws.connection_state().await, ConnectionState::GaveUp { attempts: 2 });
+
+        // 显式 restart 才重新开始
+        ws.restart().await;
+        assert!(ws.is_running().await);
+        ws.stop().await;
+    }
+}
@@ -0,0 +1,295 @@
+//! 现货参考价格 WebSocket - 为公允价值模型提供外部现货价格
+//!
+//! 交易所协议通过 `SpotFeedAdapter` 插拔；`SpotReplay` 按行回放录制的原始消息，
+//! 测试和回测不需要网络。
+
+use chrono::{DateTime, Utc};
+use futures::{SinkExt, StreamExt};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::{mpsc, RwLock};
+use tokio::time::Duration;
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tracing::{debug, error, info, warn};
+
+const WS_TIMEOUT_SECONDS: u64 = 30;
+const WS_RECONNECT_DELAY: u64 = 5;
+
+/// Binance 现货 WebSocket 地址
+pub const BINANCE_SPOT_WS_URL: &str = "wss://stream.binance.com:9443/ws";
+
+/// 一笔现货成交价格
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpotTick {
+    /// 交易对，如 BTCUSDT
+    pub symbol: String,
+    pub price: f64,
+    /// 交易所成交时间
+    pub timestamp: DateTime<Utc>,
+}
+
+/// 现货行情协议适配器
+pub trait SpotFeedAdapter: Send + Sync {
+    /// 适配器名称（配置中的 adapter 字段）
+    fn name(&self) -> &str;
+
+    /// 默认 WebSocket 地址
+    fn default_url(&self) -> &str;
+
+    /// 连接后发送的订阅消息（不需要则返回 None）
+    fn subscribe_message(&self, symbols: &[String]) -> Option<String>;
+
+    /// 解析一条原始消息
+    fn parse(&self, text: &str) -> Vec<SpotTick>;
+}
+
+/// Binance 逐笔聚合成交 (aggTrade)
+#[derive(Debug, Default)]
+pub struct BinanceAdapter;
+
+/// Binance aggTrade 消息
+#[derive(Debug, Deserialize)]
+struct BinanceAggTrade {
+    #[serde(rename = "e")]
+    event: String,
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "p")]
+    price: String,
+    #[serde(rename = "T")]
+    trade_time: i64,
+}
+
+impl SpotFeedAdapter for BinanceAdapter {
+    fn name(&self) -> &str {
+        "binance"
+    }
+
+    fn default_url(&self) -> &str {
+        BINANCE_SPOT_WS_URL
+    }
+
+    fn subscribe_message(&self, symbols: &[String]) -> Option<String> {
+        let params: Vec<String> = symbols
+            .iter()
+            .map(|s| format!("{}@aggTrade", s.to_lowercase()))
+            .collect();
+        Some(serde_json::json!({"method": "SUBSCRIBE", "params": params, "id": 1}).to_string())
+    }
+
+    fn parse(&self, text: &str) -> Vec<SpotTick> {
+        // 订阅确认等非成交消息直接忽略
+        let Ok(trade) = serde_json::from_str::<BinanceAggTrade>(text) else {
+            return Vec::new();
+        };
+        if trade.event != "aggTrade" {
+            return Vec::new();
+        }
+        let (Ok(price), Some(timestamp)) = (trade.price.parse(), DateTime::from_timestamp_millis(trade.trade_time)) else {
+            return Vec::new();
+        };
+        vec![SpotTick {
+            symbol: trade.symbol,
+            price,
+            timestamp,
+        }]
+    }
+}
+
+/// 按名称获取内置适配器
+pub fn spot_adapter(name: &str) -> Option<Arc<dyn SpotFeedAdapter>> {
+    match name {
+        "binance" => Some(Arc::new(BinanceAdapter)),
+        _ => None,
+    }
+}
+
+/// 现货行情 WebSocket 客户端
+pub struct SpotWebSocket {
+    url: String,
+    adapter: Arc<dyn SpotFeedAdapter>,
+    running: Arc<RwLock<bool>>,
+}
+
+impl SpotWebSocket {
+    /// 使用适配器默认地址创建客户端
+    pub fn new(adapter: Arc<dyn SpotFeedAdapter>) -> Self {
+        Self {
+            url: adapter.default_url().to_string(),
+            adapter,
+            running: Arc::new(RwLock::new(false)),
+        }
+    }
+
+    /// 使用自定义 WebSocket 地址
+    pub fn with_url(mut self, url: &str) -> Self {
+        self.url = url.to_string();
+        self
+    }
+
+    /// 启动连接，返回价格接收端
+    pub async fn start(&self, symbols: Vec<String>) -> mpsc::UnboundedReceiver<SpotTick> {
+        *self.running.write().await = true;
+        let (tx, rx) = mpsc::unbounded_channel();
+        let url = self.url.clone();
+        let adapter = self.adapter.clone();
+        let running = self.running.clone();
+
+        info!("🚀 Spot WebSocket ({}) starting for {}", adapter.name(), symbols.join(", "));
+        tokio::spawn(async move {
+            while *running.read().await && !tx.is_closed() {
+                match Self::try_connect(&url, adapter.as_ref(), &symbols, &running, &tx).await {
+                    Ok(()) => warn!("Spot WebSocket closed, reconnecting..."),
+                    Err(e) => error!("Spot WebSocket error: {}", e),
+                }
+                tokio::time::sleep(Duration::from_secs(WS_RECONNECT_DELAY)).await;
+            }
+            info!("Spot WebSocket connection manager stopped");
+        });
+        rx
+    }
+
+    /// 停止连接
+    pub async fn stop(&self) {
+        *self.running.write().await = false;
+    }
+
+    /// 尝试连接并转发价格
+    async fn try_connect(
+        url: &str,
+        adapter: &dyn SpotFeedAdapter,
+        symbols: &[String],
+        running: &Arc<RwLock<bool>>,
+        tx: &mpsc::UnboundedSender<SpotTick>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let (mut ws, _) = tokio::time::timeout(Duration::from_secs(WS_TIMEOUT_SECONDS), connect_async(url))
+            .await
+            .map_err(|_| "Connection timeout")?
+            .map_err(|e| format!("Connection error: {}", e))?;
+
+        if let Some(msg) = adapter.subscribe_message(symbols) {
+            ws.send(Message::Text(msg)).await?;
+        }
+        info!("📡 Spot feed subscribed ({} symbols)", symbols.len());
+
+        loop {
+            match tokio::time::timeout(Duration::from_secs(WS_TIMEOUT_SECONDS), ws.next()).await {
+                Ok(Some(Ok(Message::Text(text)))) => {
+                    for tick in adapter.parse(&text) {
+                        if tx.send(tick).is_err() {
+                            let _ = ws.close(None).await;
+                            return Ok(());
+                        }
+                    }
+                }
+                Ok(Some(Ok(Message::Ping(data)))) => ws.send(Message::Pong(data)).await?,
+                Ok(Some(Ok(Message::Close(_)))) | Ok(None) => return Ok(()),
+                Ok(Some(Err(e))) => return Err(e.into()),
+                Ok(Some(Ok(_))) => {}
+                Err(_) => return Err("No spot data before timeout".into()),
+            }
+
+            if !*running.read().await {
+                let _ = ws.close(None).await;
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// 从文件回放录制的原始消息（每行一条）
+pub struct SpotReplay {
+    path: PathBuf,
+    adapter: Arc<dyn SpotFeedAdapter>,
+    /// 按成交时间间隔回放；否则一次性推送
+    realtime: bool,
+}
+
+impl SpotReplay {
+    /// 创建回放
+    pub fn new(path: impl AsRef<Path>, adapter: Arc<dyn SpotFeedAdapter>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            adapter,
+            realtime: false,
+        }
+    }
+
+    /// 按原始时间间隔回放
+    pub fn with_realtime(mut self, realtime: bool) -> Self {
+        self.realtime = realtime;
+        self
+    }
+
+    /// 读取并解析全部价格
+    pub fn ticks(&self) -> std::io::Result<Vec<SpotTick>> {
+        let content = std::fs::read_to_string(&self.path)?;
+        Ok(content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .flat_map(|line| self.adapter.parse(line))
+            .collect())
+    }
+
+    /// 启动回放，返回价格接收端
+    pub fn start(&self) -> std::io::Result<mpsc::UnboundedReceiver<SpotTick>> {
+        let ticks = self.ticks()?;
+        info!("▶️ Replaying {} spot ticks from {}", ticks.len(), self.path.display());
+        let (tx, rx) = mpsc::unbounded_channel();
+        let realtime = self.realtime;
+        tokio::spawn(async move {
+            let mut previous: Option<DateTime<Utc>> = None;
+            for tick in ticks {
+                if let (true, Some(prev)) = (realtime, previous) {
+                    let gap = (tick.timestamp - prev).to_std().unwrap_or_default();
+                    tokio::time::sleep(gap).await;
+                }
+                previous = Some(tick.timestamp);
+                if tx.send(tick).is_err() {
+                    break;
+                }
+            }
+            debug!("Spot replay finished");
+        });
+        Ok(rx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_parse_binance_agg_trade() {
+        let adapter = BinanceAdapter;
+        let text = r#"{"e":"aggTrade","E":1760000000123,"s":"BTCUSDT","a":1,"p":"67123.45","q":"0.01","f":1,"l":1,"T":1760000000100,"m":false}"#;
+        assert_eq!(adapter.parse(text), vec![SpotTick {
+            symbol: "BTCUSDT".to_string(),
+            price: 67123.45,
+            timestamp: DateTime::from_timestamp_millis(1_760_000_000_100).unwrap(),
+        }]);
+        // 订阅确认
+        assert!(adapter.parse(r#"{"result":null,"id":1}"#).is_empty());
+        let subscribe: serde_json::Value =
+            serde_json::from_str(&adapter.subscribe_message(&["BTCUSDT".to_string()]).unwrap()).unwrap();
+        assert_eq!(subscribe["params"], serde_json::json!(["btcusdt@aggTrade"]));
+    }
+
+    #[tokio::test]
+    async fn test_replay_file() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, r#"{{"result":null,"id":1}}"#).unwrap();
+        writeln!(file, r#"{{"e":"aggTrade","s":"BTCUSDT","p":"100.0","T":1760000000000}}"#).unwrap();
+        writeln!(file).unwrap();
+        writeln!(file, r#"{{"e":"aggTrade","s":"BTCUSDT","p":"101.5","T":1760000001000}}"#).unwrap();
+
+        let replay = SpotReplay::new(file.path(), spot_adapter("binance").unwrap());
+        let mut rx = replay.start().unwrap();
+        assert_eq!(rx.recv().await.unwrap().price, 100.0);
+        assert_eq!(rx.recv().await.unwrap().price, 101.5);
+        assert!(rx.recv().await.is_none());
+    }
+}
@@ -0,0 +1,531 @@
+//! 用户频道 WebSocket - 推送我们自己的订单和成交事件
+//!
+//! 认证后订阅 /ws/user，把 PLACEMENT/UPDATE/CANCELLATION 订单事件
+//! 和 TRADE 成交事件转成 `UserEvent` 发送给调用方。
+
+use futures::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::{mpsc, watch, RwLock};
+use tokio::time::{interval, Duration};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tracing::{debug, error, info, warn};
+
+use super::{ConnectionState, ReconnectPolicy};
+use crate::trading::order_tracker::TradeFill;
+
+const WS_TIMEOUT_SECONDS: u64 = 30;
+const WS_PING_INTERVAL: u64 = 10;
+
+/// 用户频道 WebSocket 地址
+pub(crate) const USER_WS_URL: &str = "wss://ws-subscriptions-clob.polymarket.com/ws/user";
+
+/// 用户频道认证信息 (CLOB L2 API 凭证)
+#[derive(Clone, Serialize)]
+pub struct UserChannelAuth {
+    #[serde(rename = "apiKey")]
+    pub api_key: String,
+    pub secret: String,
+    pub passphrase: String,
+}
+
+impl std::fmt::Debug for UserChannelAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UserChannelAuth")
+            .field("api_key", &self.api_key)
+            .field("secret", &"***")
+            .field("passphrase", &"***")
+            .finish()
+    }
+}
+
+/// 订阅消息
+#[derive(Debug, Clone, Serialize)]
+struct UserSubscribeMessage<'a> {
+    auth: &'a UserChannelAuth,
+    markets: Vec<String>,
+    #[serde(rename = "type")]
+    channel: &'static str,
+}
+
+/// 订单事件类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserOrderEventType {
+    Placement,
+    Update,
+    Cancellation,
+}
+
+/// 订单事件
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserOrderEvent {
+    pub order_id: String,
+    pub asset_id: String,
+    pub side: String,
+    pub price: f64,
+    pub original_size: f64,
+    pub size_matched: f64,
+    pub event_type: UserOrderEventType,
+}
+
+/// 成交事件中属于某个挂单的部分
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserMakerFill {
+    pub order_id: String,
+    pub matched_amount: f64,
+    pub price: f64,
+}
+
+/// 成交事件
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserTradeEvent {
+    pub trade_id: String,
+    pub asset_id: String,
+    pub status: String,
+    pub size: f64,
+    pub price: f64,
+    pub taker_order_id: String,
+    pub maker_orders: Vec<UserMakerFill>,
+}
+
+impl UserTradeEvent {
+    /// 成交已在链上失败
+    pub fn is_failed(&self) -> bool {
+        self.status.eq_ignore_ascii_case("FAILED")
+    }
+
+    /// 取出该成交中属于指定订单的部分
+    pub fn fill_for_order(&self, order_id: &str) -> Option<TradeFill> {
+        if self.is_failed() {
+            return None;
+        }
+        let (size, price) = if self.taker_order_id == order_id {
+            (self.size, self.price)
+        } else {
+            let maker = self.maker_orders.iter().find(|m| m.order_id == order_id)?;
+            (maker.matched_amount, maker.price)
+        };
+        Some(TradeFill {
+            trade_id: self.trade_id.clone(),
+            size,
+            price,
+        })
+    }
+}
+
+/// 用户频道事件
+#[derive(Debug, Clone, PartialEq)]
+pub enum UserEvent {
+    Order(UserOrderEvent),
+    Trade(UserTradeEvent),
+}
+
+impl UserEvent {
+    /// 事件所属资产
+    pub fn asset_id(&self) -> &str {
+        match self {
+            UserEvent::Order(order) => &order.asset_id,
+            UserEvent::Trade(trade) => &trade.asset_id,
+        }
+    }
+}
+
+/// 原始订单消息
+#[derive(Debug, Deserialize)]
+struct RawOrderMessage {
+    id: String,
+    asset_id: String,
+    side: String,
+    price: String,
+    #[serde(default)]
+    original_size: Option<String>,
+    #[serde(default)]
+    size_matched: Option<String>,
+    #[serde(rename = "type")]
+    msg_type: String,
+}
+
+/// 原始挂单成交
+#[derive(Debug, Deserialize)]
+struct RawMakerOrder {
+    order_id: String,
+    matched_amount: String,
+    price: String,
+}
+
+/// 原始成交消息
+#[derive(Debug, Deserialize)]
+struct RawTradeMessage {
+    id: String,
+    asset_id: String,
+    status: String,
+    size: String,
+    price: String,
+    #[serde(default)]
+    taker_order_id: String,
+    #[serde(default)]
+    maker_orders: Vec<RawMakerOrder>,
+}
+
+fn parse_num(value: &str) -> Option<f64> {
+    value.parse::<f64>().ok()
+}
+
+/// 解析单个事件对象
+fn parse_event(value: serde_json::Value) -> Option<UserEvent> {
+    let event_type = value.get("event_type")?.as_str()?.to_string();
+    match event_type.as_str() {
+        "order" => {
+            let raw: RawOrderMessage = serde_json::from_value(value).ok()?;
+            let event_type = match raw.msg_type.to_uppercase().as_str() {
+                "PLACEMENT" => UserOrderEventType::Placement,
+                "UPDATE" => UserOrderEventType::Update,
+                "CANCELLATION" => UserOrderEventType::Cancellation,
+                other => {
+                    debug!("Unknown user order message type: {}", other);
+                    return None;
+                }
+            };
+            Some(UserEvent::Order(UserOrderEvent {
+                order_id: raw.id,
+                asset_id: raw.asset_id,
+                side: raw.side.to_uppercase(),
+                price: parse_num(&raw.price)?,
+                original_size: raw.original_size.as_deref().and_then(parse_num).unwrap_or(0.0),
+                size_matched: raw.size_matched.as_deref().and_then(parse_num).unwrap_or(0.0),
+                event_type,
+            }))
+        }
+        "trade" => {
+            let raw: RawTradeMessage = serde_json::from_value(value).ok()?;
+            let maker_orders = raw.maker_orders
+                .into_iter()
+                .filter_map(|m| Some(UserMakerFill {
+                    matched_amount: parse_num(&m.matched_amount)?,
+                    price: parse_num(&m.price)?,
+                    order_id: m.order_id,
+                }))
+                .collect();
+            Some(UserEvent::Trade(UserTradeEvent {
+                trade_id: raw.id,
+                asset_id: raw.asset_id,
+                status: raw.status.to_uppercase(),
+                size: parse_num(&raw.size)?,
+                price: parse_num(&raw.price)?,
+                taker_order_id: raw.taker_order_id,
+                maker_orders,
+            }))
+        }
+        _ => None,
+    }
+}
+
+/// 解析用户频道消息（单个对象或数组）
+pub fn parse_user_message(text: &str) -> Vec<UserEvent> {
+    match serde_json::from_str::<serde_json::Value>(text) {
+        Ok(serde_json::Value::Array(items)) => items.into_iter().filter_map(parse_event).collect(),
+        Ok(value) => parse_event(value).into_iter().collect(),
+        Err(_) => vec![],
+    }
+}
+
+/// 连接任务共享的状态
+struct UserFeed {
+    markets: Arc<RwLock<Vec<String>>>,
+    running: Arc<RwLock<bool>>,
+    messages_received: Arc<RwLock<u64>>,
+    state: Arc<watch::Sender<ConnectionState>>,
+}
+
+impl UserFeed {
+    /// 更新连接状态，变化时通知订阅方
+    fn set_state(&self, new_state: ConnectionState) {
+        self.state.send_if_modified(|current| std::mem::replace(current, new_state) != new_state);
+    }
+}
+
+/// 用户频道 WebSocket 客户端
+pub struct UserWebSocket {
+    /// 用户频道地址
+    url: String,
+    /// 认证信息
+    auth: UserChannelAuth,
+    /// 订阅的市场 condition IDs（空 = 全部市场）
+    markets: Arc<RwLock<Vec<String>>>,
+    /// 运行状态
+    running: Arc<RwLock<bool>>,
+    /// 统计
+    messages_received: Arc<RwLock<u64>>,
+    /// 重连策略（与行情频道相同的指数退避）
+    reconnect_policy: ReconnectPolicy,
+    /// 连接状态，变化时通知订阅方
+    connection_state: Arc<watch::Sender<ConnectionState>>,
+}
+
+impl UserWebSocket {
+    /// 创建新的用户频道客户端
+    pub fn new(auth: UserChannelAuth) -> Self {
+        Self {
+            url: USER_WS_URL.to_string(),
+            auth,
+            markets: Arc::new(RwLock::new(Vec::new())),
+            running: Arc::new(RwLock::new(false)),
+            messages_received: Arc::new(RwLock::new(0)),
+            reconnect_policy: ReconnectPolicy::default(),
+            connection_state: Arc::new(watch::channel(ConnectionState::Disconnected).0),
+        }
+    }
+
+    /// 使用自定义 WebSocket 地址
+    pub fn with_url(mut self, url: &str) -> Self {
+        self.url = url.to_string();
+        self
+    }
+
+    /// 使用自定义重连策略
+    pub fn with_reconnect_policy(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect_policy = policy;
+        self
+    }
+
+    /// 当前连接状态
+    pub fn connection_state(&self) -> ConnectionState {
+        *self.connection_state.borrow()
+    }
+
+    /// 订阅连接状态变化
+    pub fn watch_state(&self) -> watch::Receiver<ConnectionState> {
+        self.connection_state.subscribe()
+    }
+
+    /// 启动连接，返回事件接收端
+    pub async fn start(&self, markets: Vec<String>) -> mpsc::UnboundedReceiver<UserEvent> {
+        *self.running.write().await = true;
+        *self.markets.write().await = markets;
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        let url = self.url.clone();
+        let auth = self.auth.clone();
+        let feed = UserFeed {
+            markets: self.markets.clone(),
+            running: self.running.clone(),
+            messages_received: self.messages_received.clone(),
+            state: self.connection_state.clone(),
+        };
+        let policy = self.reconnect_policy.clone();
+
+        info!("🚀 User WebSocket client starting");
+        tokio::spawn(async move {
+            Self::connect_user(url, auth, feed, policy, tx).await;
+        });
+        rx
+    }
+
+    /// 停止连接
+    pub async fn stop(&self) {
+        *self.running.write().await = false;
+        info!("User WebSocket client stopping...");
+    }
+
+    /// 获取消息统计
+    pub async fn get_stats(&self) -> u64 {
+        *self.messages_received.read().await
+    }
+
+    /// 连接循环 - 断线按策略退避重连，次数用尽后进入 GaveUp
+    async fn connect_user(
+        url: String,
+        auth: UserChannelAuth,
+        feed: UserFeed,
+        policy: ReconnectPolicy,
+        tx: mpsc::UnboundedSender<UserEvent>,
+    ) {
+        // 连续失败的重连次数，连接成功后清零
+        let mut attempt: u32 = 0;
+
+        while *feed.running.read().await && !tx.is_closed() {
+            info!("Connecting to user WebSocket...");
+            feed.set_state(ConnectionState::Connecting);
+            match Self::try_connect(&url, &auth, &feed, &tx).await {
+                Ok(()) => warn!("User WebSocket closed"),
+                Err(e) => error!("User WebSocket error: {}", e),
+            }
+            if !*feed.running.read().await || tx.is_closed() {
+                break;
+            }
+
+            if feed.state.borrow().is_connected() {
+                attempt = 0;
+            }
+            attempt += 1;
+            if !policy.allows(attempt) {
+                error!("🛑 User WebSocket gave up after {} reconnect attempts", attempt - 1);
+                feed.set_state(ConnectionState::GaveUp { attempts: attempt - 1 });
+                return;
+            }
+
+            let delay = policy.backoff(attempt);
+            warn!("🔁 User WebSocket reconnecting in {:.1}s (attempt {})", delay.as_secs_f64(), attempt);
+            feed.set_state(ConnectionState::Reconnecting {
+                attempt,
+                delay_ms: delay.as_millis() as u64,
+            });
+            tokio::time::sleep(delay).await;
+        }
+
+        feed.set_state(ConnectionState::Disconnected);
+        info!("User WebSocket connection manager stopped");
+    }
+
+    /// 尝试连接并转发事件
+    async fn try_connect(
+        url: &str,
+        auth: &UserChannelAuth,
+        feed: &UserFeed,
+        tx: &mpsc::UnboundedSender<UserEvent>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let UserFeed { markets, running, messages_received, .. } = feed;
+        let (mut ws, _) = tokio::time::timeout(
+            Duration::from_secs(WS_TIMEOUT_SECONDS),
+            connect_async(url)
+        ).await
+            .map_err(|_| "Connection timeout")?
+            .map_err(|e| format!("Connection error: {}", e))?;
+
+        let msg = UserSubscribeMessage {
+            auth,
+            markets: markets.read().await.clone(),
+            channel: "user",
+        };
+        ws.send(Message::Text(serde_json::to_string(&msg)?)).await?;
+        info!("📡 User channel subscribed ({} markets)", msg.markets.len());
+        feed.set_state(ConnectionState::Connected);
+
+        let mut ping_interval = interval(Duration::from_secs(WS_PING_INTERVAL));
+
+        loop {
+            tokio::select! {
+                msg = tokio::time::timeout(
+                    Duration::from_secs(WS_TIMEOUT_SECONDS),
+                    ws.next()
+                ) => {
+                    match msg {
+                        Ok(Some(Ok(Message::Text(text)))) => {
+                            *messages_received.write().await += 1;
+                            for event in parse_user_message(&text) {
+                                if tx.send(event).is_err() {
+                                    // 接收端已关闭
+                                    let _ = ws.close(None).await;
+                                    return Ok(());
+                                }
+                            }
+                        }
+                        Ok(Some(Ok(Message::Ping(data)))) => {
+                            ws.send(Message::Pong(data)).await?;
+                        }
+                        Ok(Some(Ok(Message::Close(_)))) => {
+                            warn!("User WebSocket closed by server");
+                            return Ok(());
+                        }
+                        Ok(Some(Err(e))) => {
+                            error!("User WebSocket error: {}", e);
+                        }
+                        Ok(None) => {
+                            warn!("User WebSocket stream ended");
+                            return Ok(());
+                        }
+                        Err(_) => {
+                            ws.send(Message::Ping(vec![])).await?;
+                        }
+                        _ => {}
+                    }
+                }
+                _ = ping_interval.tick() => {
+                    ws.send(Message::Ping(vec![])).await?;
+                }
+            }
+
+            if !*running.read().await {
+                let _ = ws.close(None).await;
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_user_channel_backs_off_and_gives_up() {
+        // 拿一个空闲端口后立即释放，保证连接被拒绝
+        let addr = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+        let auth = UserChannelAuth {
+            api_key: "key".to_string(),
+            secret: "secret".to_string(),
+            passphrase: "pass".to_string(),
+        };
+        let ws = UserWebSocket::new(auth)
+            .with_url(&format!("ws://{}", addr))
+            .with_reconnect_policy(ReconnectPolicy {
+                base_delay: Duration::from_millis(5),
+                max_delay: Duration::from_millis(20),
+                ..ReconnectPolicy::new(true, 2)
+            });
+        let mut state = ws.watch_state();
+        let _events = ws.start(vec![]).await;
+
+        // 两次退避重连后放弃，而不是每 5 秒重试下去
+        let gave_up = tokio::time::timeout(
+            Duration::from_secs(5),
+            state.wait_for(|s| matches!(s, ConnectionState::GaveUp { .. })),
+        ).await.unwrap().map(|s| *s).unwrap();
+        assert_eq!(gave_up, ConnectionState::GaveUp { attempts: 2 });
+        assert_eq!(ws.connection_state(), gave_up);
+    }
+
+    #[test]
+    fn test_parse_trade_message() {
+        let text = r#"[{"event_type":"trade","id":"t1","asset_id":"123","status":"MATCHED",
+            "size":"10","price":"0.45","side":"BUY","taker_order_id":"0xtaker",
+            "maker_orders":[{"order_id":"0xours","matched_amount":"4","price":"0.55","asset_id":"456"}]}]"#;
+        let events = parse_user_message(text);
+        assert_eq!(events.len(), 1);
+
+        let UserEvent::Trade(trade) = &events[0] else { panic!("expected trade") };
+        let fill = trade.fill_for_order("0xours").unwrap();
+        assert_eq!(fill.size, 4.0);
+        assert_eq!(fill.price, 0.55);
+        assert_eq!(trade.fill_for_order("0xtaker").unwrap().size, 10.0);
+        assert!(trade.fill_for_order("0xother").is_none());
+    }
+
+    #[test]
+    fn test_parse_order_message() {
+        let text = r#"{"event_type":"order","id":"0xours","asset_id":"123","side":"buy",
+            "price":"0.5","original_size":"10","size_matched":"0","type":"CANCELLATION"}"#;
+        let events = parse_user_message(text);
+        assert_eq!(events, vec![UserEvent::Order(UserOrderEvent {
+            order_id: "0xours".to_string(),
+            asset_id: "123".to_string(),
+            side: "BUY".to_string(),
+            price: 0.5,
+            original_size: 10.0,
+            size_matched: 0.0,
+            event_type: UserOrderEventType::Cancellation,
+        })]);
+    }
+
+    #[test]
+    fn test_auth_debug_hides_secrets() {
+        let auth = UserChannelAuth {
+            api_key: "key".to_string(),
+            secret: "s3cret".to_string(),
+            passphrase: "pass".to_string(),
+        };
+        let printed = format!("{:?}", auth);
+        assert!(!printed.contains("s3cret"));
+        assert!(!printed.contains("pass\""));
+    }
+}
@@ -6,9 +6,10 @@ use polymarket_pro::api::Side;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use wiremock::{MockServer, Mock, ResponseTemplate};
-use wiremock::matchers::{method, path, header};
+use wiremock::matchers::{method, path};
 
 /// Setup mock Polymarket API server
+#[allow(dead_code)]
 async fn setup_mock_api() -> MockServer {
     let mock_server = MockServer::start().await;
 
@@ -30,9 +31,9 @@ async fn setup_mock_api() -> MockServer {
 async fn test_trading_cycle_flow() {
     // Setup
     let position_tracker = Arc::new(RwLock::new(PositionTracker::new()));
-    let order_tracker = Arc::new(RwLock::new(OrderTracker::new()));
-    let trade_history = Arc::new(TradeHistory::default());
-    let stats = Arc::new(RwLock::new(TradingStats::new()));
+    let _order_tracker = Arc::new(RwLock::new(OrderTracker::new()));
+    let _trade_history = Arc::new(TradeHistory::default());
+    let _stats = Arc::new(RwLock::new(TradingStats::new()));
 
     // Test inventory skew calculation
     {
@@ -13,11 +13,13 @@ fn test_config_default() {
 
 #[test]
 fn test_config_validation() {
-    let mut config = config::Config::default();
-    // Use valid 64-char hex private key (with 0x prefix = 66 chars)
-    config.pk = "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef".to_string();
-    // Use valid 40-char hex address (with 0x prefix = 42 chars)
-    config.safe_address = "0x1234567890abcdef1234567890abcdef12345678".to_string();
+    let mut config = config::Config {
+        // Use valid 64-char hex private key (with 0x prefix = 66 chars)
+        pk: "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef".to_string(),
+        // Use valid 40-char hex address (with 0x prefix = 42 chars)
+        safe_address: "0x1234567890abcdef1234567890abcdef12345678".to_string(),
+        ..config::Config::default()
+    };
     // Set BROWSER_ADDRESS env var for validation
     std::env::set_var("BROWSER_ADDRESS", "0xabc");
     
@@ -104,7 +106,8 @@ fn test_market_maker_scenarios() {
 // Test wallet integration
 #[tokio::test]
 async fn test_wallet_signing_integration() {
-    let pk = "***REMOVED***";
+    // Use a test key (not a real private key)
+    let pk = "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef";
     let wallet = PrivateKeyWallet::from_private_key(pk, 137).unwrap();
     
     // Sign message
@@ -159,10 +162,13 @@ fn test_trading_config() {
         max_spread: 0.02,
         min_spread: 0.005,
         merge_threshold: 0.5,
+        split_budget: 0.0,
         max_hold_time: 180,
         exit_before_expiry: 120,
+        expiry_schedule: Vec::new(),
         take_profit: 0.03,
         stop_loss: 0.05,
+        exit_styles: Default::default(),
         depth_lookback: 5,
         imbalance_threshold: 0.3,
         min_price: 0.01,
@@ -172,6 +178,9 @@ fn test_trading_config() {
         price_warn_cooldown: 60,
         refresh_interval: 45,
         spread: 0.02,
+        quote_model: Default::default(),
+        avellaneda_stoikov: Default::default(),
+        strategy_mode: "buy_hold".to_string(),
     };
     
     assert_eq!(config.order_size, 10.0);